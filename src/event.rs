@@ -1,8 +1,24 @@
 //! Readiness event types.
 
-use core::fmt;
+use core::{cmp, fmt};
+use core::iter::FusedIterator;
+#[cfg(feature = "nightly")]
+use core::mem::{replace, MaybeUninit};
 use core::ops::{BitOr, BitOrAssign};
+#[cfg(feature = "nightly")]
+use core::slice;
 use core::time::Duration;
+#[cfg(feature = "std")]
+use std::iter::Chain as IterChain;
+#[cfg(feature = "std")]
+use std::vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::min_timeout;
+#[cfg(feature = "std")]
+use crate::os::Interests;
 
 /// A readiness event source that can be polled for events.
 ///
@@ -46,9 +62,9 @@ use core::time::Duration;
 ///         }
 ///     }
 ///
-///     fn poll(&mut self, event_sink: &mut ES) -> Result<(), E> {
+///     fn poll(&mut self, event_sink: &mut ES) -> Result<usize, E> {
 ///         match poll_events(event_sink) {
-///             Ok(()) => Ok(()),
+///             Ok(n_events) => Ok(n_events),
 ///             // We need explicitly call `into()` to convert our error into
 ///             // the generic error. Note that this isn't required when using
 ///             // `?` (the try operator).
@@ -56,7 +72,7 @@ use core::time::Duration;
 ///         }
 ///     }
 /// }
-/// # fn poll_events<ES>(_event_sink: &mut ES) -> Result<(), SourceError> { Ok(()) }
+/// # fn poll_events<ES>(_event_sink: &mut ES) -> Result<usize, SourceError> { Ok(0) }
 ///
 /// #[derive(Debug)]
 /// struct MyError;
@@ -73,7 +89,8 @@ use core::time::Duration;
 /// // Now we can use our event source with `MyError` as error type.
 /// let mut my_source = MyEventSource(Vec::new());
 /// let mut events = Vec::new();
-/// poll(&mut [&mut my_source], &mut events, None)
+/// poll::<_, MyError>(&mut [&mut my_source], &mut events, None)?;
+/// # Ok(())
 /// # }
 /// ```
 pub trait Source<ES, E>
@@ -106,9 +123,11 @@ pub trait Source<ES, E>
     /// poll should remain in the source and should be added to the event sink
     /// in future calls to poll.
     ///
+    /// Returns the number of events added to `event_sink`.
+    ///
     /// [`event::Sink`]: Sink
     /// [`event::Sink::capacity_left`]: Sink::capacity_left
-    fn poll(&mut self, event_sink: &mut ES) -> Result<(), E>;
+    fn poll(&mut self, event_sink: &mut ES) -> Result<usize, E>;
 
     /// A blocking poll for readiness events.
     ///
@@ -121,7 +140,7 @@ pub trait Source<ES, E>
     /// The default implementation simply calls `poll`, thus it doesn't actually
     /// block.
     #[allow(unused_variables)] // Don't want to use "_timeout" in docs.
-    fn blocking_poll(&mut self, event_sink: &mut ES, timeout: Option<Duration>) -> Result<(), E> {
+    fn blocking_poll(&mut self, event_sink: &mut ES, timeout: Option<Duration>) -> Result<usize, E> {
         self.poll(event_sink)
     }
 }
@@ -134,15 +153,233 @@ impl<S, ES, E> Source<ES, E> for &mut S
         (&**self).max_timeout()
     }
 
-    fn poll(&mut self, event_sink: &mut ES) -> Result<(), E> {
+    fn poll(&mut self, event_sink: &mut ES) -> Result<usize, E> {
         (&mut **self).poll(event_sink)
     }
 
-    fn blocking_poll(&mut self, event_sink: &mut ES, timeout: Option<Duration>) -> Result<(), E> {
+    fn blocking_poll(&mut self, event_sink: &mut ES, timeout: Option<Duration>) -> Result<usize, E> {
         (&mut **self).blocking_poll(event_sink, timeout)
     }
 }
 
+/// Event source adapter that maps the id of all events.
+///
+/// This allows the ids of multiple event sources to be mapped into disjoint
+/// ranges, without changing how the event sources produce events. The function
+/// `F` is called with the id of each event the wrapped source adds to the event
+/// sink and returns the id to use instead.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), ()> {
+/// use gaea::{event, Event, Queue, Ready, poll};
+/// use gaea::event::MapId;
+///
+/// let mut queue1 = Queue::new();
+/// queue1.add(Event::new(event::Id(0), Ready::READABLE));
+/// let mut queue2 = Queue::new();
+/// queue2.add(Event::new(event::Id(0), Ready::WRITABLE));
+///
+/// // Events from the second queue use ids starting at 1000.
+/// let mut queue2 = MapId::new(queue2, |id: event::Id| event::Id(id.0 + 1000));
+///
+/// let mut events = Vec::new();
+/// poll(&mut [&mut queue1], &mut events, None)?;
+/// poll(&mut [&mut queue2], &mut events, None)?;
+/// assert_eq!(events, vec![
+///     Event::new(event::Id(0), Ready::READABLE),
+///     Event::new(event::Id(1000), Ready::WRITABLE),
+/// ]);
+/// # Ok(())
+/// # }
+/// ```
+pub struct MapId<S, F> {
+    source: S,
+    map: F,
+}
+
+impl<S, F> MapId<S, F>
+    where F: FnMut(Id) -> Id,
+{
+    /// Create a new `MapId`, mapping the ids of all events of `source` using
+    /// `map`.
+    pub fn new(source: S, map: F) -> MapId<S, F> {
+        MapId { source, map }
+    }
+
+    /// Returns a reference to the wrapped event source.
+    pub fn get_ref(&self) -> &S {
+        &self.source
+    }
+
+    /// Returns a mutable reference to the wrapped event source.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// Returns the wrapped event source.
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+}
+
+impl<S, F, ES, E> Source<ES, E> for MapId<S, F>
+    where S: for<'a> Source<MapIdSink<'a, ES, F>, E>,
+          F: FnMut(Id) -> Id,
+          ES: Sink,
+{
+    fn max_timeout(&self) -> Option<Duration> {
+        self.source.max_timeout()
+    }
+
+    fn poll(&mut self, event_sink: &mut ES) -> Result<usize, E> {
+        let mut event_sink = MapIdSink { sink: event_sink, map: &mut self.map };
+        self.source.poll(&mut event_sink)
+    }
+
+    fn blocking_poll(&mut self, event_sink: &mut ES, timeout: Option<Duration>) -> Result<usize, E> {
+        let mut event_sink = MapIdSink { sink: event_sink, map: &mut self.map };
+        self.source.blocking_poll(&mut event_sink, timeout)
+    }
+}
+
+impl<S, F> fmt::Debug for MapId<S, F>
+    where S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MapId")
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+/// Event sink used by [`MapId`] to map the ids of the events added to the
+/// wrapped event sink.
+pub struct MapIdSink<'a, ES, F> {
+    sink: &'a mut ES,
+    map: &'a mut F,
+}
+
+impl<'a, ES, F> Sink for MapIdSink<'a, ES, F>
+    where ES: Sink,
+          F: FnMut(Id) -> Id,
+{
+    fn capacity_left(&self) -> Capacity {
+        self.sink.capacity_left()
+    }
+
+    fn add(&mut self, event: Event) {
+        let id = (self.map)(event.id);
+        self.sink.add(Event { id, ..event });
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.sink.reserve(additional)
+    }
+}
+
+impl<'a, ES, F> fmt::Debug for MapIdSink<'a, ES, F>
+    where ES: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MapIdSink")
+            .field("sink", &self.sink)
+            .finish()
+    }
+}
+
+/// Event source adapter that polls a primary event source and falls back to a
+/// second event source.
+///
+/// The second event source is only polled if the first didn't add any events,
+/// this can be used to prioritise one event source over another. For example
+/// to prioritise network I/O, using [`OsQueue`], over internal messages, using
+/// a [`Queue`].
+///
+/// The maximum timeout is the minimum of the two event sources. In a blocking
+/// poll the first event source is used for blocking.
+///
+/// [`OsQueue`]: crate::os::OsQueue
+/// [`Queue`]: crate::Queue
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), ()> {
+/// use gaea::{event, Event, Queue, Ready, poll};
+/// use gaea::event::Chain;
+///
+/// let mut chain = Chain::new(Queue::new(), Queue::new());
+/// let event1 = Event::new(event::Id(0), Ready::READABLE);
+/// chain.first_mut().add(event1);
+/// let event2 = Event::new(event::Id(1), Ready::READABLE);
+/// chain.second_mut().add(event2);
+///
+/// // The second queue is only polled if the first is empty.
+/// let mut events = Vec::new();
+/// poll(&mut [&mut chain], &mut events, None)?;
+/// assert_eq!(events, vec![event1]);
+/// poll(&mut [&mut chain], &mut events, None)?;
+/// assert_eq!(events, vec![event1, event2]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Chain<S1, S2> {
+    first: S1,
+    second: S2,
+}
+
+impl<S1, S2> Chain<S1, S2> {
+    /// Create a new `Chain`, polling `first` and then `second` if `first` added
+    /// no events.
+    pub fn new(first: S1, second: S2) -> Chain<S1, S2> {
+        Chain { first, second }
+    }
+
+    /// Returns a mutable reference to the first event source.
+    pub fn first_mut(&mut self) -> &mut S1 {
+        &mut self.first
+    }
+
+    /// Returns a mutable reference to the second event source.
+    pub fn second_mut(&mut self) -> &mut S2 {
+        &mut self.second
+    }
+
+    /// Returns the wrapped event sources.
+    pub fn into_inner(self) -> (S1, S2) {
+        (self.first, self.second)
+    }
+}
+
+impl<S1, S2, ES, E> Source<ES, E> for Chain<S1, S2>
+    where S1: Source<ES, E>,
+          S2: Source<ES, E>,
+          ES: Sink,
+{
+    fn max_timeout(&self) -> Option<Duration> {
+        min_timeout(self.first.max_timeout(), self.second.max_timeout())
+    }
+
+    fn poll(&mut self, event_sink: &mut ES) -> Result<usize, E> {
+        match self.first.poll(event_sink)? {
+            0 => self.second.poll(event_sink),
+            n_events => Ok(n_events),
+        }
+    }
+
+    fn blocking_poll(&mut self, event_sink: &mut ES, timeout: Option<Duration>) -> Result<usize, E> {
+        // Don't block if the second source has events ready.
+        let timeout = min_timeout(timeout, self.second.max_timeout());
+        match self.first.blocking_poll(event_sink, timeout)? {
+            0 => self.second.poll(event_sink),
+            n_events => Ok(n_events),
+        }
+    }
+}
+
 /// An event sink to which events can be added.
 ///
 /// `event::Sink` is passed as an argument to [`poll`] and will be used to
@@ -162,108 +399,809 @@ impl<S, ES, E> Source<ES, E> for &mut S
 /// intermediate storage used to receive events only to mark processes as
 /// runnable and run them later.
 ///
-/// Using a trait removes the need for this intermediate storage and allows
-/// users to direct mark processes as runnable inside there own data structure.
+/// Using a trait removes the need for this intermediate storage and allows
+/// users to direct mark processes as runnable inside there own data structure.
+///
+/// # Examples
+///
+/// An implementation of `event::Sink` for an array.
+///
+/// ```
+/// # fn main() -> Result<(), ()> {
+/// use gaea::{event, Event, Queue, Ready, poll};
+///
+/// const EVENTS_SIZE: usize = 32;
+///
+/// /// Our `event::Sink` implementation.
+/// struct MyEvents([Option<Event>; EVENTS_SIZE]);
+///
+/// impl event::Sink for MyEvents {
+///     fn capacity_left(&self) -> event::Capacity {
+///         let limit = self.0.iter().position(Option::is_some).unwrap_or(EVENTS_SIZE);
+///         event::Capacity::Limited(limit)
+///     }
+///
+///     fn add(&mut self, event: Event) {
+///         let index = self.0.iter().position(Option::is_none).unwrap();
+///         self.0[index] = Some(event);
+///     }
+/// }
+///
+/// // An event source, with some events.
+/// let mut queue = Queue::new();
+/// let event1 = Event::new(event::Id(0), Ready::READABLE);
+/// queue.add(event1);
+/// let event2 = Event::new(event::Id(1), Ready::WRITABLE);
+/// queue.add(event2);
+///
+/// // Poll the source.
+/// let mut events = MyEvents([None; EVENTS_SIZE]);
+/// poll(&mut [&mut queue], &mut events, None)?;
+/// assert_eq!(events.0[0], Some(event1));
+/// assert_eq!(events.0[1], Some(event2));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Trait objects
+///
+/// `Sink` can be used as trait object, both `&mut dyn Sink` and `Box<dyn
+/// Sink>` implement `Sink`. This allows the type of event sink to be decided
+/// at runtime.
+///
+/// ```
+/// # fn main() -> Result<(), ()> {
+/// use gaea::{event, Event, Queue, Ready, poll};
+///
+/// let mut queue = Queue::new();
+/// queue.add(Event::new(event::Id(0), Ready::READABLE));
+///
+/// let mut events = Vec::new();
+/// let mut event_sink: &mut dyn event::Sink = &mut events;
+/// poll(&mut [&mut queue], &mut event_sink, None)?;
+/// assert_eq!(events, vec![Event::new(event::Id(0), Ready::READABLE)]);
+/// # Ok(())
+/// # }
+/// ```
+pub trait Sink {
+    /// Capacity left in the event sink.
+    ///
+    /// This must return the available capacity left, **not total capacity**.
+    ///
+    /// # Notes
+    ///
+    /// If this returns [`Capacity::Limited`] and the capacity left is incorrect
+    /// it may cause missing events.
+    fn capacity_left(&self) -> Capacity;
+
+    /// Add a single event.
+    fn add(&mut self, event: Event);
+
+    /// Reserve capacity for at least `additional` more events.
+    ///
+    /// Event sources call this before adding multiple events, allowing a
+    /// growable event sink to allocate once, rather than growing multiple
+    /// times while the events are added. The default implementation does
+    /// nothing.
+    fn reserve(&mut self, _additional: usize) {}
+
+    /// Extend with multiple events.
+    ///
+    /// Not available on trait objects, where the events are added one by one
+    /// using [`add`].
+    ///
+    /// [`add`]: Sink::add
+    fn extend<I>(&mut self, events: I)
+        where I: Iterator<Item = Event>,
+              Self: Sized,
+    {
+        for event in events {
+            self.add(event);
+        }
+    }
+}
+
+impl<'a, ES> Sink for &'a mut ES
+    where ES: Sink,
+{
+    fn capacity_left(&self) -> Capacity {
+        (&**self).capacity_left()
+    }
+
+    fn add(&mut self, event: Event) {
+        (&mut **self).add(event)
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        (**self).reserve(additional)
+    }
+
+    fn extend<I>(&mut self, events: I)
+        where I: Iterator<Item = Event>,
+    {
+        (&mut **self).extend(events)
+    }
+}
+
+impl Sink for &mut dyn Sink {
+    fn capacity_left(&self) -> Capacity {
+        (**self).capacity_left()
+    }
+
+    fn add(&mut self, event: Event) {
+        (**self).add(event)
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        (**self).reserve(additional)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Sink for Box<dyn Sink> {
+    fn capacity_left(&self) -> Capacity {
+        (**self).capacity_left()
+    }
+
+    fn add(&mut self, event: Event) {
+        (**self).add(event)
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        (**self).reserve(additional)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Sink for Vec<Event> {
+    fn capacity_left(&self) -> Capacity {
+        Capacity::Growable
+    }
+
+    fn add(&mut self, event: Event) {
+        self.push(event);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        Vec::reserve(self, additional)
+    }
+
+    fn extend<I>(&mut self, events: I)
+        where I: Iterator<Item = Event>,
+    {
+        <Self as Extend<Event>>::extend(self, events);
+    }
+}
+
+/// Fan-out event sink, adding all events to both sinks.
+///
+/// The capacity left is the minimum of the capacity left in both sinks, this
+/// way no events are lost in either sink.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), ()> {
+/// use gaea::{event, Event, Queue, Ready, poll};
+///
+/// let mut queue = Queue::new();
+/// let event = Event::new(event::Id(0), Ready::READABLE);
+/// queue.add(event);
+///
+/// // Record all events in a second event sink.
+/// let mut events = (Vec::new(), Vec::new());
+/// poll(&mut [&mut queue], &mut events, None)?;
+/// assert_eq!(events.0, vec![event]);
+/// assert_eq!(events.1, vec![event]);
+/// # Ok(())
+/// # }
+/// ```
+impl<A, B> Sink for (A, B)
+    where A: Sink,
+          B: Sink,
+{
+    fn capacity_left(&self) -> Capacity {
+        cmp::min(self.0.capacity_left(), self.1.capacity_left())
+    }
+
+    fn add(&mut self, event: Event) {
+        self.0.add(event);
+        self.1.add(event);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional);
+        self.1.reserve(additional);
+    }
+}
+
+/// A fixed capacity, array backed [event sink].
+///
+/// This event sink doesn't allocate, making it usable in environments where
+/// allocating is not possible or not preferred. It can hold at most `N` events,
+/// any events added after that are dropped. However event sources respect
+/// the [capacity left] so this shouldn't happen when using [`poll`].
+///
+/// [event sink]: Sink
+/// [capacity left]: Sink::capacity_left
+/// [`poll`]: crate::poll
+///
+/// # Notes
+///
+/// This requires the `nightly` feature as it uses const generics.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), ()> {
+/// use gaea::{event, Event, Queue, Ready, poll};
+/// use gaea::event::ArrayEvents;
+///
+/// let mut queue = Queue::new();
+/// let mut events: ArrayEvents<2> = ArrayEvents::new();
+///
+/// for id in 0..3 {
+///     queue.add(Event::new(event::Id(id), Ready::READABLE));
+/// }
+///
+/// // Only two events fit in the event sink.
+/// poll(&mut [&mut queue], &mut events, None)?;
+/// assert_eq!(events.len(), 2);
+///
+/// for event in events.drain() {
+///     println!("Got event: {:?}", event);
+/// }
+/// assert!(events.is_empty());
+///
+/// // The remaining event is returned in the next call to poll.
+/// poll(&mut [&mut queue], &mut events, None)?;
+/// assert_eq!(events.as_slice(), &[Event::new(event::Id(2), Ready::READABLE)]);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "nightly")]
+pub struct ArrayEvents<const N: usize> {
+    /// Only the first `length` events are initialised.
+    events: [MaybeUninit<Event>; N],
+    length: usize,
+}
+
+#[cfg(feature = "nightly")]
+impl<const N: usize> ArrayEvents<N> {
+    /// Create a new empty `ArrayEvents`.
+    pub const fn new() -> ArrayEvents<N> {
+        ArrayEvents {
+            events: [MaybeUninit::uninit(); N],
+            length: 0,
+        }
+    }
+
+    /// Returns the number of events.
+    pub const fn len(&self) -> usize {
+        self.length
+    }
+
+    /// Returns true if there are no events.
+    pub const fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Returns the events as slice.
+    pub fn as_slice(&self) -> &[Event] {
+        // Safety: the first `length` events are initialised and
+        // `MaybeUninit<Event>` has the same layout as `Event`.
+        unsafe { slice::from_raw_parts(self.events.as_ptr() as *const Event, self.length) }
+    }
+
+    /// Remove all events.
+    pub fn clear(&mut self) {
+        // `Event` is `Copy`, so there is nothing to drop.
+        self.length = 0;
+    }
+
+    /// Remove all events, returning them in an iterator.
+    pub fn drain(&mut self) -> ArrayEventsDrain {
+        let length = replace(&mut self.length, 0);
+        ArrayEventsDrain {
+            events: self.events[..length].iter(),
+        }
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<const N: usize> Sink for ArrayEvents<N> {
+    fn capacity_left(&self) -> Capacity {
+        Capacity::Limited(N - self.length)
+    }
+
+    fn add(&mut self, event: Event) {
+        if let Some(slot) = self.events.get_mut(self.length) {
+            *slot = MaybeUninit::new(event);
+            self.length += 1;
+        }
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<const N: usize> Default for ArrayEvents<N> {
+    fn default() -> ArrayEvents<N> {
+        ArrayEvents::new()
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<const N: usize> fmt::Debug for ArrayEvents<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.as_slice()).finish()
+    }
+}
+
+/// Draining iterator for [`ArrayEvents`].
+///
+/// Created by [`ArrayEvents::drain`].
+#[cfg(feature = "nightly")]
+#[derive(Debug)]
+pub struct ArrayEventsDrain<'a> {
+    events: slice::Iter<'a, MaybeUninit<Event>>,
+}
+
+#[cfg(feature = "nightly")]
+impl<'a> Iterator for ArrayEventsDrain<'a> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Safety: `ArrayEvents::drain` only creates an iterator over the
+        // initialised events.
+        self.events.next().map(|event| unsafe { event.assume_init() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.events.size_hint()
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<'a> ExactSizeIterator for ArrayEventsDrain<'a> {
+    fn len(&self) -> usize {
+        self.events.len()
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<'a> FusedIterator for ArrayEventsDrain<'a> {}
+
+/// Event sink adapter that only adds events that pass a predicate.
+///
+/// This allows events to be filtered while polling, rather than filtering them
+/// afterwards. Events for which the predicate `F` returns `false` are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), ()> {
+/// use gaea::{event, Event, Queue, Ready, poll};
+/// use gaea::event::FilterSink;
+///
+/// let mut queue = Queue::new();
+/// let event1 = Event::new(event::Id(0), Ready::READABLE);
+/// queue.add(event1);
+/// let event2 = Event::new(event::Id(1), Ready::WRITABLE);
+/// queue.add(event2);
+///
+/// // Only keep readable events.
+/// let mut events = FilterSink::new(Vec::new(), |event: &Event| event.readiness().is_readable());
+/// poll(&mut [&mut queue], &mut events, None)?;
+/// assert_eq!(events.into_inner(), vec![event1]);
+/// # Ok(())
+/// # }
+/// ```
+pub struct FilterSink<S, F> {
+    sink: S,
+    predicate: F,
+}
+
+impl<S, F> FilterSink<S, F>
+    where S: Sink,
+          F: FnMut(&Event) -> bool,
+{
+    /// Create a new `FilterSink`, only adding events to `sink` for which
+    /// `predicate` returns `true`.
+    pub fn new(sink: S, predicate: F) -> FilterSink<S, F> {
+        FilterSink { sink, predicate }
+    }
+
+    /// Returns a reference to the wrapped event sink.
+    pub fn get_ref(&self) -> &S {
+        &self.sink
+    }
+
+    /// Returns a mutable reference to the wrapped event sink.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    /// Returns the wrapped event sink.
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+}
+
+impl<S, F> Sink for FilterSink<S, F>
+    where S: Sink,
+          F: FnMut(&Event) -> bool,
+{
+    fn capacity_left(&self) -> Capacity {
+        self.sink.capacity_left()
+    }
+
+    fn add(&mut self, event: Event) {
+        if (self.predicate)(&event) {
+            self.sink.add(event);
+        }
+    }
+}
+
+impl<S, F> fmt::Debug for FilterSink<S, F>
+    where S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FilterSink")
+            .field("sink", &self.sink)
+            .finish()
+    }
+}
+
+/// Event sink that calls a function for each event added.
+///
+/// This allows events to be handled as they are added, without collecting
+/// them first. The capacity is always [growable]. Also see [`poll_each`].
+///
+/// [growable]: Capacity::Growable
+/// [`poll_each`]: crate::poll_each
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), ()> {
+/// use gaea::{event, Event, Queue, Ready, poll};
+/// use gaea::event::FnSink;
+///
+/// let mut queue = Queue::new();
+/// queue.add(Event::new(event::Id(0), Ready::READABLE));
+///
+/// let mut readable = 0;
+/// poll(&mut [&mut queue], &mut FnSink::new(|event: Event| {
+///     if event.readiness().is_readable() {
+///         readable += 1;
+///     }
+/// }), None)?;
+/// assert_eq!(readable, 1);
+/// # Ok(())
+/// # }
+/// ```
+pub struct FnSink<F> {
+    f: F,
+}
+
+impl<F> FnSink<F>
+    where F: FnMut(Event),
+{
+    /// Create a new `FnSink`, calling `f` for each event added.
+    pub fn new(f: F) -> FnSink<F> {
+        FnSink { f }
+    }
+
+    /// Returns the wrapped function.
+    pub fn into_inner(self) -> F {
+        self.f
+    }
+}
+
+impl<F> Sink for FnSink<F>
+    where F: FnMut(Event),
+{
+    fn capacity_left(&self) -> Capacity {
+        Capacity::Growable
+    }
+
+    fn add(&mut self, event: Event) {
+        (self.f)(event)
+    }
+}
+
+impl<F> fmt::Debug for FnSink<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("FnSink")
+    }
+}
+
+/// Event sink adapter that limits the number of events added to the wrapped
+/// event sink.
+///
+/// The capacity left is the minimum of the limit left and the capacity left in
+/// the wrapped event sink. This is used by [`poll_fair`] and
+/// [`poll_with_budget`] to limit the number of events a single event source can
+/// add.
+///
+/// [`poll_fair`]: crate::poll_fair
+/// [`poll_with_budget`]: crate::poll_with_budget
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), ()> {
+/// use gaea::{event, Event, Queue, Ready, poll};
+/// use gaea::event::LimitSink;
+///
+/// let mut queue = Queue::new();
+/// let event1 = Event::new(event::Id(0), Ready::READABLE);
+/// queue.add(event1);
+/// let event2 = Event::new(event::Id(1), Ready::WRITABLE);
+/// queue.add(event2);
+///
+/// // Only add a single event.
+/// let mut events = Vec::new();
+/// poll(&mut [&mut queue], &mut LimitSink::new(&mut events, 1), None)?;
+/// assert_eq!(events, vec![event1]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct LimitSink<'a, ES> {
+    sink: &'a mut ES,
+    left: usize,
+}
+
+impl<'a, ES> LimitSink<'a, ES>
+    where ES: Sink,
+{
+    /// Create a new `LimitSink`, adding at most `limit` events to `sink`.
+    pub fn new(sink: &'a mut ES, limit: usize) -> LimitSink<'a, ES> {
+        LimitSink { sink, left: limit }
+    }
+
+    /// Returns the number of events that can still be added before the limit
+    /// is reached.
+    pub fn limit_left(&self) -> usize {
+        self.left
+    }
+}
+
+impl<'a, ES> Sink for LimitSink<'a, ES>
+    where ES: Sink,
+{
+    fn capacity_left(&self) -> Capacity {
+        Capacity::Limited(self.sink.capacity_left().min(self.left))
+    }
+
+    fn add(&mut self, event: Event) {
+        if self.left != 0 {
+            self.left -= 1;
+            self.sink.add(event);
+        }
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.sink.reserve(additional.min(self.left))
+    }
+}
+
+/// Event sink backed by a vector, with a hard maximum number of events.
+///
+/// The first field is the vector of events, the second the maximum number of
+/// events in it. Unlike the event sink implementation for `Vec<Event>` the
+/// capacity left is `Limited` to the maximum minus the number of events already
+/// in the vector, so [`poll`] stops adding events once the maximum is reached.
+/// This bounds the memory used, without using a fixed size array.
+///
+/// Events that don't fit remain in the event source and are returned by the
+/// next call to `poll`. Note that for edge triggered registrations of
+/// [`OsQueue`] this only holds if the OS reports the event again.
+///
+/// Events added beyond the maximum, e.g. by calling [`Sink::add`] directly,
+/// are dropped.
+///
+/// [`poll`]: crate::poll
+/// [`OsQueue`]: crate::os::OsQueue
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), ()> {
+/// use gaea::{event, Event, Queue, Ready, poll};
+/// use gaea::event::CappedEvents;
+///
+/// let mut queue = Queue::new();
+/// let event = Event::new(event::Id(0), Ready::READABLE);
+/// queue.add(event);
+/// queue.add(event);
+/// queue.add(event);
+///
+/// // Never hold more than two events.
+/// let mut events = CappedEvents::new(2);
+/// poll(&mut [&mut queue], &mut events, None)?;
+/// assert_eq!(events.0, vec![event, event]);
+///
+/// // The remaining event is returned once there is room.
+/// events.0.clear();
+/// poll(&mut [&mut queue], &mut events, None)?;
+/// assert_eq!(events.0, vec![event]);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CappedEvents(pub Vec<Event>, pub usize);
+
+#[cfg(feature = "std")]
+impl CappedEvents {
+    /// Create a new, empty, `CappedEvents` holding at most `max` events.
+    pub fn new(max: usize) -> CappedEvents {
+        CappedEvents(Vec::new(), max)
+    }
+
+    /// Returns the number of events that can still be added.
+    fn left(&self) -> usize {
+        self.1.saturating_sub(self.0.len())
+    }
+}
+
+#[cfg(feature = "std")]
+impl Sink for CappedEvents {
+    fn capacity_left(&self) -> Capacity {
+        Capacity::Limited(self.left())
+    }
+
+    fn add(&mut self, event: Event) {
+        if self.left() != 0 {
+            self.0.push(event);
+        }
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional.min(self.left()))
+    }
+
+    fn extend<I>(&mut self, events: I)
+        where I: Iterator<Item = Event>,
+    {
+        let left = self.left();
+        <Vec<Event> as Extend<Event>>::extend(&mut self.0, events.take(left));
+    }
+}
+
+/// Event sink that orders events with an error ahead of all other events.
+///
+/// Events with [`ERROR`] or [`HUP`] readiness are added to a separate bucket,
+/// which [`drain`] returns before the remaining events. This allows broken
+/// connections to be torn down before spending time on reading or writing
+/// when handling a large number of events. Within each bucket the events keep
+/// the order in which they were added.
 ///
-/// # Examples
+/// [`ERROR`]: Ready::ERROR
+/// [`HUP`]: Ready::HUP
+/// [`drain`]: PrioritySink::drain
 ///
-/// An implementation of `event::Sink` for an array.
+/// # Examples
 ///
 /// ```
 /// # fn main() -> Result<(), ()> {
 /// use gaea::{event, Event, Queue, Ready, poll};
+/// use gaea::event::PrioritySink;
 ///
-/// const EVENTS_SIZE: usize = 32;
-///
-/// /// Our `event::Sink` implementation.
-/// struct MyEvents([Option<Event>; EVENTS_SIZE]);
-///
-/// impl event::Sink for MyEvents {
-///     fn capacity_left(&self) -> event::Capacity {
-///         let limit = self.0.iter().position(Option::is_some).unwrap_or(EVENTS_SIZE);
-///         event::Capacity::Limited(limit)
-///     }
-///
-///     fn add(&mut self, event: Event) {
-///         let index = self.0.iter().position(Option::is_none).unwrap();
-///         self.0[index] = Some(event);
-///     }
-/// }
-///
-/// // An event source, with some events.
 /// let mut queue = Queue::new();
 /// let event1 = Event::new(event::Id(0), Ready::READABLE);
 /// queue.add(event1);
-/// let event2 = Event::new(event::Id(1), Ready::WRITABLE);
+/// let event2 = Event::new(event::Id(1), Ready::ERROR);
 /// queue.add(event2);
 ///
-/// // Poll the source.
-/// let mut events = MyEvents([None; EVENTS_SIZE]);
+/// let mut events = PrioritySink::new();
 /// poll(&mut [&mut queue], &mut events, None)?;
-/// assert_eq!(events.0[0], Some(event1));
-/// assert_eq!(events.0[1], Some(event2));
+///
+/// // The error event is returned first.
+/// let events: Vec<Event> = events.drain().collect();
+/// assert_eq!(events, vec![event2, event1]);
 /// # Ok(())
 /// # }
 /// ```
-pub trait Sink {
-    /// Capacity left in the event sink.
-    ///
-    /// This must return the available capacity left, **not total capacity**.
-    ///
-    /// # Notes
-    ///
-    /// If this returns [`Capacity::Limited`] and the capacity left is incorrect
-    /// it may cause missing events.
-    fn capacity_left(&self) -> Capacity;
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default)]
+pub struct PrioritySink {
+    /// Events with `ERROR` or `HUP` readiness.
+    priority: Vec<Event>,
+    /// All other events.
+    normal: Vec<Event>,
+}
 
-    /// Add a single event.
-    fn add(&mut self, event: Event);
+#[cfg(feature = "std")]
+impl PrioritySink {
+    /// Readiness that gives an event priority.
+    #[cfg(unix)]
+    const PRIORITY: Ready = Ready(ERROR | HUP);
+    #[cfg(not(unix))]
+    const PRIORITY: Ready = Ready(ERROR);
 
-    /// Extend with multiple events.
-    fn extend<I>(&mut self, events: I)
-        where I: Iterator<Item = Event>,
-    {
-        for event in events {
-            self.add(event);
+    /// Create a new empty `PrioritySink`.
+    pub fn new() -> PrioritySink {
+        PrioritySink {
+            priority: Vec::new(),
+            normal: Vec::new(),
         }
     }
-}
 
-impl<'a, ES> Sink for &'a mut ES
-    where ES: Sink,
-{
-    fn capacity_left(&self) -> Capacity {
-        (&**self).capacity_left()
+    /// Returns the number of events.
+    pub fn len(&self) -> usize {
+        self.priority.len() + self.normal.len()
     }
 
-    fn add(&mut self, event: Event) {
-        (&mut **self).add(event)
+    /// Returns true if there are no events.
+    pub fn is_empty(&self) -> bool {
+        self.priority.is_empty() && self.normal.is_empty()
     }
 
-    fn extend<I>(&mut self, events: I)
-        where I: Iterator<Item = Event>,
-    {
-        (&mut **self).extend(events)
+    /// Remove all events.
+    pub fn clear(&mut self) {
+        self.priority.clear();
+        self.normal.clear();
+    }
+
+    /// Remove all events, returning them in an iterator. Events with an error
+    /// are returned first.
+    pub fn drain(&mut self) -> PrioritySinkDrain<'_> {
+        PrioritySinkDrain {
+            events: self.priority.drain(..).chain(self.normal.drain(..)),
+        }
     }
 }
 
 #[cfg(feature = "std")]
-impl Sink for Vec<Event> {
+impl Sink for PrioritySink {
     fn capacity_left(&self) -> Capacity {
         Capacity::Growable
     }
 
     fn add(&mut self, event: Event) {
-        self.push(event);
+        if event.readiness().contains_any(PrioritySink::PRIORITY) {
+            self.priority.push(event);
+        } else {
+            self.normal.push(event);
+        }
     }
 
-    fn extend<I>(&mut self, events: I)
-        where I: Iterator<Item = Event>,
-    {
-        <Self as Extend<Event>>::extend(self, events);
+    fn reserve(&mut self, additional: usize) {
+        // Most events are expected to be normal events.
+        self.normal.reserve(additional)
+    }
+}
+
+/// Draining iterator for [`PrioritySink`].
+///
+/// Created by [`PrioritySink::drain`].
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct PrioritySinkDrain<'a> {
+    events: IterChain<vec::Drain<'a, Event>, vec::Drain<'a, Event>>,
+}
+
+#[cfg(feature = "std")]
+impl<'a> Iterator for PrioritySinkDrain<'a> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.events.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.events.size_hint()
     }
 }
 
+#[cfg(feature = "std")]
+impl<'a> ExactSizeIterator for PrioritySinkDrain<'a> {}
+
+#[cfg(feature = "std")]
+impl<'a> FusedIterator for PrioritySinkDrain<'a> {}
+
 /// The capacity left in the [event sink].
 ///
 /// If the event source can grow it should use `Growable`. If there is some kind
@@ -355,16 +1293,85 @@ impl Capacity {
 /// assert_eq!(my_event.id(), event::Id(0));
 /// assert_eq!(my_event.readiness(), Ready::READABLE | Ready::WRITABLE);
 /// ```
+///
+/// # User data
+///
+/// Besides the id an `Event` can also carry a small user data payload, see
+/// [`Event::with_data`]. Note that not all event sources support this, for
+/// example events returned by [`OsQueue`] always have a data of `0`, as the
+/// space available in the OS events is used by the id. [`Queue`] does support
+/// it.
+///
+/// [`OsQueue`]: crate::os::OsQueue
+/// [`Queue`]: crate::Queue
+///
+/// # Error code
+///
+/// Events with [error readiness] may carry the OS error code (`errno`) of the
+/// error, see [`Event::error`]. This saves a system call to retrieve the error
+/// from the handle. Currently only [`OsQueue`] provides the error code. On
+/// platforms that use kqueue the error is returned by the OS, on Linux it's
+/// only provided if [`OsQueue::set_take_errors`] is enabled, otherwise the
+/// error must be retrieved from the handle itself, e.g. using
+/// [`TcpStream::take_error`].
+///
+/// [error readiness]: Ready::ERROR
+/// [`OsQueue::set_take_errors`]: crate::os::OsQueue::set_take_errors
+/// [`TcpStream::take_error`]: crate::net::TcpStream::take_error
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Event {
     id: Id,
     readiness: Ready,
+    data: u64,
+    /// OS error code, if any.
+    error: Option<i32>,
 }
 
 impl Event {
     /// Creates a new `Event` containing `id` and `readiness`.
+    ///
+    /// The user data of the event will be `0`.
     pub const fn new(id: Id, readiness: Ready) -> Event {
-        Event { id, readiness }
+        Event::with_data(id, readiness, 0)
+    }
+
+    /// Creates a new `Event` containing `id`, `readiness` and user `data`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gaea::{event, Event, Ready};
+    ///
+    /// let my_event = Event::with_data(event::Id(0), Ready::READABLE, 123);
+    ///
+    /// assert_eq!(my_event.id(), event::Id(0));
+    /// assert_eq!(my_event.readiness(), Ready::READABLE);
+    /// assert_eq!(my_event.data(), 123);
+    /// ```
+    pub const fn with_data(id: Id, readiness: Ready, data: u64) -> Event {
+        Event { id, readiness, data, error: None }
+    }
+
+    /// Creates a new `Event` containing `id`, `readiness` and an OS `error`
+    /// code.
+    ///
+    /// An `error` of `0` means no error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gaea::{event, Event, Ready};
+    ///
+    /// let my_event = Event::with_error(event::Id(0), Ready::ERROR, 104);
+    ///
+    /// assert_eq!(my_event.id(), event::Id(0));
+    /// assert_eq!(my_event.readiness(), Ready::ERROR);
+    /// assert_eq!(my_event.error(), Some(104));
+    /// ```
+    pub fn with_error(id: Id, readiness: Ready, error: i32) -> Event {
+        let error = if error != 0 { Some(error) } else { None };
+        Event { id, readiness, data: 0, error }
     }
 
     /// Returns the event's id.
@@ -376,6 +1383,47 @@ impl Event {
     pub const fn readiness(&self) -> Ready {
         self.readiness
     }
+
+    /// Returns the event's user data.
+    pub const fn data(&self) -> u64 {
+        self.data
+    }
+
+    /// Returns the OS error code (`errno`) of the event, if any.
+    ///
+    /// The error code can be converted into an `io::Error` using
+    /// [`io::Error::from_raw_os_error`].
+    ///
+    /// [`io::Error::from_raw_os_error`]: std::io::Error::from_raw_os_error
+    pub const fn error(&self) -> Option<i32> {
+        self.error
+    }
+}
+
+/// Formats the event's id and readiness, and the user data and error if set.
+///
+/// # Examples
+///
+/// ```
+/// use gaea::{event, Event, Ready};
+///
+/// let event = Event::new(event::Id(3), Ready::READABLE | Ready::WRITABLE);
+/// assert_eq!(event.to_string(), "Event { id: 3, readiness: READABLE | WRITABLE }");
+///
+/// let event = Event::with_data(event::Id(3), Ready::TIMER, 123);
+/// assert_eq!(event.to_string(), "Event { id: 3, readiness: TIMER, data: 123 }");
+/// ```
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Event {{ id: {}, readiness: {}", self.id, self.readiness)?;
+        if self.data != 0 {
+            write!(f, ", data: {}", self.data)?;
+        }
+        if let Some(error) = self.error() {
+            write!(f, ", error: {}", error)?;
+        }
+        f.write_str(" }")
+    }
 }
 
 /// Identifier of an event.
@@ -394,6 +1442,7 @@ impl Event {
 /// the same connection. The `Id` is effectively opaque to any readiness event
 /// sources.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(transparent)]
 pub struct Id(pub usize);
 
@@ -415,6 +1464,92 @@ impl fmt::Display for Id {
     }
 }
 
+/// Generator of unique [`Id`]s.
+///
+/// Hands out monotonically increasing ids, reusing ids that are [freed] first.
+/// Ids below a certain value can be reserved, for example for an [`Awakener`],
+/// using [`IdGenerator::reserve`].
+///
+/// [freed]: IdGenerator::free
+/// [`Awakener`]: crate::os::Awakener
+///
+/// # Examples
+///
+/// ```
+/// use gaea::event::{self, IdGenerator};
+///
+/// // Reserve id 0 for internal use.
+/// let mut ids = IdGenerator::reserve(1);
+///
+/// let id1 = ids.next();
+/// let id2 = ids.next();
+/// assert_eq!(id1, event::Id(1));
+/// assert_eq!(id2, event::Id(2));
+///
+/// // Once an id is no longer used it can be freed, after which it will be
+/// // reused.
+/// ids.free(id1);
+/// assert_eq!(ids.next(), event::Id(1));
+/// assert_eq!(ids.next(), event::Id(3));
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+pub struct IdGenerator {
+    /// Next id to hand out, if the free list is empty.
+    next: usize,
+    /// First id not reserved.
+    first: usize,
+    /// Ids freed and not yet handed out again.
+    free: Vec<Id>,
+}
+
+#[cfg(feature = "std")]
+impl IdGenerator {
+    /// Create a new generator, starting at `Id(0)`.
+    pub fn new() -> IdGenerator {
+        IdGenerator::reserve(0)
+    }
+
+    /// Create a new generator that reserves all ids below `n`, i.e. the first
+    /// id handed out is `Id(n)`.
+    pub fn reserve(n: usize) -> IdGenerator {
+        IdGenerator {
+            next: n,
+            first: n,
+            free: Vec::new(),
+        }
+    }
+
+    /// Get the next unique id.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if all ids are in use.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Id {
+        if let Some(id) = self.free.pop() {
+            return id;
+        }
+        let id = Id(self.next);
+        self.next = self.next.checked_add(1).expect("ran out of event ids");
+        id
+    }
+
+    /// Free `id`, allowing it to be handed out again.
+    ///
+    /// Reserved ids and ids not handed out by this generator are ignored.
+    ///
+    /// # Notes
+    ///
+    /// Freeing the same id twice, without it being handed out in between,
+    /// results in the id being handed out twice.
+    pub fn free(&mut self, id: Id) {
+        if id.0 >= self.first && id.0 < self.next {
+            self.free.push(id);
+        }
+    }
+}
+
 /// A set of readiness event kinds.
 ///
 /// `Ready` is a set of operation descriptors indicating which kind of operation
@@ -440,6 +1575,7 @@ impl fmt::Display for Id {
 /// assert!(!ready.is_error());
 /// ```
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(transparent)]
 pub struct Ready(u8);
 
@@ -449,6 +1585,10 @@ const ERROR: u8 = 1 << 2;
 const TIMER: u8 = 1 << 3;
 #[cfg(unix)]
 const HUP: u8 = 1 << 4;
+#[cfg(unix)]
+const PRIORITY: u8 = 1 << 5;
+#[cfg(unix)]
+const READ_HUP: u8 = 1 << 6;
 
 impl Ready {
     /// Empty set.
@@ -470,12 +1610,80 @@ impl Ready {
     #[cfg(unix)]
     pub const HUP: Ready = Ready(HUP);
 
+    /// Read hup readiness, this signal is Unix specific.
+    ///
+    /// This is set when the peer closed its writing side of the connection
+    /// (i.e. a half-close), while writing might still be possible. Different
+    /// to [`Ready::HUP`] which is set when both sides are closed.
+    #[cfg(unix)]
+    pub const READ_HUP: Ready = Ready(READ_HUP);
+
+    /// Priority readiness, this signal is Unix specific.
+    ///
+    /// This is set when urgent data is available, e.g. out-of-band data on a
+    /// TCP socket. Only set when registered with [`Interests::PRIORITY`].
+    ///
+    /// [`Interests::PRIORITY`]: crate::os::Interests::PRIORITY
+    #[cfg(unix)]
+    pub const PRIORITY: Ready = Ready(PRIORITY);
+
     /// Whether or not all flags in `other` are contained within `self`.
     #[inline]
     pub const fn contains(self, other: Ready) -> bool {
         (self.0 & other.0) == other.0
     }
 
+    /// Whether or not any flag in `other` is contained within `self`.
+    ///
+    /// This is different from [`contains`], which checks if **all** flags in
+    /// `other` are contained within `self`.
+    ///
+    /// [`contains`]: Ready::contains
+    #[inline]
+    pub const fn contains_any(self, other: Ready) -> bool {
+        (self.0 & other.0) != 0
+    }
+
+    /// Remove all flags in `other` from `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gaea::Ready;
+    ///
+    /// let mut ready = Ready::READABLE | Ready::WRITABLE;
+    /// ready.remove(Ready::READABLE);
+    /// assert_eq!(ready, Ready::WRITABLE);
+    /// ```
+    #[inline]
+    pub fn remove(&mut self, other: Ready) {
+        self.0 &= !other.0;
+    }
+
+    /// Returns the flags in `self` that are not in `other`.
+    #[inline]
+    pub const fn difference(self, other: Ready) -> Ready {
+        Ready(self.0 & !other.0)
+    }
+
+    /// Returns the flags that are in both `self` and `other`.
+    #[inline]
+    pub const fn intersection(self, other: Ready) -> Ready {
+        Ready(self.0 & other.0)
+    }
+
+    /// Returns the raw flags, used to store readiness in an atomic integer.
+    #[cfg(feature = "std")]
+    pub(crate) const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Create readiness from raw flags returned by `bits`.
+    #[cfg(feature = "std")]
+    pub(crate) const fn from_bits(bits: u8) -> Ready {
+        Ready(bits)
+    }
+
     /// Returns true if the value includes readable readiness.
     #[inline]
     pub const fn is_readable(self) -> bool {
@@ -506,8 +1714,117 @@ impl Ready {
     pub const fn is_hup(self) -> bool {
         self.contains(Self::HUP)
     }
+
+    /// Returns true if the value includes read HUP readiness.
+    #[inline]
+    #[cfg(unix)]
+    pub const fn is_read_hup(self) -> bool {
+        self.contains(Self::READ_HUP)
+    }
+
+    /// Returns true if the value includes priority readiness.
+    #[inline]
+    #[cfg(unix)]
+    pub const fn is_priority(self) -> bool {
+        self.contains(Self::PRIORITY)
+    }
+
+    /// Returns the [`Interests`] to register for to receive the readiness in
+    /// `self`.
+    ///
+    /// Only readable, writable and priority readiness have matching
+    /// interests, other readiness (e.g. [`Ready::ERROR`] or [`Ready::TIMER`])
+    /// is ignored. Returns `None` if `self` contains no readiness that can be
+    /// registered for.
+    ///
+    /// The reverse conversion is done using the `From<Interests>`
+    /// implementation.
+    ///
+    /// [`Interests`]: crate::os::Interests
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gaea::Ready;
+    /// use gaea::os::Interests;
+    ///
+    /// let ready = Ready::READABLE | Ready::ERROR;
+    /// assert_eq!(ready.as_interests(), Some(Interests::READABLE));
+    /// assert_eq!(Ready::TIMER.as_interests(), None);
+    ///
+    /// assert_eq!(Ready::from(Interests::WRITABLE), Ready::WRITABLE);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn as_interests(self) -> Option<Interests> {
+        Interests::from_ready(self)
+    }
+
+    /// Returns an iterator over the individual readiness flags set in `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gaea::Ready;
+    ///
+    /// let ready = Ready::READABLE | Ready::ERROR;
+    /// let mut iter = ready.iter();
+    /// assert_eq!(iter.next(), Some(Ready::READABLE));
+    /// assert_eq!(iter.next(), Some(Ready::ERROR));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub const fn iter(self) -> ReadyIter {
+        ReadyIter(self)
+    }
+}
+
+impl IntoIterator for Ready {
+    type Item = Ready;
+    type IntoIter = ReadyIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the individual readiness flags set in [`Ready`].
+///
+/// Created by [`Ready::iter`]. The flags are returned in order from lowest to
+/// highest bit, e.g. [`Ready::READABLE`] before [`Ready::WRITABLE`].
+#[derive(Debug)]
+pub struct ReadyIter(Ready);
+
+impl Iterator for ReadyIter {
+    type Item = Ready;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if (self.0).0 == 0 {
+            None
+        } else {
+            // Isolate the lowest set bit and remove it from the set.
+            let flag = (self.0).0 & (self.0).0.wrapping_neg();
+            (self.0).0 &= !flag;
+            Some(Ready(flag))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let size = self.len();
+        (size, Some(size))
+    }
+
+    fn count(self) -> usize {
+        self.len()
+    }
 }
 
+impl ExactSizeIterator for ReadyIter {
+    fn len(&self) -> usize {
+        (self.0).0.count_ones() as usize
+    }
+}
+
+impl FusedIterator for ReadyIter {}
+
 impl BitOr for Ready {
     type Output = Self;
 
@@ -554,6 +1871,12 @@ macro_rules! fmt_debug {
 impl fmt::Debug for Ready {
     #[allow(clippy::cognitive_complexity)]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fmt_debug!(self, f, READABLE, WRITABLE, ERROR, TIMER, HUP)
+        fmt_debug!(self, f, READABLE, WRITABLE, ERROR, TIMER, HUP, PRIORITY, READ_HUP)
+    }
+}
+
+impl fmt::Display for Ready {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
     }
 }
@@ -0,0 +1,173 @@
+//! Module with the event loop.
+
+use std::io;
+use std::mem;
+
+use log::trace;
+
+use crate::event::Event;
+use crate::Reactor;
+
+/// Event loop, running a [`Handler`] for all events.
+///
+/// `EventLoop` owns a [`Reactor`] and a buffer for events. [`run`] polls the
+/// reactor and calls the handler for each event, until the loop is
+/// [stopped]. This removes the need for the common `loop { poll(..); for event
+/// in &events { .. } events.clear(); }` boilerplate. [`poll`] remains
+/// available for advanced use.
+///
+/// [`run`]: EventLoop::run
+/// [stopped]: EventLoop::stop
+/// [`poll`]: crate::poll
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::io;
+///
+/// use gaea::{event, Event, EventLoop, Handler, Ready};
+///
+/// /// Handler that counts events and stops after the third.
+/// struct Counter(usize);
+///
+/// impl Handler for Counter {
+///     fn handle(&mut self, event_loop: &mut EventLoop, event: Event) -> io::Result<()> {
+///         self.0 += 1;
+///         if self.0 < 3 {
+///             // Add another event, which will be handled in the next
+///             // iteration of the loop.
+///             event_loop.reactor_mut().notify(Event::new(event.id(), Ready::READABLE));
+///         } else {
+///             event_loop.stop();
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let mut event_loop = EventLoop::new()?;
+/// event_loop.reactor_mut().notify(Event::new(event::Id(0), Ready::READABLE));
+///
+/// let mut counter = Counter(0);
+/// event_loop.run(&mut counter)?;
+/// assert_eq!(counter.0, 3);
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct EventLoop {
+    reactor: Reactor,
+    events: Vec<Event>,
+    running: bool,
+}
+
+/// Handler of events, used in [`EventLoop::run`].
+pub trait Handler {
+    /// Handle a single `event`.
+    ///
+    /// The `event_loop` can be used to register new event sources, or to
+    /// [stop] the loop. Returning an error also stops the loop, see
+    /// [`EventLoop::run`].
+    ///
+    /// [stop]: EventLoop::stop
+    fn handle(&mut self, event_loop: &mut EventLoop, event: Event) -> io::Result<()>;
+}
+
+impl EventLoop {
+    /// Create a new `EventLoop`.
+    ///
+    /// This creates a new [`Reactor`], which may fail.
+    pub fn new() -> io::Result<EventLoop> {
+        Reactor::new().map(EventLoop::with_reactor)
+    }
+
+    /// Create a new `EventLoop` using `reactor`.
+    pub fn with_reactor(reactor: Reactor) -> EventLoop {
+        EventLoop {
+            reactor,
+            events: Vec::new(),
+            running: false,
+        }
+    }
+
+    /// Run the event loop, calling `handler` for each event.
+    ///
+    /// This polls the reactor, without a timeout, and calls the handler for
+    /// each returned event. This repeats until either [`stop`] is called or
+    /// the handler returns an error, in which case the error is returned.
+    ///
+    /// After calling `stop` the remaining events of the current poll are
+    /// still handled before this returns. If the handler returns an error the
+    /// remaining events are kept, they're handled first by the next call to
+    /// `run`, before polling again.
+    ///
+    /// [`stop`]: EventLoop::stop
+    #[allow(clippy::mem_replace_with_default)] // `mem::take` requires Rust 1.40.
+    pub fn run<H>(&mut self, handler: &mut H) -> io::Result<()>
+        where H: Handler + ?Sized,
+    {
+        trace!("running event loop");
+        self.running = true;
+        // Reuse the allocation of the events buffer, while allowing the handler
+        // to access the event loop.
+        let mut events = mem::replace(&mut self.events, Vec::new());
+        let res = self.run_loop(handler, &mut events);
+        self.events = events;
+        self.running = false;
+        res
+    }
+
+    fn run_loop<H>(&mut self, handler: &mut H, events: &mut Vec<Event>) -> io::Result<()>
+        where H: Handler + ?Sized,
+    {
+        while self.running {
+            // Events left after the handler returned an error in a previous
+            // run are handled before polling again.
+            if events.is_empty() {
+                let _ = self.reactor.poll(events, None)?;
+            }
+            let mut handled = 0;
+            while handled < events.len() {
+                let event = events[handled];
+                handled += 1;
+                if let Err(err) = handler.handle(self, event) {
+                    // Keep the unhandled events for the next run.
+                    let _ = events.drain(..handled);
+                    return Err(err);
+                }
+            }
+            events.clear();
+        }
+        Ok(())
+    }
+
+    /// Stop the event loop.
+    ///
+    /// See [`run`] for more.
+    ///
+    /// [`run`]: EventLoop::run
+    pub fn stop(&mut self) {
+        trace!("stopping event loop");
+        self.running = false;
+    }
+
+    /// Returns true if the event loop is running.
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Returns a reference to the [`Reactor`].
+    pub fn reactor(&self) -> &Reactor {
+        &self.reactor
+    }
+
+    /// Returns a mutable reference to the [`Reactor`].
+    pub fn reactor_mut(&mut self) -> &mut Reactor {
+        &mut self.reactor
+    }
+
+    /// Returns the [`Reactor`].
+    pub fn into_reactor(self) -> Reactor {
+        self.reactor
+    }
+}
@@ -145,11 +145,15 @@
 #[cfg(all(not(feature = "std"), feature = "user_space"))]
 extern crate alloc;
 
-use core::cmp::min;
+use core::cmp::{max, min};
 use core::time::Duration;
 
 use log::trace;
 
+#[cfg(feature = "std")]
+mod event_loop;
+#[cfg(feature = "std")]
+mod reactor;
 #[cfg(feature = "std")]
 mod sys;
 #[cfg(feature = "std")]
@@ -158,7 +162,7 @@ mod timers;
 mod user_space;
 
 pub mod event;
-#[cfg(feature = "std")]
+#[cfg(feature = "net")]
 pub mod net;
 #[cfg(feature = "std")]
 pub mod os;
@@ -170,11 +174,21 @@ pub mod unix {
     #[doc(inline)]
     pub use crate::sys::pipe::{new_pipe, Receiver, Sender};
     #[doc(inline)]
+    pub use crate::sys::pty::{new_pty, PtyMaster, PtySlave};
+    #[doc(inline)]
+    pub use crate::sys::ucred::{peer_cred, UCred};
+    #[doc(inline)]
     pub use crate::sys::EventedFd;
 }
 
 #[cfg(feature = "std")]
-pub use crate::timers::Timers;
+pub use crate::event_loop::{EventLoop, Handler};
+#[cfg(feature = "std")]
+pub use crate::reactor::Reactor;
+#[cfg(feature = "std")]
+pub use crate::timers::{Clock, Deadlines, SystemClock, TakeExpired, Timers};
+#[cfg(all(feature = "std", any(target_os = "android", target_os = "linux")))]
+pub use crate::timers::CoarseClock;
 #[cfg(any(feature = "std", feature = "user_space"))]
 pub use crate::user_space::Queue;
 
@@ -200,12 +214,17 @@ pub use crate::os::OsQueue;
 ///
 /// Readiness events will be added to the supplied `event_sink`. If not all
 /// events fit into the event sink, they will be returned in the next call to
-/// `poll`.
+/// `poll`. Returns the total number of events added to `event_sink` by all
+/// event sources.
 ///
 /// Providing a `timeout` of `None` means that `poll` will block until the
 /// `blocking_source` is awoken by an external factor, what this means is
-/// different for each event source.
+/// different for each event source. For [`OsQueue`] it blocks until a
+/// registered handle is ready or an [`Awakener`] is woken. Waking an
+/// `Awakener` from another thread is the way to interrupt a blocking `poll`,
+/// e.g. to shutdown gracefully.
 ///
+/// [`Awakener`]: crate::os::Awakener
 /// [blocking poll]: event::Source::blocking_poll
 /// [polled]: event::Source::poll
 ///
@@ -224,6 +243,10 @@ pub use crate::os::OsQueue;
 /// [`Queue`] and [`Timers`] don't return an error as they are both implemented
 /// in user space and will accept any type as error type (as they don't use it).
 ///
+/// For example [`OsQueue`] can be polled using any error type that implements
+/// `From<io::Error>`, so it can be polled together with a `Queue` and
+/// `Timers` using a single application specific error type.
+///
 /// [`io::Error`]: std::io::Error
 ///
 /// # Examples
@@ -281,8 +304,8 @@ pub use crate::os::OsQueue;
 /// #         None
 /// #     }
 /// #
-/// #     fn poll(&mut self, _event_sink: &mut ES) -> Result<(), E> {
-/// #         Ok(())
+/// #     fn poll(&mut self, _event_sink: &mut ES) -> Result<usize, E> {
+/// #         Ok(0)
 /// #     }
 /// # }
 /// #
@@ -294,8 +317,8 @@ pub use crate::os::OsQueue;
 /// #         None
 /// #     }
 /// #
-/// #     fn poll(&mut self, _event_sink: &mut ES) -> Result<(), E> {
-/// #         Ok(())
+/// #     fn poll(&mut self, _event_sink: &mut ES) -> Result<usize, E> {
+/// #         Ok(0)
 /// #     }
 /// # }
 ///
@@ -344,7 +367,7 @@ pub fn poll<ES, E>(
     event_sources: &mut [&mut dyn event::Source<ES, E>],
     event_sink: &mut ES,
     timeout: Option<Duration>,
-) -> Result<(), E>
+) -> Result<usize, E>
     where ES: event::Sink,
 {
     trace!("polling: timeout={:?}", timeout);
@@ -354,22 +377,370 @@ pub fn poll<ES, E>(
         min_timeout(timeout, event_source.max_timeout())
     });
 
+    let mut n_events = 0;
     let mut iter = event_sources.iter_mut();
     if let Some(event_source) = iter.next() {
         // Start with polling the blocking source.
-        event_source.blocking_poll(event_sink, timeout)?;
+        n_events += event_source.blocking_poll(event_sink, timeout)?;
 
         // Next poll all non-blocking sources.
         for event_source in iter {
-            event_source.poll(event_sink)?;
+            n_events += event_source.poll(event_sink)?;
+        }
+    }
+
+    Ok(n_events)
+}
+
+/// Poll event sources for readiness events, calling `f` for each event.
+///
+/// This is the same as [`poll`], but rather than adding the events to an event
+/// sink, `f` is called for each event as it's produced by the event sources.
+/// This avoids collecting the events into a collection (and possibly
+/// allocating), only to iterate over them afterwards.
+///
+/// See [`event::FnSink`] for the event sink used.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), ()> {
+/// use gaea::{event, Event, Queue, Ready, poll_each};
+///
+/// let mut queue = Queue::new();
+/// queue.add(Event::new(event::Id(0), Ready::READABLE));
+/// queue.add(Event::new(event::Id(1), Ready::WRITABLE));
+///
+/// let mut ids = Vec::new();
+/// let n_events = poll_each(&mut [&mut queue], None, |event| ids.push(event.id()))?;
+/// assert_eq!(n_events, 2);
+/// assert_eq!(ids, vec![event::Id(0), event::Id(1)]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn poll_each<F, E>(
+    event_sources: &mut [&mut dyn event::Source<event::FnSink<F>, E>],
+    timeout: Option<Duration>,
+    f: F,
+) -> Result<usize, E>
+    where F: FnMut(Event),
+{
+    poll(event_sources, &mut event::FnSink::new(f), timeout)
+}
+
+/// Poll event sources for readiness events, blocking until at most `deadline`.
+///
+/// This is the same as [`poll`], but takes an absolute deadline rather than a
+/// relative timeout. This is useful in loops that do work between calls to
+/// poll, as the wake up time doesn't drift when polling multiple times.
+///
+/// If the `deadline` has already passed the event sources are polled without
+/// blocking.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use std::time::{Duration, Instant};
+///
+/// use gaea::{OsQueue, poll_until};
+///
+/// # fn main() -> io::Result<()> {
+/// let mut os_queue = OsQueue::new()?;
+/// let mut events = Vec::new();
+///
+/// let deadline = Instant::now() + Duration::from_millis(10);
+/// // Poll until the deadline has passed, this can be called multiple times
+/// // without extending the total wait time.
+/// while Instant::now() < deadline {
+///     poll_until::<_, io::Error>(&mut [&mut os_queue], &mut events, deadline)?;
+/// }
+/// assert!(events.is_empty());
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "std")]
+pub fn poll_until<ES, E>(
+    event_sources: &mut [&mut dyn event::Source<ES, E>],
+    event_sink: &mut ES,
+    deadline: std::time::Instant,
+) -> Result<usize, E>
+    where ES: event::Sink,
+{
+    let now = std::time::Instant::now();
+    let timeout = if deadline > now {
+        deadline - now
+    } else {
+        Duration::from_millis(0)
+    };
+    poll(event_sources, event_sink, Some(timeout))
+}
+
+/// Poll event sources for readiness events, sharing the capacity of the event
+/// sink between the event sources.
+///
+/// [`poll`] polls the event sources in order, this means that if the first
+/// event source always has events ready it can fill up an event sink with
+/// [limited capacity], starving the other event sources. `poll_fair` prevents
+/// this by first polling each event source for at most its share of the
+/// capacity left in the event sink, i.e. the capacity left divided by the
+/// number of event sources (with a minimum of one). If any capacity is left
+/// after that the event sources are polled again, in order, to fill it up.
+///
+/// Other then the above this is the same as [`poll`]: the first event source
+/// is used in the blocking poll and the total number of events added to
+/// `event_sink` is returned.
+///
+/// For event sinks with a growable capacity this is the same as `poll`.
+///
+/// [limited capacity]: event::Capacity::Limited
+///
+/// # Ordering
+///
+/// Because the event sources are polled twice, the events of each event source
+/// are no longer guaranteed to be added to the event sink together. For
+/// example the events of the first event source can be added both before and
+/// after the events of the second event source.
+///
+/// If the capacity left is smaller than the number of event sources, only the
+/// first event sources (one per event) are polled.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), ()> {
+/// use gaea::{event, Event, Queue, Ready, poll_fair};
+/// use gaea::event::LimitSink;
+///
+/// let mut queue1 = Queue::new();
+/// let mut queue2 = Queue::new();
+/// for n in 0..4 {
+///     queue1.add(Event::new(event::Id(n), Ready::READABLE));
+///     queue2.add(Event::new(event::Id(10 + n), Ready::READABLE));
+/// }
+///
+/// // Only room for 4 events, `poll` would add the 4 events from `queue1`,
+/// // while `poll_fair` adds 2 events from each queue.
+/// let mut events = Vec::new();
+/// poll_fair(&mut [&mut queue1, &mut queue2], &mut LimitSink::new(&mut events, 4), None)?;
+/// assert_eq!(events, vec![
+///     Event::new(event::Id(0), Ready::READABLE),
+///     Event::new(event::Id(1), Ready::READABLE),
+///     Event::new(event::Id(10), Ready::READABLE),
+///     Event::new(event::Id(11), Ready::READABLE),
+/// ]);
+/// # Ok(())
+/// # }
+/// ```
+#[allow(clippy::legacy_numeric_constants)] // `usize::MAX` requires Rust 1.43.
+pub fn poll_fair<ES, E>(
+    event_sources: &mut [&mut dyn for<'a> event::Source<event::LimitSink<'a, ES>, E>],
+    event_sink: &mut ES,
+    timeout: Option<Duration>,
+) -> Result<usize, E>
+    where ES: event::Sink,
+{
+    trace!("polling fair: timeout={:?}", timeout);
+
+    let timeout = event_sources.iter().fold(timeout, |timeout, event_source| {
+        min_timeout(timeout, event_source.max_timeout())
+    });
+
+    let share = match event_sink.capacity_left() {
+        event::Capacity::Limited(left) => max(left / max(event_sources.len(), 1), 1),
+        event::Capacity::Growable => usize::max_value(),
+    };
+
+    let mut n_events = 0;
+    for (n, event_source) in event_sources.iter_mut().enumerate() {
+        let mut event_sink = event::LimitSink::new(event_sink, share);
+        if n == 0 {
+            // Start with polling the blocking source.
+            n_events += event_source.blocking_poll(&mut event_sink, timeout)?;
+        } else {
+            n_events += event_source.poll(&mut event_sink)?;
         }
     }
 
-    Ok(())
+    // Fill up any capacity left.
+    if let event::Capacity::Limited(_) = event_sink.capacity_left() {
+        for event_source in event_sources.iter_mut() {
+            if event_sink.capacity_left().min(1) == 0 {
+                break;
+            }
+            let mut event_sink = event::LimitSink::new(event_sink, usize::max_value());
+            n_events += event_source.poll(&mut event_sink)?;
+        }
+    }
+
+    Ok(n_events)
+}
+
+/// Poll event sources for readiness events, adding at most `budget` events per
+/// event source.
+///
+/// This is the same as [`poll`], but once an event source has added `budget`
+/// events to the event sink it's no longer polled and the next event source is
+/// polled instead. This way a single event source with a lot of ready events,
+/// e.g. a flood of events from a single connection, can't monopolize the whole
+/// event sink.
+///
+/// Events that are not added to the event sink remain in the event source and
+/// will be returned in the next call to poll. Note that for [edge-triggered]
+/// registrations this is only the case if the handle isn't drained.
+///
+/// [edge-triggered]: crate::os::RegisterOption::EDGE
+///
+/// # Panics
+///
+/// This will panic if `budget` is zero. No event source could add any events,
+/// so the blocking event source would return immediately rather than block,
+/// causing calls in a loop to spin.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), ()> {
+/// use gaea::{event, Event, Queue, Ready, poll_with_budget};
+///
+/// let mut queue1 = Queue::new();
+/// let mut queue2 = Queue::new();
+/// for n in 0..3 {
+///     queue1.add(Event::new(event::Id(n), Ready::READABLE));
+///     queue2.add(Event::new(event::Id(10 + n), Ready::READABLE));
+/// }
+///
+/// // Only allow 2 events per queue.
+/// let mut events = Vec::new();
+/// poll_with_budget(&mut [&mut queue1, &mut queue2], &mut events, None, 2)?;
+/// assert_eq!(events, vec![
+///     Event::new(event::Id(0), Ready::READABLE),
+///     Event::new(event::Id(1), Ready::READABLE),
+///     Event::new(event::Id(10), Ready::READABLE),
+///     Event::new(event::Id(11), Ready::READABLE),
+/// ]);
+///
+/// // The remaining events are returned in the next call.
+/// events.clear();
+/// poll_with_budget(&mut [&mut queue1, &mut queue2], &mut events, None, 2)?;
+/// assert_eq!(events, vec![
+///     Event::new(event::Id(2), Ready::READABLE),
+///     Event::new(event::Id(12), Ready::READABLE),
+/// ]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn poll_with_budget<ES, E>(
+    event_sources: &mut [&mut dyn for<'a> event::Source<event::LimitSink<'a, ES>, E>],
+    event_sink: &mut ES,
+    timeout: Option<Duration>,
+    budget: usize,
+) -> Result<usize, E>
+    where ES: event::Sink,
+{
+    assert!(budget != 0, "poll_with_budget: budget must be at least one");
+    trace!("polling with budget: timeout={:?}, budget={}", timeout, budget);
+
+    let timeout = event_sources.iter().fold(timeout, |timeout, event_source| {
+        min_timeout(timeout, event_source.max_timeout())
+    });
+
+    let mut n_events = 0;
+    for (n, event_source) in event_sources.iter_mut().enumerate() {
+        let mut event_sink = event::LimitSink::new(event_sink, budget);
+        if n == 0 {
+            // Start with polling the blocking source.
+            n_events += event_source.blocking_poll(&mut event_sink, timeout)?;
+        } else {
+            n_events += event_source.poll(&mut event_sink)?;
+        }
+    }
+
+    Ok(n_events)
+}
+
+/// Poll event sources for readiness events, collecting errors rather than
+/// returning on the first error.
+///
+/// This is the same as [`poll`], but an error returned by one event source
+/// doesn't stop the remaining event sources from being polled. Instead all
+/// event sources are polled and the errors are returned, together with the
+/// index of the event source in `event_sources` that returned it. This
+/// prevents a single failing event source from hiding the events of the other
+/// event sources.
+///
+/// The events of all event sources that didn't return an error are added to
+/// `event_sink`, an empty vector is returned if no event source returned an
+/// error.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use std::time::Duration;
+///
+/// use gaea::{event, poll_collect_errors, Event, Queue, Ready};
+///
+/// /// Event source that always fails.
+/// struct Failing;
+///
+/// impl<ES, E> event::Source<ES, E> for Failing
+///     where ES: event::Sink,
+///           E: From<io::Error>,
+/// {
+///     fn max_timeout(&self) -> Option<Duration> {
+///         None
+///     }
+///
+///     fn poll(&mut self, _event_sink: &mut ES) -> Result<usize, E> {
+///         Err(io::Error::new(io::ErrorKind::Other, "oops").into())
+///     }
+/// }
+///
+/// let mut failing = Failing;
+/// let mut queue = Queue::new();
+/// queue.add(Event::new(event::Id(0), Ready::READABLE));
+///
+/// let mut events = Vec::new();
+/// let errors = poll_collect_errors::<_, io::Error>(&mut [&mut failing, &mut queue],
+///     &mut events, None);
+///
+/// // The error of `failing` doesn't hide the events of `queue`.
+/// assert_eq!(errors.len(), 1);
+/// assert_eq!(errors[0].0, 0);
+/// assert_eq!(events, vec![Event::new(event::Id(0), Ready::READABLE)]);
+/// ```
+#[cfg(feature = "std")]
+pub fn poll_collect_errors<ES, E>(
+    event_sources: &mut [&mut dyn event::Source<ES, E>],
+    event_sink: &mut ES,
+    timeout: Option<Duration>,
+) -> Vec<(usize, E)>
+    where ES: event::Sink,
+{
+    trace!("polling collecting errors: timeout={:?}", timeout);
+
+    let timeout = event_sources.iter().fold(timeout, |timeout, event_source| {
+        min_timeout(timeout, event_source.max_timeout())
+    });
+
+    let mut errors = Vec::new();
+    for (n, event_source) in event_sources.iter_mut().enumerate() {
+        let result = if n == 0 {
+            // Start with polling the blocking source.
+            event_source.blocking_poll(event_sink, timeout)
+        } else {
+            event_source.poll(event_sink)
+        };
+        if let Err(err) = result {
+            errors.push((n, err));
+        }
+    }
+    errors
 }
 
 /// Returns the smallest timeout of the two timeouts provided.
-fn min_timeout(left: Option<Duration>, right: Option<Duration>) -> Option<Duration> {
+pub(crate) fn min_timeout(left: Option<Duration>, right: Option<Duration>) -> Option<Duration> {
     match (left, right) {
         (Some(left), Some(right)) => Some(min(left, right)),
         (Some(left), None) => Some(left),
@@ -0,0 +1,333 @@
+//! Adapters implementing the traits of the futures crate, enabled by the
+//! `futures` feature.
+
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+use futures_core::Stream;
+use futures_io::{AsyncRead, AsyncWrite};
+use log::{error, trace};
+
+use crate::event::{self, Event, IdGenerator, Ready};
+use crate::net::{TcpListener, TcpStream};
+use crate::os::{Evented, Interests, OsQueue, RegisterOption};
+
+/// Driver of the futures adapters, waking the tasks waiting for readiness of
+/// [`IncomingStream`]s and [`AsyncTcpStream`]s.
+///
+/// All handles created using the driver's [`handle`] are registered with a
+/// single [`OsQueue`]. [`poll`] polls that queue and wakes the tasks waiting
+/// for the returned readiness, it must be called in a loop, e.g. on a separate
+/// thread, for the tasks to make progress.
+///
+/// [`handle`]: AsyncDriver::handle
+/// [`poll`]: AsyncDriver::poll
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::thread;
+///
+/// use gaea::net::{AsyncDriver, IncomingStream, TcpListener};
+///
+/// let mut driver = AsyncDriver::new()?;
+/// let handle = driver.handle();
+/// thread::spawn(move || loop {
+///     if let Err(err) = driver.poll(None) {
+///         eprintln!("error polling driver: {}", err);
+///     }
+/// });
+///
+/// let listener = TcpListener::bind("127.0.0.1:0".parse()?)?;
+/// let incoming = IncomingStream::new(listener, &handle)?;
+///
+/// // Now `incoming` can be used with any executor, e.g. using
+/// // `StreamExt::for_each` from the futures crate.
+/// # drop(incoming);
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct AsyncDriver {
+    os_queue: OsQueue,
+    events: Vec<Event>,
+    shared: Arc<Mutex<Shared>>,
+}
+
+/// Handle to an [`AsyncDriver`], used to create [`IncomingStream`]s and
+/// [`AsyncTcpStream`]s driven by it.
+#[derive(Clone, Debug)]
+pub struct AsyncDriverHandle {
+    shared: Arc<Mutex<Shared>>,
+}
+
+/// State shared between `AsyncDriver` and its handles.
+#[derive(Debug)]
+struct Shared {
+    /// Clone of the driver's `OsQueue`, used to register handles.
+    os_queue: OsQueue,
+    ids: IdGenerator,
+    /// State of all registered handles.
+    handles: HashMap<event::Id, HandleState>,
+}
+
+/// State of a single registered handle.
+#[derive(Debug)]
+struct HandleState {
+    /// Readiness received since it was last checked.
+    readiness: Ready,
+    reader: Option<Waker>,
+    writer: Option<Waker>,
+}
+
+impl AsyncDriver {
+    /// Create a new driver.
+    pub fn new() -> io::Result<AsyncDriver> {
+        let os_queue = OsQueue::new()?;
+        let shared = Arc::new(Mutex::new(Shared {
+            os_queue: os_queue.try_clone()?,
+            ids: IdGenerator::new(),
+            handles: HashMap::new(),
+        }));
+        Ok(AsyncDriver { os_queue, events: Vec::new(), shared })
+    }
+
+    /// Returns a handle to the driver.
+    pub fn handle(&self) -> AsyncDriverHandle {
+        AsyncDriverHandle { shared: Arc::clone(&self.shared) }
+    }
+
+    /// Poll for readiness events, waiting up to `timeout` (`None` meaning
+    /// indefinitely), and wake the tasks waiting for them.
+    pub fn poll(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        let _ = crate::poll::<_, io::Error>(&mut [&mut self.os_queue], &mut self.events, timeout)?;
+
+        let mut shared = lock(&self.shared);
+        for event in self.events.drain(..) {
+            let state = match shared.handles.get_mut(&event.id()) {
+                Some(state) => state,
+                // Handle was dropped.
+                None => continue,
+            };
+            trace!("waking futures: id={}, readiness={:?}", event.id(), event.readiness());
+            state.readiness |= event.readiness();
+            if event.readiness().contains_any(Direction::Read.readiness()) {
+                wake(state.reader.take());
+            }
+            if event.readiness().contains_any(Direction::Write.readiness()) {
+                wake(state.writer.take());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Stream of incoming connections, accepted from a [`TcpListener`].
+///
+/// This implements the [`Stream`] trait of the futures crate, returning all
+/// connections accepted by the listener. The stream never ends.
+///
+/// The task is woken by the [`AsyncDriver`] used to create the stream, see
+/// its documentation for an example.
+///
+/// [`Stream`]: futures_core::Stream
+#[derive(Debug)]
+pub struct IncomingStream {
+    listener: TcpListener,
+    registration: Registration,
+}
+
+impl IncomingStream {
+    /// Create a new stream of incoming connections accepted from `listener`,
+    /// driven by `driver`.
+    pub fn new(mut listener: TcpListener, driver: &AsyncDriverHandle) -> io::Result<IncomingStream> {
+        let registration = Registration::new(driver, &mut listener, Interests::READABLE)?;
+        Ok(IncomingStream { listener, registration })
+    }
+
+    /// Returns a mutable reference to the [`TcpListener`].
+    pub fn listener_mut(&mut self) -> &mut TcpListener {
+        &mut self.listener
+    }
+}
+
+impl Stream for IncomingStream {
+    type Item = io::Result<(TcpStream, SocketAddr)>;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let listener = &mut this.listener;
+        this.registration.poll_io(ctx, Direction::Read, || listener.accept()).map(Some)
+    }
+}
+
+impl Drop for IncomingStream {
+    fn drop(&mut self) {
+        self.registration.deregister(&mut self.listener);
+    }
+}
+
+/// [`TcpStream`] implementing the [`AsyncRead`] and [`AsyncWrite`] traits of
+/// the futures crate.
+///
+/// The tasks are woken by the [`AsyncDriver`] used to create the stream.
+///
+/// [`AsyncRead`]: futures_io::AsyncRead
+/// [`AsyncWrite`]: futures_io::AsyncWrite
+#[derive(Debug)]
+pub struct AsyncTcpStream {
+    stream: TcpStream,
+    registration: Registration,
+}
+
+impl AsyncTcpStream {
+    /// Create a new `AsyncTcpStream` from `stream`, driven by `driver`.
+    pub fn new(mut stream: TcpStream, driver: &AsyncDriverHandle) -> io::Result<AsyncTcpStream> {
+        let registration = Registration::new(driver, &mut stream, TcpStream::INTERESTS)?;
+        Ok(AsyncTcpStream { stream, registration })
+    }
+
+    /// Returns a mutable reference to the [`TcpStream`].
+    pub fn stream_mut(&mut self) -> &mut TcpStream {
+        &mut self.stream
+    }
+}
+
+impl AsyncRead for AsyncTcpStream {
+    fn poll_read(self: Pin<&mut Self>, ctx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let stream = &mut this.stream;
+        this.registration.poll_io(ctx, Direction::Read, || stream.read(buf))
+    }
+}
+
+impl AsyncWrite for AsyncTcpStream {
+    fn poll_write(self: Pin<&mut Self>, ctx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let stream = &mut this.stream;
+        this.registration.poll_io(ctx, Direction::Write, || stream.write(buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(self.get_mut().stream.flush())
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(self.get_mut().stream.shutdown(Shutdown::Write))
+    }
+}
+
+impl Drop for AsyncTcpStream {
+    fn drop(&mut self) {
+        self.registration.deregister(&mut self.stream);
+    }
+}
+
+/// Direction of an I/O operation.
+#[derive(Copy, Clone, Debug)]
+enum Direction {
+    Read,
+    Write,
+}
+
+impl Direction {
+    /// Readiness that allows the operation to make progress.
+    fn readiness(self) -> Ready {
+        match self {
+            Direction::Read => Ready::READABLE | Ready::READ_HUP | Ready::HUP | Ready::ERROR,
+            Direction::Write => Ready::WRITABLE | Ready::HUP | Ready::ERROR,
+        }
+    }
+}
+
+/// Registration of a single handle with an `AsyncDriver`.
+#[derive(Debug)]
+struct Registration {
+    id: event::Id,
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl Registration {
+    fn new<E>(driver: &AsyncDriverHandle, handle: &mut E, interests: Interests) -> io::Result<Registration>
+        where E: Evented,
+    {
+        let mut shared = lock(&driver.shared);
+        let id = shared.ids.next();
+        // Add the state first, the driver could return an event before
+        // `register` returns.
+        let _ = shared.handles.insert(id, HandleState {
+            readiness: Ready::EMPTY,
+            reader: None,
+            writer: None,
+        });
+        if let Err(err) = shared.os_queue.register(handle, id, interests, RegisterOption::EDGE) {
+            let _ = shared.handles.remove(&id);
+            shared.ids.free(id);
+            return Err(err);
+        }
+        Ok(Registration { id, shared: Arc::clone(&driver.shared) })
+    }
+
+    /// Attempt the I/O operation `op`, registering the task's waker if it
+    /// would block.
+    fn poll_io<T, F>(&self, ctx: &mut Context<'_>, direction: Direction, mut op: F) -> Poll<io::Result<T>>
+        where F: FnMut() -> io::Result<T>,
+    {
+        loop {
+            match op() {
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {},
+                result => return Poll::Ready(result),
+            }
+
+            let mut shared = lock(&self.shared);
+            let state = match shared.handles.get_mut(&self.id) {
+                Some(state) => state,
+                None => unreachable!("futures handle state removed while registered"),
+            };
+            let readiness = direction.readiness();
+            if state.readiness.contains_any(readiness) {
+                // Became ready after the operation was attempted, try again.
+                state.readiness.remove(readiness);
+                continue;
+            }
+            let waker = Some(ctx.waker().clone());
+            match direction {
+                Direction::Read => state.reader = waker,
+                Direction::Write => state.writer = waker,
+            }
+            return Poll::Pending;
+        }
+    }
+
+    /// Deregister `handle` and remove its state, freeing the id.
+    fn deregister<E>(&self, handle: &mut E)
+        where E: Evented,
+    {
+        let mut shared = lock(&self.shared);
+        if let Err(err) = shared.os_queue.deregister(handle) {
+            error!("unable to deregister futures handle: {}", err);
+        }
+        let _ = shared.handles.remove(&self.id);
+        shared.ids.free(self.id);
+    }
+}
+
+fn wake(waker: Option<Waker>) {
+    if let Some(waker) = waker {
+        waker.wake();
+    }
+}
+
+/// Lock the shared state, ignoring poisoning as the state is always valid.
+fn lock(shared: &Mutex<Shared>) -> MutexGuard<'_, Shared> {
+    match shared.lock() {
+        Ok(shared) => shared,
+        Err(err) => err.into_inner(),
+    }
+}
@@ -9,6 +9,10 @@
 
 mod tcp;
 mod udp;
+#[cfg(feature = "futures")]
+mod futures;
 
-pub use self::tcp::{TcpListener, TcpStream};
+pub use self::tcp::{ConnState, TcpListener, TcpStream};
 pub use self::udp::UdpSocket;
+#[cfg(feature = "futures")]
+pub use self::futures::{AsyncDriver, AsyncDriverHandle, AsyncTcpStream, IncomingStream};
@@ -1,7 +1,11 @@
 use std::io::{self, Read, Write};
+use std::mem::MaybeUninit;
 use std::net::{Shutdown, SocketAddr};
 #[cfg(unix)]
 use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+use std::time::{Duration, Instant};
+
+use log::trace;
 
 use crate::os::{Evented, Interests, OsQueue, RegisterOption};
 use crate::{event, sys};
@@ -50,6 +54,9 @@ use crate::{event, sys};
 #[derive(Debug)]
 pub struct TcpStream {
     inner: sys::TcpStream,
+    /// True if reading returned a `WouldBlock` error since the last readable
+    /// event, see `is_readable_hint`.
+    read_blocked: bool,
 }
 
 impl TcpStream {
@@ -60,7 +67,165 @@ impl TcpStream {
     /// Create a new TCP stream and issue a non-blocking connect to the
     /// specified address.
     pub fn connect(address: SocketAddr) -> io::Result<TcpStream> {
-        sys::TcpStream::connect(address).map(|inner| TcpStream { inner })
+        sys::TcpStream::connect(address).map(TcpStream::from_inner)
+    }
+
+    /// Create a new TCP stream bound to the network interface named
+    /// `interface`, e.g. `"eth0"`, and issue a non-blocking connect to the
+    /// specified address.
+    ///
+    /// The `SO_BINDTODEVICE` option is set before connecting, so the
+    /// connection is routed via the interface. Setting the option after
+    /// connecting has no effect on the route, which is why there is no
+    /// `set_bind_device` method for streams.
+    ///
+    /// # Notes
+    ///
+    /// This is only available on Android and Linux. It requires the
+    /// `CAP_NET_RAW` capability, without it a [`PermissionDenied`] error is
+    /// returned.
+    ///
+    /// [`PermissionDenied`]: std::io::ErrorKind::PermissionDenied
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn connect_with_device(address: SocketAddr, interface: &str) -> io::Result<TcpStream> {
+        sys::TcpStream::connect_with_device(address, interface).map(TcpStream::from_inner)
+    }
+
+    fn from_inner(inner: sys::TcpStream) -> TcpStream {
+        TcpStream { inner, read_blocked: false }
+    }
+
+    /// Create a new TCP stream using TCP Fast Open, sending `data` as part of
+    /// the connection handshake. On success, returns the stream and the
+    /// number of bytes of `data` send.
+    ///
+    /// TCP Fast Open saves a round trip by sending the first data in the SYN
+    /// packet. This is only possible once a Fast Open cookie is received from
+    /// the server, which is requested on the first connection to the server.
+    /// If no data could be send, e.g. there is no cookie yet, this returns `0`
+    /// and the data must be written once the stream is writable, just like
+    /// after a regular [`connect`].
+    ///
+    /// [`connect`]: TcpStream::connect
+    ///
+    /// # Notes
+    ///
+    /// This uses `sendto(2)` with `MSG_FASTOPEN` on Android and Linux, if
+    /// client side Fast Open is disabled (see the `net.ipv4.tcp_fastopen`
+    /// sysctl) this falls back to a regular connect. On other platforms this
+    /// always uses a regular connect and returns `0`.
+    pub fn connect_fastopen(address: SocketAddr, data: &[u8]) -> io::Result<(TcpStream, usize)> {
+        sys::TcpStream::connect_fastopen(address, data)
+            .map(|(inner, n)| (TcpStream::from_inner(inner), n))
+    }
+
+    /// Connect to one of `addresses` using Happy Eyeballs (RFC 8305), racing
+    /// connection attempts. Returns the first stream to successfully connect.
+    ///
+    /// A non-blocking connect is started to the first address, if it doesn't
+    /// complete within `stagger` (RFC 8305 recommends 250 milliseconds) the
+    /// next connect is started, without aborting the previous attempt. An
+    /// attempt that fails starts the next attempt immediately. Once a stream
+    /// is connected all other attempts are aborted. The addresses are tried in
+    /// order, alternating between IPv6 and IPv4 addresses starting with the
+    /// family of the first address, e.g. resolved addresses are best passed in
+    /// the order returned by the resolver.
+    ///
+    /// If all attempts fail the error of the last attempt is returned. If
+    /// `addresses` is empty an [`InvalidInput`] error is returned.
+    ///
+    /// [`InvalidInput`]: std::io::ErrorKind::InvalidInput
+    ///
+    /// # Notes
+    ///
+    /// This function blocks until a connection is made, or all attempts
+    /// failed. It uses its own [`OsQueue`], the returned stream is not
+    /// registered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use std::net::ToSocketAddrs;
+    /// use std::time::Duration;
+    ///
+    /// use gaea::net::{TcpListener, TcpStream};
+    ///
+    /// # let mut listener = TcpListener::bind("127.0.0.1:0".parse()?)?;
+    /// # let port = listener.local_addr()?.port();
+    /// let addresses: Vec<_> = ("localhost", port).to_socket_addrs()?.collect();
+    /// let mut stream = TcpStream::connect_happy_eyeballs(&addresses, Duration::from_millis(250))?;
+    /// # assert_eq!(stream.peer_addr()?.port(), port);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn connect_happy_eyeballs(addresses: &[SocketAddr], stagger: Duration) -> io::Result<TcpStream> {
+        let mut addresses = interleave_families(addresses).into_iter();
+        let mut os_queue = OsQueue::new()?;
+        let mut events = Vec::new();
+        // Pending connection attempts, indexed by id.
+        let mut attempts: Vec<Option<TcpStream>> = Vec::new();
+        let mut last_error = None;
+        let mut next_attempt = Instant::now();
+
+        loop {
+            let now = Instant::now();
+            if now >= next_attempt {
+                if let Some(address) = addresses.next() {
+                    trace!("happy eyeballs: connecting to {}", address);
+                    let id = event::Id(attempts.len());
+                    match TcpStream::connect(address) {
+                        Ok(mut stream) => {
+                            os_queue.register(&mut stream, id, Interests::WRITABLE, RegisterOption::EDGE)?;
+                            attempts.push(Some(stream));
+                            next_attempt = now + stagger;
+                        },
+                        Err(err) => {
+                            last_error = Some(err);
+                            continue;
+                        },
+                    }
+                }
+            }
+
+            let more_addresses = !addresses.as_slice().is_empty();
+            if !more_addresses && attempts.iter().all(Option::is_none) {
+                return Err(last_error.unwrap_or_else(|| io::Error::new(io::ErrorKind::InvalidInput,
+                    "no addresses to connect to")));
+            }
+
+            let timeout = if !more_addresses {
+                None
+            } else if next_attempt > now {
+                Some(next_attempt - now)
+            } else {
+                Some(Duration::from_millis(0))
+            };
+            let _ = crate::poll::<_, io::Error>(&mut [&mut os_queue], &mut events, timeout)?;
+
+            for event in events.drain(..) {
+                let mut stream = match attempts.get_mut(event.id().0).and_then(Option::take) {
+                    Some(stream) => stream,
+                    None => continue,
+                };
+                os_queue.deregister(&mut stream)?;
+                match connect_result(&mut stream) {
+                    Ok(()) => {
+                        // Abort all other attempts, dropping the streams
+                        // closes the sockets.
+                        for mut stream in attempts.drain(..).flatten() {
+                            let _ = os_queue.deregister(&mut stream);
+                        }
+                        return Ok(stream);
+                    },
+                    Err(err) => {
+                        // Start the next attempt immediately.
+                        last_error = Some(err);
+                        next_attempt = Instant::now();
+                    },
+                }
+            }
+        }
     }
 
     /// Returns the socket address of the remote peer of this TCP connection.
@@ -83,6 +248,22 @@ impl TcpStream {
         self.inner.ttl()
     }
 
+    /// Sets the value for the `IP_TOS` option on this socket, or
+    /// `IPV6_TCLASS` for IPv6 sockets.
+    ///
+    /// This can be used to set the Differentiated Services Code Point (DSCP)
+    /// of the packets send, the DSCP value must be shifted two bits to the
+    /// left, e.g. `46 << 2` for expedited forwarding.
+    pub fn set_tos(&mut self, tos: u32) -> io::Result<()> {
+        self.inner.set_tos(tos)
+    }
+
+    /// Gets the value of the `IP_TOS` option for this socket, or
+    /// `IPV6_TCLASS` for IPv6 sockets.
+    pub fn tos(&mut self) -> io::Result<u32> {
+        self.inner.tos()
+    }
+
     /// Sets the value of the `TCP_NODELAY` option on this socket.
     pub fn set_nodelay(&mut self, nodelay: bool) -> io::Result<()> {
         self.inner.set_nodelay(nodelay)
@@ -93,6 +274,34 @@ impl TcpStream {
         self.inner.nodelay()
     }
 
+    /// Sets the value of the `TCP_USER_TIMEOUT` option on this socket.
+    ///
+    /// This is the maximum amount of time transmitted data may remain
+    /// unacknowledged before the connection is closed by the OS, after which
+    /// reading or writing returns a [`TimedOut`] error. This allows detecting
+    /// a peer that is gone, without closing the connection, faster than using
+    /// keepalive probes alone. A timeout of zero uses the OS default. The
+    /// timeout has a resolution of milliseconds.
+    ///
+    /// [`TimedOut`]: std::io::ErrorKind::TimedOut
+    ///
+    /// # Notes
+    ///
+    /// This is only supported on Android and Linux, on other platforms this
+    /// returns an `ENOPROTOOPT` error.
+    pub fn set_user_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+        self.inner.set_user_timeout(timeout)
+    }
+
+    /// Gets the value of the `TCP_USER_TIMEOUT` option on this socket.
+    ///
+    /// See [`set_user_timeout`] for more.
+    ///
+    /// [`set_user_timeout`]: TcpStream::set_user_timeout
+    pub fn user_timeout(&mut self) -> io::Result<Duration> {
+        self.inner.user_timeout()
+    }
+
     /// Receives data on the socket from the remote address to which it is
     /// connected, without removing that data from the queue. On success,
     /// returns the number of bytes peeked.
@@ -100,7 +309,63 @@ impl TcpStream {
     /// Successive calls return the same data. This is accomplished by passing
     /// `MSG_PEEK` as a flag to the underlying recv system call.
     pub fn peek(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        self.inner.peek(buf)
+        let res = self.inner.peek(buf);
+        self.track_read(res)
+    }
+
+    /// Read data into a possibly uninitialised buffer. On success, returns
+    /// the number of bytes read.
+    ///
+    /// This works like [`Read::read`], but doesn't require `buf` to be
+    /// initialised, saving the cost of zeroing large buffers before reading
+    /// into them. It calls the `read` system call directly.
+    ///
+    /// If no data is available a [`WouldBlock`] error is returned, which means
+    /// one should wait for a readable event before calling `read_uninit`
+    /// again.
+    ///
+    /// [`WouldBlock`]: std::io::ErrorKind::WouldBlock
+    ///
+    /// # Initialisation
+    ///
+    /// On success, returning `n`, the first `n` bytes of `buf` are
+    /// initialised. The remainder of `buf` is left untouched, i.e. it's only
+    /// initialised if it was before the call. On error nothing is initialised.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use std::io::{self, Write};
+    /// use std::mem::MaybeUninit;
+    /// use std::slice;
+    ///
+    /// use gaea::net::{TcpListener, TcpStream};
+    ///
+    /// let mut listener = TcpListener::bind("127.0.0.1:0".parse()?)?;
+    /// let mut stream = TcpStream::connect(listener.local_addr()?)?;
+    /// # std::thread::sleep(std::time::Duration::from_millis(10));
+    /// let (mut peer, _) = listener.accept()?;
+    /// peer.write_all(b"Hello world")?;
+    /// # std::thread::sleep(std::time::Duration::from_millis(10));
+    ///
+    /// let mut buf: [MaybeUninit<u8>; 4096] = unsafe { MaybeUninit::uninit().assume_init() };
+    /// let n = loop {
+    ///     match stream.read_uninit(&mut buf) {
+    ///         Ok(n) => break n,
+    ///         Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => continue,
+    ///         Err(err) => return Err(err.into()),
+    ///     }
+    /// };
+    /// // Safety: `read_uninit` initialised the first `n` bytes.
+    /// let data = unsafe { slice::from_raw_parts(buf.as_ptr() as *const u8, n) };
+    /// assert_eq!(data, b"Hello world");
+    /// #    Ok(())
+    /// # }
+    /// ```
+    pub fn read_uninit(&mut self, buf: &mut [MaybeUninit<u8>]) -> io::Result<usize> {
+        let res = self.inner.read_uninit(buf);
+        self.track_read(res)
     }
 
     /// Shuts down the read, write, or both halves of this connection.
@@ -112,19 +377,374 @@ impl TcpStream {
         self.inner.shutdown(how)
     }
 
+    /// Moves this socket into or out of non-blocking mode.
+    ///
+    /// Sockets created by this crate are in non-blocking mode, which is
+    /// required for use with [`OsQueue`]. This can be used to switch the
+    /// socket back to blocking mode before handing it off to blocking code,
+    /// e.g. after converting it into a `std::net::TcpStream` using
+    /// [`IntoRawFd`].
+    ///
+    /// [`OsQueue`]: crate::os::OsQueue
+    ///
+    /// # Notes
+    ///
+    /// A socket in blocking mode must not be used with `OsQueue`, as reading
+    /// or writing may block the thread.
+    pub fn set_nonblocking(&mut self, nonblocking: bool) -> io::Result<()> {
+        self.inner.set_nonblocking(nonblocking)
+    }
+
     /// Get the value of the `SO_ERROR` option on this socket.
     ///
     /// This will retrieve the stored error in the underlying socket, clearing
     /// the field in the process. This can be useful for checking errors between
     /// calls.
+    ///
+    /// After receiving an event with [error readiness] for this stream this
+    /// should be called to retrieve the error, e.g. the reason a connect
+    /// failed. See [`had_error`] if only the presence of an error matters.
+    ///
+    /// [error readiness]: crate::event::Ready::ERROR
+    /// [`had_error`]: TcpStream::had_error
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    ///
+    /// use gaea::Event;
+    /// use gaea::net::TcpStream;
+    ///
+    /// fn handle_event(stream: &mut TcpStream, event: Event) -> io::Result<()> {
+    ///     if event.readiness().is_error() {
+    ///         if let Some(err) = stream.take_error()? {
+    ///             return Err(err);
+    ///         }
+    ///     }
+    ///     // Handle other readiness ...
+    ///     Ok(())
+    /// }
+    /// # let _ = handle_event;
+    /// ```
     pub fn take_error(&mut self) -> io::Result<Option<io::Error>> {
         self.inner.take_error()
     }
+
+    /// Returns true if the socket had a pending error, clearing it.
+    ///
+    /// This is a convenience method for [`take_error`] that discards the
+    /// error, for when only the presence of an error matters, e.g. to decide
+    /// whether to close the connection.
+    ///
+    /// [`take_error`]: TcpStream::take_error
+    pub fn had_error(&mut self) -> io::Result<bool> {
+        self.take_error().map(|err| err.is_some())
+    }
+
+    /// Returns the state of the non-blocking connect started in [`connect`].
+    ///
+    /// Returns `Ok(true)` if the connection is established, `Ok(false)` if the
+    /// connect is still in progress and an error if the connect failed. The
+    /// error is read from the `SO_ERROR` option, see [`take_error`].
+    ///
+    /// This should be called after the stream is ready to write, for which it
+    /// must be registered with writable interests.
+    ///
+    /// [`connect`]: TcpStream::connect
+    /// [`take_error`]: TcpStream::take_error
+    ///
+    /// # Notes
+    ///
+    /// Reading `SO_ERROR` clears the error, so the error of a failed connect
+    /// is returned only once, after which this returns `Ok(false)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use std::io;
+    ///
+    /// use gaea::{event, poll};
+    /// use gaea::net::{TcpListener, TcpStream};
+    /// use gaea::os::{Interests, OsQueue, RegisterOption};
+    ///
+    /// let mut listener = TcpListener::bind("127.0.0.1:0".parse()?)?;
+    /// let mut stream = TcpStream::connect(listener.local_addr()?)?;
+    ///
+    /// let mut os_queue = OsQueue::new()?;
+    /// let mut events = Vec::new();
+    /// os_queue.register(&mut stream, event::Id(0), Interests::WRITABLE, RegisterOption::LEVEL)?;
+    ///
+    /// // Wait until the connect is complete.
+    /// while !stream.poll_connect()? {
+    ///     poll::<_, io::Error>(&mut [&mut os_queue], &mut events, None)?;
+    /// }
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn poll_connect(&mut self) -> io::Result<bool> {
+        if let Some(err) = self.take_error()? {
+            return Err(err);
+        }
+        match self.peer_addr() {
+            Ok(_) => Ok(true),
+            Err(ref err) if err.kind() == io::ErrorKind::NotConnected => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Returns the state of the connection based on the readiness of `event`.
+    ///
+    /// This can be used to determine whether or not reading from or writing
+    /// to the stream is still possible after the peer closed (one side of) the
+    /// connection, for example in a proxy to decide whether or not to keep
+    /// flushing data. This doesn't make any system calls, use [`take_error`]
+    /// to retrieve the error if [`ConnState::Closed`] is returned due to error
+    /// readiness.
+    ///
+    /// `event` must be an event for this stream.
+    ///
+    /// [`take_error`]: TcpStream::take_error
+    ///
+    /// # Notes
+    ///
+    /// The returned state is determined as follows:
+    ///
+    /// - error readiness always results in [`ConnState::Closed`].
+    /// - [read hup] readiness means the peer closed its writing side, i.e.
+    ///   [`ConnState::ReadClosed`].
+    /// - [hup] readiness on Linux and Android means both sides are closed, i.e.
+    ///   [`ConnState::Closed`]. On the BSDs and macOS hup readiness is set
+    ///   when the peer closed its reading side, i.e.
+    ///   [`ConnState::WriteClosed`], or [`ConnState::Closed`] if combined with
+    ///   read hup readiness.
+    ///
+    /// Note that the stream might still have data buffered to read after the
+    /// peer closed its writing side.
+    ///
+    /// [read hup]: crate::event::Ready::READ_HUP
+    /// [hup]: crate::event::Ready::HUP
+    pub fn connection_state(&self, event: &event::Event) -> ConnState {
+        let readiness = event.readiness();
+        if readiness.is_error() {
+            return ConnState::Closed;
+        }
+
+        let read_closed = readiness.is_read_hup();
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        let read_closed = read_closed || readiness.is_hup();
+        let write_closed = readiness.is_hup();
+
+        match (read_closed, write_closed) {
+            (false, false) => ConnState::Open,
+            (true, false) => ConnState::ReadClosed,
+            (false, true) => ConnState::WriteClosed,
+            (true, true) => ConnState::Closed,
+        }
+    }
+
+    /// Returns false if reading is known to return a [`WouldBlock`] error.
+    ///
+    /// After reading returned a `WouldBlock` error the stream isn't readable
+    /// until the next readable event, but it's easy to attempt another read
+    /// anyway. Checking this hint before reading can save those system calls
+    /// in hot loops. The hint is cleared by passing events for this stream to
+    /// [`update_readiness_hint`] and by (re)registering the stream.
+    ///
+    /// [`WouldBlock`]: std::io::ErrorKind::WouldBlock
+    /// [`update_readiness_hint`]: TcpStream::update_readiness_hint
+    ///
+    /// # Notes
+    ///
+    /// This is a best-effort optimisation, not a guarantee: `true` doesn't
+    /// mean reading won't block. It's only useful for streams registered with
+    /// [edge] triggers, and only if all events for this stream are passed to
+    /// `update_readiness_hint`, otherwise the stream could stop reading even
+    /// though data is available.
+    ///
+    /// [edge]: crate::os::RegisterOption::EDGE
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{self, Read};
+    ///
+    /// use gaea::Event;
+    /// use gaea::net::TcpStream;
+    ///
+    /// fn handle_event(stream: &mut TcpStream, event: &Event, buf: &mut [u8]) -> io::Result<()> {
+    ///     stream.update_readiness_hint(event);
+    ///     // Read until the stream would block, without making another read
+    ///     // system call for the next (e.g. writable) event.
+    ///     while stream.is_readable_hint() {
+    ///         match stream.read(buf) {
+    ///             Ok(0) => return Ok(()),
+    ///             Ok(_) => { /* Process the data. */ },
+    ///             Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+    ///             Err(err) => return Err(err),
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// # let _ = handle_event;
+    /// ```
+    pub fn is_readable_hint(&self) -> bool {
+        !self.read_blocked
+    }
+
+    /// Update the readiness hint using an `event` for this stream.
+    ///
+    /// See [`is_readable_hint`] for more.
+    ///
+    /// [`is_readable_hint`]: TcpStream::is_readable_hint
+    pub fn update_readiness_hint(&mut self, event: &event::Event) {
+        // Closing and errors also make a read return without blocking.
+        let readiness = event::Ready::READABLE | event::Ready::READ_HUP | event::Ready::HUP | event::Ready::ERROR;
+        if event.readiness().contains_any(readiness) {
+            self.read_blocked = false;
+        }
+    }
+
+    /// Tracks whether the read result `res` would block, for the readiness
+    /// hint.
+    fn track_read(&mut self, res: io::Result<usize>) -> io::Result<usize> {
+        if let Err(ref err) = res {
+            if err.kind() == io::ErrorKind::WouldBlock {
+                self.read_blocked = true;
+            }
+        }
+        res
+    }
+
+    /// Sets the socket option `name` at `level` to the raw bytes of `value`,
+    /// using `setsockopt(2)`.
+    ///
+    /// This is a low-level escape hatch to set options for which no dedicated
+    /// method exists, e.g. `TCP_CONGESTION` or platform specific options. The
+    /// `level` and `name` are the OS constants, e.g. from the `libc` crate.
+    ///
+    /// # Notes
+    ///
+    /// The bytes are passed as is, the caller is responsible for using the
+    /// layout the OS expects for the option. Most options use a `c_int`, in
+    /// the native byte order (not network byte order), e.g.
+    /// `1i32.to_ne_bytes()` to enable a boolean option. Setting options can
+    /// change the behaviour of the socket in ways this crate doesn't expect,
+    /// e.g. `SO_RCVTIMEO`, use with care.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use gaea::net::{TcpListener, TcpStream};
+    ///
+    /// # let mut listener = TcpListener::bind("127.0.0.1:0".parse()?)?;
+    /// # let address = listener.local_addr()?;
+    /// let mut stream = TcpStream::connect(address)?;
+    ///
+    /// // Set `TCP_NODELAY`, a `c_int` option.
+    /// stream.set_sockopt(libc::IPPROTO_TCP, libc::TCP_NODELAY, &1i32.to_ne_bytes())?;
+    ///
+    /// let mut buf = [0; 4];
+    /// let n = stream.get_sockopt(libc::IPPROTO_TCP, libc::TCP_NODELAY, &mut buf)?;
+    /// assert_eq!(n, 4);
+    /// assert_ne!(i32::from_ne_bytes(buf), 0);
+    /// assert!(stream.nodelay()?);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn set_sockopt(&mut self, level: i32, name: i32, value: &[u8]) -> io::Result<()> {
+        self.inner.set_sockopt(level, name, value)
+    }
+
+    /// Gets the socket option `name` at `level` using `getsockopt(2)`, writing
+    /// the raw bytes into `buf`. On success, returns the length of the option.
+    ///
+    /// If `buf` is too small the option is truncated. See [`set_sockopt`] for
+    /// the byte layout of options.
+    ///
+    /// [`set_sockopt`]: TcpStream::set_sockopt
+    pub fn get_sockopt(&mut self, level: i32, name: i32, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.get_sockopt(level, name, buf)
+    }
+}
+
+/// Order `addresses` alternating between address families, starting with the
+/// family of the first address.
+fn interleave_families(addresses: &[SocketAddr]) -> Vec<SocketAddr> {
+    let first_is_ipv6 = match addresses.first() {
+        Some(address) => address.is_ipv6(),
+        None => false,
+    };
+    let (first, second): (Vec<_>, Vec<_>) = addresses.iter()
+        .partition(|address| address.is_ipv6() == first_is_ipv6);
+    let mut ordered = Vec::with_capacity(addresses.len());
+    let mut first = first.into_iter();
+    let mut second = second.into_iter();
+    loop {
+        match (first.next(), second.next()) {
+            (None, None) => return ordered,
+            (a, b) => ordered.extend(a.into_iter().chain(b).cloned()),
+        }
+    }
+}
+
+/// Returns the result of the non-blocking connect of `stream`, after it
+/// became writable.
+fn connect_result(stream: &mut TcpStream) -> io::Result<()> {
+    if stream.poll_connect()? {
+        Ok(())
+    } else {
+        Err(io::Error::new(io::ErrorKind::NotConnected, "connect not completed"))
+    }
+}
+
+/// State of a [`TcpStream`] connection, see [`TcpStream::connection_state`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ConnState {
+    /// Both reading and writing are possible.
+    Open,
+    /// The peer closed its writing side, reading will return any remaining
+    /// data followed by end of file, but writing is still possible.
+    ReadClosed,
+    /// The peer closed its reading side, writing is no longer possible, but
+    /// reading still is.
+    WriteClosed,
+    /// The connection is closed, or an error occurred.
+    Closed,
+}
+
+impl ConnState {
+    /// Returns true if the peer may still send data, i.e. the state is
+    /// [`Open`] or [`WriteClosed`].
+    ///
+    /// [`Open`]: ConnState::Open
+    /// [`WriteClosed`]: ConnState::WriteClosed
+    pub fn can_read(self) -> bool {
+        match self {
+            ConnState::Open | ConnState::WriteClosed => true,
+            ConnState::ReadClosed | ConnState::Closed => false,
+        }
+    }
+
+    /// Returns true if data can still be sent to the peer, i.e. the state is
+    /// [`Open`] or [`ReadClosed`].
+    ///
+    /// [`Open`]: ConnState::Open
+    /// [`ReadClosed`]: ConnState::ReadClosed
+    pub fn can_write(self) -> bool {
+        match self {
+            ConnState::Open | ConnState::ReadClosed => true,
+            ConnState::WriteClosed | ConnState::Closed => false,
+        }
+    }
 }
 
 impl Read for TcpStream {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        self.inner.read(buf)
+        let res = self.inner.read(buf);
+        self.track_read(res)
     }
 }
 
@@ -140,10 +760,12 @@ impl Write for TcpStream {
 
 impl Evented for TcpStream {
     fn register(&mut self, os_queue: &mut OsQueue, id: event::Id, interests: Interests, opt: RegisterOption) -> io::Result<()> {
+        self.read_blocked = false;
         self.inner.register(os_queue, id, interests, opt)
     }
 
     fn reregister(&mut self, os_queue: &mut OsQueue, id: event::Id, interests: Interests, opt: RegisterOption) -> io::Result<()> {
+        self.read_blocked = false;
         self.inner.reregister(os_queue, id, interests, opt)
     }
 
@@ -157,9 +779,7 @@ impl FromRawFd for TcpStream {
     /// The caller must ensure that the stream is in non-blocking mode when
     /// using this function.
     unsafe fn from_raw_fd(fd: RawFd) -> TcpStream {
-        TcpStream {
-            inner: FromRawFd::from_raw_fd(fd),
-        }
+        TcpStream::from_inner(FromRawFd::from_raw_fd(fd))
     }
 }
 
@@ -239,7 +859,24 @@ impl TcpListener {
     /// This also sets the `SO_REUSEPORT` and `SO_REUSEADDR` options on the
     /// socket.
     pub fn bind(address: SocketAddr) -> io::Result<TcpListener> {
-        sys::TcpListener::bind(address).map(|inner| TcpListener { inner })
+        TcpListener::bind_with_backlog(address, 128)
+    }
+
+    /// Same as [`bind`], but uses `backlog` as the maximum length of the queue
+    /// of pending connections, [`bind`] uses a backlog of 128.
+    ///
+    /// A larger backlog prevents connections from being dropped when a burst
+    /// of connections arrives, before they can be accepted.
+    ///
+    /// [`bind`]: TcpListener::bind
+    ///
+    /// # Notes
+    ///
+    /// The OS silently caps the backlog to a system wide maximum, e.g.
+    /// `net.core.somaxconn` on Linux and `kern.ipc.somaxconn` on FreeBSD and
+    /// macOS. To use a larger backlog this maximum must be raised as well.
+    pub fn bind_with_backlog(address: SocketAddr, backlog: i32) -> io::Result<TcpListener> {
+        sys::TcpListener::bind(address, backlog).map(|inner| TcpListener { inner })
     }
 
     /// Create a independently owned handle to the underlying socket.
@@ -268,7 +905,7 @@ impl TcpListener {
     ///
     /// [`WouldBlock`]: std::io::ErrorKind::WouldBlock
     pub fn accept(&mut self) -> io::Result<(TcpStream, SocketAddr)> {
-        self.inner.accept().map(|(inner, address)| (TcpStream{ inner }, address))
+        self.inner.accept().map(|(inner, address)| (TcpStream::from_inner(inner), address))
     }
 
     /// Returns the local socket address of this listener.
@@ -286,14 +923,85 @@ impl TcpListener {
         self.inner.ttl()
     }
 
+    /// Binds this socket to the network interface named `interface`, e.g.
+    /// `"eth0"`, using the `SO_BINDTODEVICE` option. Passing `None` removes
+    /// the binding.
+    ///
+    /// Only packets received on the interface are processed by the socket and
+    /// send packets are routed via the interface.
+    ///
+    /// # Notes
+    ///
+    /// This is only available on Android and Linux. It requires the
+    /// `CAP_NET_RAW` capability, without it a [`PermissionDenied`] error is
+    /// returned.
+    ///
+    /// [`PermissionDenied`]: std::io::ErrorKind::PermissionDenied
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_bind_device(&mut self, interface: Option<&str>) -> io::Result<()> {
+        self.inner.set_bind_device(interface)
+    }
+
+    /// Enables TCP Fast Open on the listener, using the `TCP_FASTOPEN`
+    /// option. The `queue_length` is the maximum number of pending Fast Open
+    /// requests, i.e. connections that sent data in the SYN packet but are not
+    /// yet accepted. A `queue_length` of `0` disables Fast Open.
+    ///
+    /// Connections made using [`TcpStream::connect_fastopen`] can then send
+    /// data in the SYN packet, saving a round trip.
+    ///
+    /// # Notes
+    ///
+    /// This is only available on Android and Linux. Server side Fast Open
+    /// must also be enabled in the `net.ipv4.tcp_fastopen` sysctl, if it's not
+    /// connections fall back to a regular handshake.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_fastopen(&mut self, queue_length: u32) -> io::Result<()> {
+        self.inner.set_fastopen(queue_length)
+    }
+
+    /// Moves this socket into or out of non-blocking mode.
+    ///
+    /// See [`TcpStream::set_nonblocking`] for more.
+    ///
+    /// [`TcpStream::set_nonblocking`]: TcpStream::set_nonblocking
+    pub fn set_nonblocking(&mut self, nonblocking: bool) -> io::Result<()> {
+        self.inner.set_nonblocking(nonblocking)
+    }
+
     /// Get the value of the `SO_ERROR` option on this socket.
     ///
     /// This will retrieve the stored error in the underlying socket, clearing
     /// the field in the process. This can be useful for checking errors between
     /// calls.
+    ///
+    /// After receiving an event with [error readiness] for this listener this
+    /// should be called to retrieve the error.
+    ///
+    /// [error readiness]: crate::event::Ready::ERROR
     pub fn take_error(&mut self) -> io::Result<Option<io::Error>> {
         self.inner.take_error()
     }
+
+    /// Sets the socket option `name` at `level` to the raw bytes of `value`,
+    /// using `setsockopt(2)`.
+    ///
+    /// See [`TcpStream::set_sockopt`] for more.
+    ///
+    /// [`TcpStream::set_sockopt`]: TcpStream::set_sockopt
+    pub fn set_sockopt(&mut self, level: i32, name: i32, value: &[u8]) -> io::Result<()> {
+        self.inner.set_sockopt(level, name, value)
+    }
+
+    /// Gets the socket option `name` at `level` using `getsockopt(2)`, writing
+    /// the raw bytes into `buf`. On success, returns the length of the option.
+    ///
+    /// See [`TcpStream::set_sockopt`] for more.
+    ///
+    /// [`TcpStream::set_sockopt`]: TcpStream::set_sockopt
+    pub fn get_sockopt(&mut self, level: i32, name: i32, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.get_sockopt(level, name, buf)
+    }
 }
 
 impl Evented for TcpListener {
@@ -1,5 +1,8 @@
 use std::io;
-use std::net::SocketAddr;
+#[cfg(feature = "nightly")]
+use std::io::IoSliceMut;
+use std::mem::MaybeUninit;
+use std::net::{IpAddr, SocketAddr};
 #[cfg(unix)]
 use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 
@@ -334,6 +337,33 @@ impl UdpSocket {
         self.socket.recv(buf)
     }
 
+    /// Receives data from the socket into a possibly uninitialised buffer. On
+    /// success, returns the number of bytes read.
+    ///
+    /// This works like [`recv`], but doesn't require `buf` to be initialised,
+    /// saving the cost of zeroing large buffers before receiving into them. It
+    /// calls the `recv` system call directly.
+    ///
+    /// If no datagrams are available a [`WouldBlock`] error is returned.
+    ///
+    /// [`recv`]: UdpSocket::recv
+    /// [`WouldBlock`]: std::io::ErrorKind::WouldBlock
+    ///
+    /// # Initialisation
+    ///
+    /// On success, returning `n`, the first `n` bytes of `buf` are
+    /// initialised. The remainder of `buf` is left untouched, i.e. it's only
+    /// initialised if it was before the call. On error nothing is initialised.
+    ///
+    /// # Notes
+    ///
+    /// This requires the socket to be [connected].
+    ///
+    /// [connected]: UdpSocket::connect
+    pub fn recv_uninit(&mut self, buf: &mut [MaybeUninit<u8>]) -> io::Result<usize> {
+        self.socket.recv_uninit(buf)
+    }
+
     /// Receives data from the socket, without removing it from the input queue.
     /// On success, returns the number of bytes read and the address from whence
     /// the data came.
@@ -451,14 +481,241 @@ impl UdpSocket {
         self.socket.peek(buf)
     }
 
+    /// Sends multiple datagrams, each to its own address. On success, returns
+    /// the number of datagrams sent.
+    ///
+    /// If not all datagrams could be sent without blocking the number of
+    /// datagrams sent so far is returned. If no datagrams could be sent a
+    /// [`WouldBlock`] error is returned.
+    ///
+    /// [`WouldBlock`]: std::io::ErrorKind::WouldBlock
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use gaea::net::UdpSocket;
+    ///
+    /// let mut socket = UdpSocket::bind("127.0.0.1:7014".parse()?)?;
+    ///
+    /// let address = "127.0.0.1:7015".parse()?;
+    /// let bufs = [(&b"Hello"[..], address), (&b"world"[..], address)];
+    /// let datagrams_sent = socket.send_mmsg(&bufs)?;
+    /// assert_eq!(datagrams_sent, 2);
+    /// #    Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Implementation notes
+    ///
+    /// On Android and Linux this uses a single `sendmmsg(2)` system call, on
+    /// other platforms the datagrams are send one by one.
+    pub fn send_mmsg(&mut self, bufs: &[(&[u8], SocketAddr)]) -> io::Result<usize> {
+        self.socket.send_mmsg(bufs)
+    }
+
+    /// Receives multiple datagrams. On success, returns the number of
+    /// datagrams received.
+    ///
+    /// For each received datagram the buffer is truncated to the number of
+    /// bytes received and the address is set to the address from whence the
+    /// datagram came. If the datagram was larger than the buffer the excess
+    /// bytes are discarded and the boolean is set to `true`, otherwise it's set
+    /// to `false`. Entries after the returned number of datagrams are left
+    /// untouched.
+    ///
+    /// If no datagrams are available a [`WouldBlock`] error is returned, which
+    /// means one should wait for a readable event before calling `recv_mmsg`
+    /// again.
+    ///
+    /// [`WouldBlock`]: std::io::ErrorKind::WouldBlock
+    ///
+    /// # Implementation notes
+    ///
+    /// On Android and Linux this uses a single `recvmmsg(2)` system call, on
+    /// other platforms the datagrams are received one by one.
+    pub fn recv_mmsg(&mut self, bufs: &mut [(&mut [u8], SocketAddr, bool)]) -> io::Result<usize> {
+        self.socket.recv_mmsg(bufs)
+    }
+
+    /// Enables or disables receiving the destination address of datagrams,
+    /// required by [`recv_from_with_info`].
+    ///
+    /// This sets the `IP_PKTINFO` option (`IP_RECVDSTADDR` on FreeBSD and
+    /// OpenBSD) for IPv4 sockets, or `IPV6_RECVPKTINFO` for IPv6 sockets.
+    ///
+    /// [`recv_from_with_info`]: UdpSocket::recv_from_with_info
+    pub fn set_pktinfo(&mut self, pktinfo: bool) -> io::Result<()> {
+        self.socket.set_pktinfo(pktinfo)
+    }
+
+    /// Receives data from the socket. On success, returns the number of bytes
+    /// read, the address from whence the data came and the local address the
+    /// data was send to.
+    ///
+    /// This is useful for sockets bound to an unspecified address, e.g.
+    /// `0.0.0.0`, on hosts with multiple addresses. Replies should be send
+    /// from the returned local address.
+    ///
+    /// This requires [`set_pktinfo`] to be enabled, if it's not an error is
+    /// returned (and the datagram is discarded). The same is true if the
+    /// control messages received with the datagram were truncated, e.g. because
+    /// other options that add control messages are enabled, as the destination
+    /// address could have been discarded.
+    ///
+    /// [`set_pktinfo`]: UdpSocket::set_pktinfo
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use std::net::IpAddr;
+    ///
+    /// use gaea::net::UdpSocket;
+    ///
+    /// // Bind to all addresses.
+    /// let mut socket = UdpSocket::bind("0.0.0.0:0".parse()?)?;
+    /// socket.set_pktinfo(true)?;
+    /// let port = socket.local_addr()?.port();
+    ///
+    /// let mut sender = UdpSocket::bind("127.0.0.1:0".parse()?)?;
+    /// sender.send_to(b"Hello world", ([127, 0, 0, 1], port).into())?;
+    /// # std::thread::sleep(std::time::Duration::from_millis(10));
+    ///
+    /// let mut buf = [0; 20];
+    /// let (n, source, destination) = socket.recv_from_with_info(&mut buf)?;
+    /// assert_eq!(&buf[..n], b"Hello world");
+    /// assert_eq!(source, sender.local_addr()?);
+    /// assert_eq!(destination, IpAddr::from([127, 0, 0, 1]));
+    /// #    Ok(())
+    /// # }
+    /// ```
+    pub fn recv_from_with_info(&mut self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr, IpAddr)> {
+        self.socket.recv_from_with_info(buf)
+    }
+
+    /// Receives a single datagram into multiple buffers. On success, returns
+    /// the number of bytes read and the address from whence the data came.
+    ///
+    /// The buffers are filled in order, e.g. this can be used to receive a
+    /// fixed size header into one buffer and the payload into another, without
+    /// copying. If the datagram is larger than the buffers combined the
+    /// remaining bytes are discarded.
+    ///
+    /// If no datagrams are available a [`WouldBlock`] error is returned, which
+    /// means one should wait for a readable event before calling
+    /// `recv_from_vectored` again.
+    ///
+    /// [`WouldBlock`]: std::io::ErrorKind::WouldBlock
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use std::io::IoSliceMut;
+    ///
+    /// use gaea::net::UdpSocket;
+    ///
+    /// let mut socket = UdpSocket::bind("127.0.0.1:0".parse()?)?;
+    /// let address = socket.local_addr()?;
+    ///
+    /// let mut sender = UdpSocket::bind("127.0.0.1:0".parse()?)?;
+    /// sender.send_to(b"HEADHello world", address)?;
+    /// # std::thread::sleep(std::time::Duration::from_millis(10));
+    ///
+    /// let mut header = [0; 4];
+    /// let mut body = [0; 20];
+    /// let (n, source) = socket.recv_from_vectored(&mut [IoSliceMut::new(&mut header), IoSliceMut::new(&mut body)])?;
+    /// assert_eq!(n, 15);
+    /// assert_eq!(source, sender.local_addr()?);
+    /// assert_eq!(&header, b"HEAD");
+    /// assert_eq!(&body[..n - header.len()], b"Hello world");
+    /// #    Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "nightly")]
+    pub fn recv_from_vectored(&mut self, bufs: &mut [IoSliceMut]) -> io::Result<(usize, SocketAddr)> {
+        self.socket.recv_from_vectored(bufs)
+    }
+
+    /// Sets the value for the `IP_TOS` option on this socket, or
+    /// `IPV6_TCLASS` for IPv6 sockets.
+    ///
+    /// This can be used to set the Differentiated Services Code Point (DSCP)
+    /// of the packets send, the DSCP value must be shifted two bits to the
+    /// left, e.g. `46 << 2` for expedited forwarding.
+    pub fn set_tos(&mut self, tos: u32) -> io::Result<()> {
+        self.socket.set_tos(tos)
+    }
+
+    /// Gets the value of the `IP_TOS` option for this socket, or
+    /// `IPV6_TCLASS` for IPv6 sockets.
+    pub fn tos(&mut self) -> io::Result<u32> {
+        self.socket.tos()
+    }
+
+    /// Binds this socket to the network interface named `interface`, e.g.
+    /// `"eth0"`, using the `SO_BINDTODEVICE` option. Passing `None` removes
+    /// the binding.
+    ///
+    /// Only packets received on the interface are processed by the socket and
+    /// send packets are routed via the interface.
+    ///
+    /// # Notes
+    ///
+    /// This is only available on Android and Linux. It requires the
+    /// `CAP_NET_RAW` capability, without it a [`PermissionDenied`] error is
+    /// returned.
+    ///
+    /// [`PermissionDenied`]: std::io::ErrorKind::PermissionDenied
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_bind_device(&mut self, interface: Option<&str>) -> io::Result<()> {
+        self.socket.set_bind_device(interface)
+    }
+
+    /// Moves this socket into or out of non-blocking mode.
+    ///
+    /// See [`TcpStream::set_nonblocking`] for more.
+    ///
+    /// [`TcpStream::set_nonblocking`]: crate::net::TcpStream::set_nonblocking
+    pub fn set_nonblocking(&mut self, nonblocking: bool) -> io::Result<()> {
+        self.socket.set_nonblocking(nonblocking)
+    }
+
     /// Get the value of the `SO_ERROR` option on this socket.
     ///
     /// This will retrieve the stored error in the underlying socket, clearing
     /// the field in the process. This can be useful for checking errors between
     /// calls.
+    ///
+    /// After receiving an event with [error readiness] for this socket this
+    /// should be called to retrieve the error, e.g. an ICMP error received for
+    /// a connected socket.
+    ///
+    /// [error readiness]: crate::event::Ready::ERROR
     pub fn take_error(&mut self) -> io::Result<Option<io::Error>> {
         self.socket.take_error()
     }
+
+    /// Sets the socket option `name` at `level` to the raw bytes of `value`,
+    /// using `setsockopt(2)`.
+    ///
+    /// See [`TcpStream::set_sockopt`] for more.
+    ///
+    /// [`TcpStream::set_sockopt`]: crate::net::TcpStream::set_sockopt
+    pub fn set_sockopt(&mut self, level: i32, name: i32, value: &[u8]) -> io::Result<()> {
+        self.socket.set_sockopt(level, name, value)
+    }
+
+    /// Gets the socket option `name` at `level` using `getsockopt(2)`, writing
+    /// the raw bytes into `buf`. On success, returns the length of the option.
+    ///
+    /// See [`TcpStream::set_sockopt`] for more.
+    ///
+    /// [`TcpStream::set_sockopt`]: crate::net::TcpStream::set_sockopt
+    pub fn get_sockopt(&mut self, level: i32, name: i32, buf: &mut [u8]) -> io::Result<usize> {
+        self.socket.get_sockopt(level, name, buf)
+    }
 }
 
 impl Evented for UdpSocket {
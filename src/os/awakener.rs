@@ -1,7 +1,12 @@
 use std::io;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
+use log::error;
+
+use crate::event::{self, Ready};
 use crate::os::OsQueue;
-use crate::{event, sys};
+use crate::sys;
 
 /// Awakener allows cross-thread waking of [`OsQueue`].
 ///
@@ -17,9 +22,20 @@ use crate::{event, sys};
 /// the `Awakener` that was dropped, resulting the [`OsQueue`] not being woken
 /// up.
 ///
-/// Only a single `Awakener` should active per [`OsQueue`], the `Awakener` can
-/// be cloned using [`try_clone`] if more are needed. What happens if multiple
-/// `Awakener`s are registered with the same `OsQueue` is undefined.
+/// Waking an `Awakener` is the way to interrupt a thread blocked polling an
+/// [`OsQueue`] without a timeout, e.g. to notify it of a shutdown.
+///
+/// Multiple calls to [`wake`] before the [`OsQueue`] is polled result in a
+/// single event. When the event is returned the `Awakener` is drained (e.g.
+/// the eventfd counter is reset), so polling blocks again afterwards. This is
+/// done by the `OsQueue` used to create the `Awakener`, and by clones of that
+/// `OsQueue` created afterwards.
+///
+/// Multiple `Awakener`s can be created for the same [`OsQueue`], each with its
+/// own id, waking one only returns an event with the id of that `Awakener`.
+/// Each id must only be used by a single `Awakener`, use [`try_clone`] to wake
+/// the same id from multiple threads. What happens if multiple `Awakener`s
+/// are created with the same id is undefined.
 ///
 /// [`Ready::READABLE`]: crate::event::Ready::READABLE
 /// [`wake`]: Awakener::wake
@@ -80,21 +96,131 @@ use crate::{event, sys};
 #[derive(Debug)]
 pub struct Awakener {
     inner: sys::Awakener,
+    /// Readiness passed to `wake_with`, shared with all clones and the
+    /// `OsQueue`.
+    readiness: Arc<AtomicUsize>,
 }
 
 impl Awakener {
     /// Create a new `Awakener`.
     pub fn new(os_queue: &mut OsQueue, id: event::Id) -> io::Result<Awakener> {
-        sys::Awakener::new(os_queue.selector(), id).map(|inner| Awakener { inner })
+        let inner = sys::Awakener::new(os_queue.selector(), id)?;
+        let readiness = Arc::new(AtomicUsize::new(0));
+        os_queue.add_awakener(AwakenerState {
+            id,
+            drainer: inner.drainer()?,
+            readiness: Arc::clone(&readiness),
+        });
+        Ok(Awakener { inner, readiness })
     }
 
     /// Attempts to clone the `Awakener`.
     pub fn try_clone(&self) -> io::Result<Awakener> {
-        self.inner.try_clone().map(|inner| Awakener { inner })
+        self.inner.try_clone().map(|inner| Awakener {
+            inner,
+            readiness: Arc::clone(&self.readiness),
+        })
     }
 
     /// Wake up the [`OsQueue`] associated with this `Awakener`.
+    ///
+    /// This is the same as calling [`wake_with`] with [`Ready::READABLE`].
+    ///
+    /// [`wake_with`]: Awakener::wake_with
+    /// [`Ready::READABLE`]: crate::event::Ready::READABLE
     pub fn wake(&self) -> io::Result<()> {
+        self.wake_with(Ready::READABLE)
+    }
+
+    /// Wake up the [`OsQueue`] associated with this `Awakener`, returning an
+    /// event with `readiness`.
+    ///
+    /// This can be used to signal the reason for waking, for example using
+    /// [`Ready::READABLE`] for new work and [`Ready::HUP`] to request a
+    /// shutdown. If the `Awakener` is woken multiple times before the event is
+    /// returned, the event will contain the readiness of all calls combined.
+    ///
+    /// [`Ready::READABLE`]: crate::event::Ready::READABLE
+    /// [`Ready::HUP`]: crate::event::Ready::HUP
+    ///
+    /// # Notes
+    ///
+    /// The readiness is kept in user space, rather then by the OS, so all
+    /// readiness flags can be used on all platforms. The readiness is only
+    /// returned by the `OsQueue` used to create the `Awakener`, and by clones
+    /// of that `OsQueue` created afterwards, other clones return events with
+    /// [`Ready::READABLE`].
+    ///
+    /// Due to a race between waking and polling an event with the
+    /// combined readiness can be followed by an event with only
+    /// `Ready::READABLE`, even if `wake` wasn't called.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use std::io;
+    ///
+    /// use gaea::{event, poll};
+    /// use gaea::event::{Event, Ready};
+    /// use gaea::os::{Awakener, OsQueue};
+    ///
+    /// const WAKE_ID: event::Id = event::Id(0);
+    ///
+    /// let mut os_queue = OsQueue::new()?;
+    /// let mut events = Vec::new();
+    ///
+    /// let awakener = Awakener::new(&mut os_queue, WAKE_ID)?;
+    ///
+    /// // Request a shutdown.
+    /// awakener.wake_with(Ready::HUP)?;
+    ///
+    /// poll::<_, io::Error>(&mut [&mut os_queue], &mut events, None)?;
+    /// assert_eq!(events, vec![Event::new(WAKE_ID, Ready::HUP)]);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn wake_with(&self, readiness: Ready) -> io::Result<()> {
+        let _ = self.readiness.fetch_or(readiness.bits() as usize, Ordering::SeqCst);
         self.inner.wake()
     }
 }
+
+/// State of an `Awakener` kept by `OsQueue`, see `OsQueue::add_awakener`.
+#[derive(Debug)]
+pub(crate) struct AwakenerState {
+    id: event::Id,
+    /// Clone of the system awakener to drain, if it needs draining.
+    drainer: Option<sys::Awakener>,
+    readiness: Arc<AtomicUsize>,
+}
+
+impl AwakenerState {
+    pub(crate) fn id(&self) -> event::Id {
+        self.id
+    }
+
+    pub(crate) fn try_clone(&self) -> io::Result<AwakenerState> {
+        let drainer = match self.drainer {
+            Some(ref drainer) => Some(drainer.try_clone()?),
+            None => None,
+        };
+        Ok(AwakenerState { id: self.id, drainer, readiness: Arc::clone(&self.readiness) })
+    }
+
+    /// Drain the awakener, returning the readiness to use in the event.
+    pub(crate) fn drain(&self) -> Ready {
+        if let Some(ref drainer) = self.drainer {
+            if let Err(err) = drainer.drain() {
+                // Not fatal, at worst it causes a spurious wake up.
+                error!("error draining awakener: {}", err);
+            }
+        }
+        // Drain before taking the readiness, this way the readiness of a
+        // concurrent call to `wake_with` is never lost.
+        match self.readiness.swap(0, Ordering::SeqCst) {
+            0 => Ready::READABLE,
+            readiness => Ready::from_bits(readiness as u8),
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use std::mem;
+use std::ops::{Index, IndexMut};
+
+use crate::event;
+
+/// Collection of connection state, keyed by [`event::Id`].
+///
+/// `Connections` is a slab: inserting a value returns the id to use when
+/// registering the connection with [`OsQueue`], the id is the index of the
+/// value in the slab. This makes looking up the connection state for an event
+/// cheap. Ids of removed values are reused.
+///
+/// Indexing `Connections` using an id for which no connection exists panics,
+/// use [`get`] or [`get_mut`] if this is not wanted.
+///
+/// [`get`]: Connections::get
+/// [`get_mut`]: Connections::get_mut
+///
+/// [`OsQueue`]: crate::os::OsQueue
+///
+/// # Examples
+///
+/// Dispatching events to the connections that are ready.
+///
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::io::{self, Read, Write};
+///
+/// use gaea::{poll, OsQueue};
+/// use gaea::os::{Connections, Interests, RegisterOption};
+/// use gaea::unix::new_pipe;
+///
+/// let mut os_queue = OsQueue::new()?;
+/// let mut connections = Connections::new();
+///
+/// // Insert the connections and register them using their id.
+/// let (mut sender1, receiver1) = new_pipe()?;
+/// let (_sender2, receiver2) = new_pipe()?;
+/// for receiver in vec![receiver1, receiver2] {
+///     let id = connections.insert(receiver);
+///     os_queue.register(&mut connections[id], id, Interests::READABLE, RegisterOption::EDGE)?;
+/// }
+///
+/// sender1.write_all(b"Hello world")?;
+///
+/// let mut events = Vec::new();
+/// poll::<_, io::Error>(&mut [&mut os_queue], &mut events, None)?;
+///
+/// for event in &events {
+///     // Find the connection that is ready.
+///     if let Some(receiver) = connections.get_mut(event.id()) {
+///         let mut buf = [0; 20];
+///         let n = receiver.read(&mut buf)?;
+///         assert_eq!(&buf[..n], b"Hello world");
+///     }
+/// }
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Connections<T> {
+    entries: Vec<Entry<T>>,
+    /// Index of the first vacant entry, equal to the length of `entries` if
+    /// there are no vacant entries.
+    next: usize,
+    /// Number of occupied entries.
+    len: usize,
+}
+
+#[derive(Debug)]
+enum Entry<T> {
+    Occupied(T),
+    /// Index of the next vacant entry.
+    Vacant(usize),
+}
+
+impl<T> Connections<T> {
+    /// Create a new empty `Connections`.
+    pub fn new() -> Connections<T> {
+        Connections::with_capacity(0)
+    }
+
+    /// Create a new empty `Connections`, with room for `capacity` connections
+    /// before allocating.
+    pub fn with_capacity(capacity: usize) -> Connections<T> {
+        Connections {
+            entries: Vec::with_capacity(capacity),
+            next: 0,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of connections.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if there are no connections.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Insert a new connection, returning its id.
+    pub fn insert(&mut self, value: T) -> event::Id {
+        let index = self.next;
+        if index == self.entries.len() {
+            self.entries.push(Entry::Occupied(value));
+            self.next += 1;
+        } else {
+            match mem::replace(&mut self.entries[index], Entry::Occupied(value)) {
+                Entry::Vacant(next) => self.next = next,
+                Entry::Occupied(_) => unreachable!("invalid vacant entry in Connections"),
+            }
+        }
+        self.len += 1;
+        event::Id(index)
+    }
+
+    /// Returns true if a connection with `id` exists.
+    pub fn contains(&self, id: event::Id) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// Returns a reference to the connection with `id`, if any.
+    pub fn get(&self, id: event::Id) -> Option<&T> {
+        match self.entries.get(id.0) {
+            Some(Entry::Occupied(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the connection with `id`, if any.
+    pub fn get_mut(&mut self, id: event::Id) -> Option<&mut T> {
+        match self.entries.get_mut(id.0) {
+            Some(Entry::Occupied(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Remove the connection with `id`, if any, allowing the id to be reused.
+    pub fn remove(&mut self, id: event::Id) -> Option<T> {
+        let index = id.0;
+        if !self.contains(id) {
+            return None;
+        }
+        match mem::replace(&mut self.entries[index], Entry::Vacant(self.next)) {
+            Entry::Occupied(value) => {
+                self.next = index;
+                self.len -= 1;
+                Some(value)
+            },
+            Entry::Vacant(_) => unreachable!("checked entry is occupied"),
+        }
+    }
+
+    /// Returns an iterator over all connections and their ids.
+    pub fn iter(&self) -> impl Iterator<Item = (event::Id, &T)> {
+        self.entries.iter().enumerate().filter_map(|(index, entry)| match entry {
+            Entry::Occupied(value) => Some((event::Id(index), value)),
+            Entry::Vacant(_) => None,
+        })
+    }
+}
+
+impl<T> Default for Connections<T> {
+    fn default() -> Connections<T> {
+        Connections::new()
+    }
+}
+
+impl<T> Index<event::Id> for Connections<T> {
+    type Output = T;
+
+    fn index(&self, id: event::Id) -> &T {
+        self.get(id).expect("no connection with id")
+    }
+}
+
+impl<T> IndexMut<event::Id> for Connections<T> {
+    fn index_mut(&mut self, id: event::Id) -> &mut T {
+        self.get_mut(id).expect("no connection with id")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event;
+    use crate::os::Connections;
+
+    #[test]
+    fn insert_get_remove() {
+        let mut connections = Connections::new();
+        assert!(connections.is_empty());
+
+        let id1 = connections.insert("a");
+        let id2 = connections.insert("b");
+        assert_eq!(id1, event::Id(0));
+        assert_eq!(id2, event::Id(1));
+        assert_eq!(connections.len(), 2);
+        assert_eq!(connections.get(id1), Some(&"a"));
+        assert_eq!(connections[id2], "b");
+        assert_eq!(connections.get(event::Id(2)), None);
+
+        *connections.get_mut(id1).unwrap() = "c";
+        assert_eq!(connections[id1], "c");
+
+        assert_eq!(connections.remove(id1), Some("c"));
+        assert_eq!(connections.remove(id1), None);
+        assert_eq!(connections.remove(event::Id(100)), None);
+        assert!(!connections.contains(id1));
+        assert_eq!(connections.len(), 1);
+    }
+
+    #[test]
+    fn reuse_ids() {
+        let mut connections = Connections::with_capacity(4);
+        for n in 0..4 {
+            assert_eq!(connections.insert(n), event::Id(n));
+        }
+
+        assert_eq!(connections.remove(event::Id(1)), Some(1));
+        assert_eq!(connections.remove(event::Id(3)), Some(3));
+        // Last removed id is reused first.
+        assert_eq!(connections.insert(30), event::Id(3));
+        assert_eq!(connections.insert(10), event::Id(1));
+        assert_eq!(connections.insert(4), event::Id(4));
+
+        let values: Vec<_> = connections.iter().collect();
+        assert_eq!(values, vec![
+            (event::Id(0), &0),
+            (event::Id(1), &10),
+            (event::Id(2), &2),
+            (event::Id(3), &30),
+            (event::Id(4), &4),
+        ]);
+    }
+
+    #[test]
+    #[should_panic(expected = "no connection with id")]
+    fn index_missing() {
+        let connections: Connections<()> = Connections::new();
+        let _ = &connections[event::Id(0)];
+    }
+}
@@ -2,6 +2,8 @@ use std::fmt;
 use std::num::NonZeroU8;
 use std::ops::BitOr;
 
+use crate::event::Ready;
+
 /// Interests supplied when [registering] an [`Evented`] handle with [`OsQueue`].
 ///
 /// Interests are used in [registering][] [`Evented`] handles with [`OsQueue`],
@@ -20,6 +22,8 @@ pub struct Interests(NonZeroU8);
 
 const READABLE: u8 = 1;
 const WRITABLE: u8 = 1 << 1;
+#[cfg(unix)]
+const PRIORITY: u8 = 1 << 2;
 
 impl Interests {
     /// Readable interest.
@@ -28,8 +32,25 @@ impl Interests {
     /// Writable interest.
     pub const WRITABLE: Interests = Interests(unsafe { NonZeroU8::new_unchecked(WRITABLE) });
 
+    /// Priority interest, this interest is Unix specific.
+    ///
+    /// Used to receive [`Ready::PRIORITY`] readiness, e.g. for out-of-band data
+    /// on a TCP socket or a changed `sysfs` file.
+    ///
+    /// [`Ready::PRIORITY`]: crate::event::Ready::PRIORITY
+    ///
+    /// # Notes
+    ///
+    /// This is only supported on Linux, on platforms that use kqueue this
+    /// interest is ignored and `Ready::PRIORITY` is never set. Registering
+    /// with only this interest on those platforms returns an error with kind
+    /// `InvalidInput`.
+    #[cfg(unix)]
+    pub const PRIORITY: Interests = Interests(unsafe { NonZeroU8::new_unchecked(PRIORITY) });
+
     /// Both readable and writable interests, not public because `Interests`
     /// might be expanded in the future.
+    #[cfg(any(feature = "net", unix, test))]
     pub(crate) const BOTH: Interests = Interests(unsafe { NonZeroU8::new_unchecked(READABLE | WRITABLE) });
 
     /// Returns true if the value includes readable interest.
@@ -43,6 +64,37 @@ impl Interests {
     pub const fn is_writable(self) -> bool {
         self.0.get() & WRITABLE != 0
     }
+
+    /// Returns true if the value includes priority interest.
+    #[inline]
+    #[cfg(unix)]
+    pub const fn is_priority(self) -> bool {
+        self.0.get() & PRIORITY != 0
+    }
+
+    /// Returns the interests matching `ready`, see [`Ready::as_interests`].
+    pub(crate) fn from_ready(ready: Ready) -> Option<Interests> {
+        let mut interests = 0;
+        if ready.is_readable() {
+            interests |= READABLE;
+        }
+        if ready.is_writable() {
+            interests |= WRITABLE;
+        }
+        #[cfg(unix)]
+        {
+            if ready.is_priority() {
+                interests |= PRIORITY;
+            }
+        }
+        NonZeroU8::new(interests).map(Interests)
+    }
+
+    /// Returns the interests in `self` that are not in `other`, or `None` if
+    /// that would leave no interests.
+    pub(crate) fn difference(self, other: Interests) -> Option<Interests> {
+        NonZeroU8::new(self.0.get() & !other.0.get()).map(Interests)
+    }
 }
 
 impl BitOr for Interests {
@@ -53,8 +105,41 @@ impl BitOr for Interests {
     }
 }
 
+/// Converts the interests into the readiness they're monitoring for, e.g.
+/// [`Interests::READABLE`] into [`Ready::READABLE`].
+impl From<Interests> for Ready {
+    fn from(interests: Interests) -> Ready {
+        let mut ready = Ready::EMPTY;
+        if interests.is_readable() {
+            ready |= Ready::READABLE;
+        }
+        if interests.is_writable() {
+            ready |= Ready::WRITABLE;
+        }
+        #[cfg(unix)]
+        {
+            if interests.is_priority() {
+                ready |= Ready::PRIORITY;
+            }
+        }
+        ready
+    }
+}
+
 impl fmt::Debug for Interests {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        #[cfg(unix)]
+        {
+            if self.is_priority() {
+                return f.pad(match (self.is_readable(), self.is_writable()) {
+                    (true, true) => "READABLE | WRITABLE | PRIORITY",
+                    (true, false) => "READABLE | PRIORITY",
+                    (false, true) => "WRITABLE | PRIORITY",
+                    (false, false) => "PRIORITY",
+                });
+            }
+        }
+
         f.pad(match (self.is_readable(), self.is_writable()) {
             (true, true) => "READABLE | WRITABLE",
             (true, false) => "READABLE",
@@ -66,6 +151,7 @@ impl fmt::Debug for Interests {
 
 #[cfg(test)]
 mod tests {
+    use crate::event::Ready;
     use crate::os::Interests;
 
     #[test]
@@ -85,10 +171,61 @@ mod tests {
         assert!(interests.is_writable());
     }
 
+    #[test]
+    fn difference() {
+        assert_eq!(Interests::BOTH.difference(Interests::WRITABLE), Some(Interests::READABLE));
+        assert_eq!(Interests::READABLE.difference(Interests::WRITABLE), Some(Interests::READABLE));
+        assert_eq!(Interests::READABLE.difference(Interests::READABLE), None);
+        assert_eq!(Interests::READABLE.difference(Interests::BOTH), None);
+    }
+
+    #[test]
+    fn ready_conversions() {
+        assert_eq!(Ready::from(Interests::READABLE), Ready::READABLE);
+        assert_eq!(Ready::from(Interests::WRITABLE), Ready::WRITABLE);
+        assert_eq!(Ready::from(Interests::BOTH), Ready::READABLE | Ready::WRITABLE);
+
+        assert_eq!(Ready::READABLE.as_interests(), Some(Interests::READABLE));
+        assert_eq!(Ready::WRITABLE.as_interests(), Some(Interests::WRITABLE));
+        assert_eq!((Ready::READABLE | Ready::WRITABLE).as_interests(), Some(Interests::BOTH));
+
+        // Readiness that can't be registered is ignored.
+        assert_eq!(Ready::EMPTY.as_interests(), None);
+        assert_eq!(Ready::TIMER.as_interests(), None);
+        assert_eq!(Ready::ERROR.as_interests(), None);
+        assert_eq!((Ready::READABLE | Ready::ERROR).as_interests(), Some(Interests::READABLE));
+
+        // Round trip.
+        for &interests in &[Interests::READABLE, Interests::WRITABLE, Interests::BOTH] {
+            assert_eq!(Ready::from(interests).as_interests(), Some(interests));
+        }
+    }
+
     #[test]
     fn fmt_debug() {
         assert_eq!(format!("{:?}", Interests::READABLE), "READABLE");
         assert_eq!(format!("{:?}", Interests::WRITABLE), "WRITABLE");
         assert_eq!(format!("{:?}", Interests::BOTH), "READABLE | WRITABLE");
     }
+
+    #[test]
+    #[cfg(unix)]
+    fn priority() {
+        assert!(Interests::PRIORITY.is_priority());
+        assert!(!Interests::PRIORITY.is_readable());
+        assert!(!Interests::PRIORITY.is_writable());
+        assert!(!Interests::BOTH.is_priority());
+
+        let interests = Interests::READABLE | Interests::PRIORITY;
+        assert!(interests.is_readable());
+        assert!(interests.is_priority());
+
+        assert_eq!(format!("{:?}", Interests::PRIORITY), "PRIORITY");
+        assert_eq!(format!("{:?}", interests), "READABLE | PRIORITY");
+        assert_eq!(format!("{:?}", Interests::BOTH | Interests::PRIORITY), "READABLE | WRITABLE | PRIORITY");
+
+        assert_eq!(Ready::from(interests), Ready::READABLE | Ready::PRIORITY);
+        assert_eq!(Ready::PRIORITY.as_interests(), Some(Interests::PRIORITY));
+        assert_eq!(Ready::HUP.as_interests(), None);
+    }
 }
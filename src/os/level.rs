@@ -0,0 +1,126 @@
+//! Emulation of level-triggered events, see `RegisterOption::EMULATED_LEVEL`.
+
+use std::collections::HashMap;
+use std::io;
+use std::os::unix::io::RawFd;
+
+use crate::event::{self, Event, Ready};
+use crate::os::Interests;
+
+/// Level-triggered event emulation.
+///
+/// Handles are registered with the OS using edge-triggered events. Once an
+/// event is returned for a handle it's added to the ready list, which is
+/// checked each poll using `poll(2)`. As long as the handle is still ready
+/// events are returned for it.
+#[derive(Debug)]
+pub struct LevelEmulation {
+    /// Id and interests of handles registered with emulated level triggers,
+    /// per file descriptor.
+    registered: HashMap<RawFd, (event::Id, Interests)>,
+    /// File descriptors of handles that returned an event and might still be
+    /// ready.
+    ready: Vec<RawFd>,
+}
+
+impl LevelEmulation {
+    pub fn new() -> LevelEmulation {
+        LevelEmulation {
+            registered: HashMap::new(),
+            ready: Vec::new(),
+        }
+    }
+
+    /// Returns `true` if no handles are registered.
+    pub fn is_empty(&self) -> bool {
+        self.registered.is_empty()
+    }
+
+    /// Add, or update, a registration.
+    pub fn register(&mut self, fd: RawFd, id: event::Id, interests: Interests) {
+        let _ = self.registered.insert(fd, (id, interests));
+    }
+
+    /// Remove a registration, if any.
+    pub fn deregister(&mut self, fd: RawFd) {
+        if self.registered.remove(&fd).is_some() {
+            self.ready.retain(|ready_fd| *ready_fd != fd);
+        }
+    }
+
+    /// Called for the id of each event returned by the OS, marking the
+    /// handles registered with emulated level triggers using `id` as ready.
+    pub fn mark_ready(&mut self, id: event::Id) {
+        for (&fd, &(registered_id, _)) in &self.registered {
+            if registered_id == id && !self.ready.contains(&fd) {
+                self.ready.push(fd);
+            }
+        }
+    }
+
+    /// Check which handles in the ready list are still ready, removing the
+    /// ones that are not from the list.
+    pub fn check_ready(&mut self) -> io::Result<Vec<Event>> {
+        if self.ready.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let registered = &self.registered;
+        let mut pollfds: Vec<libc::pollfd> = self.ready.iter()
+            .map(|&fd| {
+                let (_, interests) = registered[&fd];
+                let mut events = 0;
+                if interests.is_readable() {
+                    events |= libc::POLLIN;
+                }
+                if interests.is_writable() {
+                    events |= libc::POLLOUT;
+                }
+                libc::pollfd { fd, events, revents: 0 }
+            })
+            .collect();
+
+        loop {
+            let n = unsafe { libc::poll(pollfds.as_mut_ptr(), pollfds.len() as libc::nfds_t, 0) };
+            if n != -1 {
+                break;
+            }
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err);
+            }
+        }
+
+        let mut events = Vec::new();
+        let mut pollfds = pollfds.iter();
+        self.ready.retain(|fd| {
+            let revents = pollfds.next().map_or(0, |pollfd| pollfd.revents);
+            let readiness = revents_to_ready(revents);
+            if readiness == Ready::EMPTY {
+                false
+            } else {
+                events.push(Event::new(registered[fd].0, readiness));
+                true
+            }
+        });
+        Ok(events)
+    }
+}
+
+/// Convert the `revents` field of `pollfd` into `Ready`.
+fn revents_to_ready(revents: libc::c_short) -> Ready {
+    let mut readiness = Ready::EMPTY;
+    if revents & libc::POLLIN != 0 {
+        readiness |= Ready::READABLE;
+    }
+    if revents & libc::POLLOUT != 0 {
+        readiness |= Ready::WRITABLE;
+    }
+    if revents & libc::POLLERR != 0 {
+        readiness |= Ready::ERROR;
+    }
+    if revents & libc::POLLHUP != 0 {
+        readiness |= Ready::HUP;
+    }
+    readiness
+}
@@ -105,30 +105,58 @@
 //!
 //! On all supported platforms socket operations are handled by using the system
 //! queue. Platform specific extensions (e.g. [`EventedFd`]) allow accessing
-//! other features provided by individual system selectors.
+//! other features provided by individual system selectors, for example kqueue
+//! based platforms provide a kernel backed [`Timer`].
 //!
 //! [`Eventedfd`]: crate::sys::unix::EventedFd
+//! [`Timer`]: crate::os::Timer
 //! [`signalfd`]: http://man7.org/linux/man-pages/man2/signalfd.2.html
 
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
 use std::io;
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, RawFd};
+#[cfg(unix)]
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::Duration;
 
 use log::trace;
 
-use crate::{event, sys};
+use crate::event::{self, Event};
+use crate::sys;
+use self::awakener::AwakenerState;
+#[cfg(unix)]
+use self::level::LevelEmulation;
 
 mod awakener;
+mod connections;
 mod evented;
 mod interests;
+#[cfg(unix)]
+mod level;
 mod option;
+mod registration;
+#[cfg(any(target_os = "freebsd", target_os = "macos",
+          target_os = "netbsd", target_os = "openbsd"))]
+mod timer;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod splice;
 
 pub mod signals;
 
 pub use self::awakener::Awakener;
+pub use self::connections::Connections;
 pub use self::evented::Evented;
 pub use self::interests::Interests;
 pub use self::option::RegisterOption;
+pub use self::registration::{Notifier, Registration};
 pub use self::signals::{Signal, SignalSet, Signals};
+#[cfg(any(target_os = "freebsd", target_os = "macos",
+          target_os = "netbsd", target_os = "openbsd"))]
+pub use self::timer::Timer;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use self::splice::Splicer;
 
 /// Readiness event queue backed by the OS.
 ///
@@ -157,6 +185,21 @@ pub use self::signals::{Signal, SignalSet, Signals};
 #[derive(Debug)]
 pub struct OsQueue {
     selector: sys::Selector,
+    /// Registered file descriptors, shared between cloned handles.
+    #[cfg(unix)]
+    registrations: Arc<Mutex<Registrations>>,
+    /// Used to coalesce events, if enabled, see `OsQueue::set_coalesce`.
+    coalescer: Option<Coalescer>,
+    /// Whether or not to take the error of sockets for events with error
+    /// readiness, see `OsQueue::set_take_errors`.
+    take_errors: bool,
+    /// File descriptors to register in a single call, only `Some` during
+    /// `OsQueue::register_many`.
+    #[cfg(unix)]
+    batch: Option<Vec<BatchedFd>>,
+    /// Awakeners to drain when their event is returned, see
+    /// `OsQueue::add_awakener`.
+    awakeners: Vec<AwakenerState>,
 }
 
 impl OsQueue {
@@ -189,7 +232,121 @@ impl OsQueue {
     /// # }
     /// ```
     pub fn new() -> io::Result<OsQueue> {
-        sys::Selector::new().map(|selector| OsQueue { selector })
+        sys::Selector::new().map(OsQueue::from_selector)
+    }
+
+    /// Create a new OS backed readiness event queue, returning at most
+    /// `capacity` events per poll.
+    ///
+    /// The events are first read into a buffer of `capacity` events, before
+    /// being added to the [event sink]. A larger capacity allows more events to
+    /// be returned in a single system call, while a smaller capacity uses less
+    /// memory. [`OsQueue::new`] uses a capacity of 128 events.
+    ///
+    /// If `capacity` is zero this returns an error with kind `InvalidInput`.
+    ///
+    /// [event sink]: crate::event::Sink
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use gaea::os::OsQueue;
+    ///
+    /// // Return up to 1024 events per poll.
+    /// let os_queue = OsQueue::with_capacity(1024)?;
+    /// #     drop(os_queue);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn with_capacity(capacity: usize) -> io::Result<OsQueue> {
+        if capacity == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                "OsQueue capacity must be larger then zero"));
+        }
+        sys::Selector::with_capacity(capacity).map(OsQueue::from_selector)
+    }
+
+    /// Create a new handle to the same OS backed readiness event queue.
+    ///
+    /// The returned `OsQueue` refers to the same underlying kernel object as
+    /// the original, the file descriptor is duplicated using `fcntl(2)` with
+    /// `F_DUPFD_CLOEXEC`. This means that handles registered with one
+    /// `OsQueue` are also registered with the other, and that events are
+    /// returned from polling either one, but only once.
+    ///
+    /// # Notes
+    ///
+    /// `OsQueue` is designed to be used on a single thread. Moving a clone to
+    /// another thread is possible, but polling both handles concurrently is
+    /// the caller's responsibility, e.g. events for a single handle may be
+    /// returned to either thread.
+    ///
+    /// The registrations tracked by `OsQueue`, e.g. as returned by
+    /// [`interests`], are shared between the handles.
+    ///
+    /// The [signal mask] is copied to the new handle, but changing it on one
+    /// handle doesn't change it on the other.
+    ///
+    /// [`interests`]: OsQueue::interests
+    ///
+    /// [signal mask]: OsQueue::set_signal_mask
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use std::io;
+    ///
+    /// use gaea::os::{Awakener, OsQueue};
+    /// use gaea::{event, poll};
+    /// use gaea::event::{Event, Ready};
+    ///
+    /// let mut os_queue = OsQueue::new()?;
+    /// let mut events = Vec::new();
+    ///
+    /// // Register an awakener using the cloned handle.
+    /// let mut os_queue2 = os_queue.try_clone()?;
+    /// let awakener = Awakener::new(&mut os_queue2, event::Id(0))?;
+    /// awakener.wake()?;
+    ///
+    /// // The event is returned by the original handle.
+    /// poll::<_, io::Error>(&mut [&mut os_queue], &mut events, None)?;
+    /// assert_eq!(events, vec![Event::new(event::Id(0), Ready::READABLE)]);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn try_clone(&self) -> io::Result<OsQueue> {
+        self.selector.try_clone().map(|selector| OsQueue {
+            selector,
+            #[cfg(unix)]
+            registrations: Arc::clone(&self.registrations),
+            coalescer: self.coalescer.as_ref().map(|_| Coalescer::new()),
+            take_errors: self.take_errors,
+            #[cfg(unix)]
+            batch: None,
+            awakeners: Vec::new(),
+        }).and_then(|mut os_queue| {
+            for awakener in &self.awakeners {
+                os_queue.awakeners.push(awakener.try_clone()?);
+            }
+            Ok(os_queue)
+        })
+    }
+
+    /// Create a new `OsQueue` from the system selector, without any
+    /// registrations.
+    fn from_selector(selector: sys::Selector) -> OsQueue {
+        OsQueue {
+            selector,
+            #[cfg(unix)]
+            registrations: Arc::new(Mutex::new(Registrations::new())),
+            coalescer: None,
+            take_errors: false,
+            #[cfg(unix)]
+            batch: None,
+            awakeners: Vec::new(),
+        }
     }
 
     /// Register an [`Evented`] handle with the `OsQueue`.
@@ -238,7 +395,18 @@ impl OsQueue {
     /// that `OsQueue` for the lifetime of the `Evented` handle. This remains
     /// true even if the `Evented` handle is [deregistered].
     ///
+    /// Registering a handle that is already registered returns an error with
+    /// kind `AlreadyExists`, use [`reregister`] to change the registration
+    /// instead. This relies on the registrations tracked by `OsQueue`, which
+    /// only knows about handles registered using their file descriptor (e.g.
+    /// using [`EventedFd`]), and is the same on all platforms, e.g. also for
+    /// oneshot registrations that already fired. Handles closed without being
+    /// deregistered remain tracked, so before their file descriptor can be
+    /// registered again (once reused) it must be [deregistered], which only
+    /// removes the stale registration.
+    ///
     /// [deregistered]: OsQueue::deregister
+    /// [`EventedFd`]: crate::unix::EventedFd
     ///
     /// # Examples
     ///
@@ -281,6 +449,98 @@ impl OsQueue {
         handle.register(self, id, interests, opt)
     }
 
+    /// Register multiple [`Evented`] handles with the `OsQueue`.
+    ///
+    /// This is the same as calling [`register`] for each handle, but on
+    /// platforms that use kqueue the file descriptors of all handles are
+    /// registered using a single system call. On other platforms, e.g. Linux
+    /// (epoll), each handle is still registered separately.
+    ///
+    /// Either all or none of the handles are registered. If registering a
+    /// handle fails the handles that were registered are deregistered again
+    /// and the index (into `handles`) of the first handle that failed is
+    /// returned, along with its error.
+    ///
+    /// [`register`]: OsQueue::register
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use gaea::event;
+    /// use gaea::os::{Interests, OsQueue, RegisterOption};
+    /// use gaea::unix::new_pipe;
+    ///
+    /// let mut os_queue = OsQueue::new()?;
+    /// let (mut sender, mut receiver) = new_pipe()?;
+    ///
+    /// os_queue.register_many(&mut [
+    ///     (&mut sender, event::Id(0), Interests::WRITABLE, RegisterOption::EDGE),
+    ///     (&mut receiver, event::Id(1), Interests::READABLE, RegisterOption::EDGE),
+    /// ]).map_err(|(index, err)| {
+    ///     println!("failed to register handle {}: {}", index, err);
+    ///     err
+    /// })?;
+    /// assert_eq!(os_queue.len(), 2);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    #[cfg(unix)]
+    pub fn register_many(&mut self, handles: &mut [(&mut dyn Evented, event::Id, Interests, RegisterOption)]) -> Result<(), (usize, io::Error)> {
+        trace!("registering {} handles", handles.len());
+        self.batch = Some(Vec::new());
+        // Index into `handles` of each file descriptor in the batch.
+        let mut owners = Vec::new();
+        let mut failed = vec![false; handles.len()];
+        let mut error = None;
+        let mut n_handles = handles.len();
+        for (index, &mut (ref mut handle, id, interests, opt)) in handles.iter_mut().enumerate() {
+            trace!("registering handle: id={}, interests={:?}, opt={:?}", id, interests, opt);
+            let res = handle.register(self, id, interests, opt);
+            owners.resize(self.batch.as_ref().map_or(0, Vec::len), index);
+            if let Err(err) = res {
+                failed[index] = true;
+                error = Some((index, err));
+                n_handles = index + 1;
+                break;
+            }
+        }
+
+        // Register the file descriptors of the handles registered before an
+        // error occurred as well, so we can deregister all handles below.
+        let batch = self.batch.take().unwrap_or_default();
+        let registrations: Vec<_> = batch.iter().map(|batched| batched.os_registration).collect();
+        let results = self.selector.register_many(&registrations);
+        for ((result, batched), &index) in results.into_iter().zip(batch).zip(&owners) {
+            let (fd, id, interests, _) = batched.os_registration;
+            match result {
+                Ok(()) => self.registrations().track(fd, id, interests, batched.opt),
+                Err(err) => {
+                    failed[index] = true;
+                    match error {
+                        // Only return the error of the first handle.
+                        Some((err_index, _)) if err_index <= index => {},
+                        _ => error = Some((index, register_error(err))),
+                    }
+                },
+            }
+        }
+
+        match error {
+            Some((index, err)) => {
+                for (index, handle) in handles[..n_handles].iter_mut().enumerate() {
+                    if !failed[index] {
+                        // We're already returning an error, so we can only
+                        // ignore this one.
+                        let _ = handle.0.deregister(self);
+                    }
+                }
+                Err((index, err))
+            },
+            None => Ok(()),
+        }
+    }
+
     /// Re-register an `Evented` handle with `OsQueue`.
     ///
     /// Re-registering an `Evented` handle allows changing the details of the
@@ -367,9 +627,14 @@ impl OsQueue {
     /// but not all. To properly re-register a handle after deregistering use
     /// `register`, this works on all platforms.
     ///
+    /// Deregistering a handle that isn't registered returns an error with kind
+    /// `NotFound`, see [`try_deregister`] to check whether or not the handle
+    /// was registered.
+    ///
     /// [`oneshot`]: RegisterOption::ONESHOT
     /// [`register`]: OsQueue::register
     /// [`reregister`]: OsQueue::reregister
+    /// [`try_deregister`]: OsQueue::try_deregister
     ///
     /// # Examples
     ///
@@ -410,11 +675,672 @@ impl OsQueue {
         handle.deregister(self)
     }
 
-    /// Get access to the system selector. Used by platform specific code, e.g.
+    /// Deregister an `Evented` handle from `OsQueue`, returning whether or not
+    /// the handle was registered.
+    ///
+    /// This is the same as [`deregister`], but returns `Ok(false)` rather then
+    /// an error with kind `NotFound` if the handle isn't registered. This can
+    /// be used to catch bugs such as deregistering a handle twice, or
+    /// deregistering the wrong handle.
+    ///
+    /// [`deregister`]: OsQueue::deregister
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use gaea::event;
+    /// use gaea::os::{Interests, OsQueue, RegisterOption};
+    /// use gaea::unix::new_pipe;
+    ///
+    /// let mut os_queue = OsQueue::new()?;
+    /// let (_sender, mut receiver) = new_pipe()?;
+    ///
+    /// os_queue.register(&mut receiver, event::Id(0), Interests::READABLE, RegisterOption::EDGE)?;
+    ///
+    /// assert!(os_queue.try_deregister(&mut receiver)?);
+    /// // Already deregistered.
+    /// assert!(!os_queue.try_deregister(&mut receiver)?);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn try_deregister<E>(&mut self, handle: &mut E) -> io::Result<bool>
+        where E: Evented + ?Sized,
+    {
+        match self.deregister(handle) {
+            Ok(()) => Ok(true),
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Set the signal mask to use while blocking in a [`poll`].
+    ///
+    /// While blocking the signal mask of the polling thread is *atomically*
+    /// replaced by `signals`, i.e. only the signals in `signals` are blocked,
+    /// and restored once the poll returns. This allows signals to be blocked
+    /// at all times, except while polling, without the race condition of a
+    /// signal arriving just before the blocking system call is made.
+    ///
+    /// [`poll`]: crate::poll
+    ///
+    /// # Notes
+    ///
+    /// Signals handled by [`Signals`] should be included in `signals`,
+    /// otherwise they might be delivered to the process while polling, rather
+    /// then to `Signals`.
+    ///
+    /// This is only supported on Linux (using `epoll_pwait`), on other
+    /// platforms this returns an error.
+    pub fn set_signal_mask(&mut self, signals: SignalSet) -> io::Result<()> {
+        trace!("setting signal mask: signals={:?}", signals);
+        self.selector.set_sigmask(signals)
+    }
+
+    /// Enable or disable coalescing of events.
+    ///
+    /// If enabled, events with the same id returned by a single poll are
+    /// merged into a single event, combining the readiness of all events. This
+    /// can happen for example if a handle is registered with both readable and
+    /// writable interests on platforms using kqueue, or if the same id is used
+    /// for multiple handles. Coalescing is disabled by default.
+    ///
+    /// The coalesced events are returned in the order the first event for the
+    /// id was returned by the OS.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use std::io;
+    /// use std::os::unix::net::UnixStream;
+    ///
+    /// use gaea::event::{self, Event, Ready};
+    /// use gaea::os::{Interests, OsQueue, RegisterOption};
+    /// use gaea::poll;
+    /// use gaea::unix::EventedFd;
+    /// use std::os::unix::io::AsRawFd;
+    ///
+    /// let mut os_queue = OsQueue::new()?;
+    /// os_queue.set_coalesce(true);
+    /// let mut events = Vec::new();
+    ///
+    /// // Register two streams using the same id.
+    /// let (stream1, _peer1) = UnixStream::pair()?;
+    /// let (stream2, _peer2) = UnixStream::pair()?;
+    /// let id = event::Id(0);
+    /// os_queue.register(&mut EventedFd(&stream1.as_raw_fd()), id, Interests::WRITABLE, RegisterOption::EDGE)?;
+    /// os_queue.register(&mut EventedFd(&stream2.as_raw_fd()), id, Interests::WRITABLE, RegisterOption::EDGE)?;
+    ///
+    /// // Both streams are writable, but only a single event is returned.
+    /// poll::<_, io::Error>(&mut [&mut os_queue], &mut events, None)?;
+    /// assert_eq!(events, vec![Event::new(id, Ready::WRITABLE)]);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn set_coalesce(&mut self, coalesce: bool) {
+        trace!("setting coalesce: coalesce={}", coalesce);
+        self.coalescer = if coalesce { Some(Coalescer::new()) } else { None };
+    }
+
+    /// Returns `true` if events are coalesced, see [`set_coalesce`].
+    ///
+    /// [`set_coalesce`]: OsQueue::set_coalesce
+    pub fn is_coalescing(&self) -> bool {
+        self.coalescer.is_some()
+    }
+
+    /// Enable or disable taking the error of sockets for events with [error
+    /// readiness].
+    ///
+    /// On Linux epoll doesn't return the error for events with error
+    /// readiness. If enabled `OsQueue` takes the pending error of the socket
+    /// using `SO_ERROR` and sets it as the [error of the event]. Taking the
+    /// error clears it, e.g. [`TcpStream::take_error`] and
+    /// [`TcpStream::poll_connect`] will no longer return it. Disabled by
+    /// default.
+    ///
+    /// On platforms that use kqueue the error is always returned by the OS and
+    /// this option does nothing.
+    ///
+    /// [error readiness]: crate::event::Ready::ERROR
+    /// [error of the event]: crate::event::Event::error
+    /// [`TcpStream::take_error`]: crate::net::TcpStream::take_error
+    /// [`TcpStream::poll_connect`]: crate::net::TcpStream::poll_connect
+    ///
+    /// # Notes
+    ///
+    /// This only works for handles registered using their file descriptor
+    /// (e.g. using [`EventedFd`]). If multiple handles are registered using
+    /// the same id the error isn't taken, as it's unknown which handle
+    /// returned the event.
+    ///
+    /// [`EventedFd`]: crate::unix::EventedFd
+    pub fn set_take_errors(&mut self, take_errors: bool) {
+        trace!("setting take errors: take_errors={}", take_errors);
+        self.take_errors = take_errors;
+    }
+
+    /// Returns `true` if the error of sockets is taken, see
+    /// [`set_take_errors`].
+    ///
+    /// [`set_take_errors`]: OsQueue::set_take_errors
+    pub fn is_taking_errors(&self) -> bool {
+        self.take_errors
+    }
+
+    /// Returns the interests currently registered for `id`, or `None` if no
+    /// handle is registered with `id`.
+    ///
+    /// This is kept up to date by [`register`], [`reregister`] and
+    /// [`deregister`].
+    ///
+    /// [`register`]: OsQueue::register
+    /// [`reregister`]: OsQueue::reregister
+    /// [`deregister`]: OsQueue::deregister
+    ///
+    /// # Notes
+    ///
+    /// If multiple handles are registered using the same id this returns the
+    /// union of their interests.
+    ///
+    /// Closing a handle without deregistering it removes it from the OS queue,
+    /// but `OsQueue` can't detect this. Its registration is still returned by
+    /// this method, and counted by [`len`], until the file descriptor is used
+    /// to register another handle or the handle is removed using [`clear`].
+    ///
+    /// Handles that are not registered using their file descriptor, e.g.
+    /// [`Awakener`] and [`Signals`], are not tracked.
+    ///
+    /// [`len`]: OsQueue::len
+    /// [`clear`]: OsQueue::clear
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use gaea::event;
+    /// use gaea::os::{Interests, OsQueue, RegisterOption};
+    /// use gaea::unix::new_pipe;
+    ///
+    /// let mut os_queue = OsQueue::new()?;
+    /// let (_sender, mut receiver) = new_pipe()?;
+    ///
+    /// let id = event::Id(0);
+    /// assert_eq!(os_queue.interests(id), None);
+    ///
+    /// os_queue.register(&mut receiver, id, Interests::READABLE, RegisterOption::EDGE)?;
+    /// assert_eq!(os_queue.interests(id), Some(Interests::READABLE));
+    ///
+    /// os_queue.deregister(&mut receiver)?;
+    /// assert_eq!(os_queue.interests(id), None);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    #[cfg(unix)]
+    pub fn interests(&self, id: event::Id) -> Option<Interests> {
+        self.registrations().fds.values()
+            .filter(|&&(registered_id, _, _)| registered_id == id)
+            .fold(None, |acc, &(_, interests, _)| match acc {
+                Some(acc) => Some(acc | interests),
+                None => Some(interests),
+            })
+    }
+
+    /// Add `interests` to the interests registered for the `Evented` handle.
+    ///
+    /// This [reregisters] `handle` with the union of the current interests, as
+    /// returned by [`interests`], and the provided `interests`, using the same
+    /// `id` and the [`RegisterOption`] it was previously (re)registered with.
+    ///
+    /// If no handle is registered with `id` this returns an error with kind
+    /// `NotFound`, if multiple handles are registered with `id` an error with
+    /// kind `InvalidInput` is returned.
+    ///
+    /// [reregisters]: OsQueue::reregister
+    /// [`interests`]: OsQueue::interests
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use gaea::event;
+    /// use gaea::os::{Interests, OsQueue, RegisterOption};
+    /// use gaea::unix::EventedFd;
+    /// use std::os::unix::io::AsRawFd;
+    /// use std::os::unix::net::UnixStream;
+    ///
+    /// let mut os_queue = OsQueue::new()?;
+    /// let (stream, _) = UnixStream::pair()?;
+    /// let fd = stream.as_raw_fd();
+    ///
+    /// let id = event::Id(0);
+    /// os_queue.register(&mut EventedFd(&fd), id, Interests::READABLE, RegisterOption::EDGE)?;
+    ///
+    /// // Now we also care about writability.
+    /// os_queue.add_interest(&mut EventedFd(&fd), id, Interests::WRITABLE)?;
+    /// assert_eq!(os_queue.interests(id), Some(Interests::READABLE | Interests::WRITABLE));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    #[cfg(unix)]
+    pub fn add_interest<E>(&mut self, handle: &mut E, id: event::Id, interests: Interests) -> io::Result<()>
+        where E: Evented + ?Sized,
+    {
+        let (current, opt) = self.registration(id)?;
+        self.reregister(handle, id, current | interests, opt)
+    }
+
+    /// Remove `interests` from the interests registered for the `Evented`
+    /// handle.
+    ///
+    /// This [reregisters] `handle` with the current interests, as returned by
+    /// [`interests`], without the provided `interests`, using the same `id`
+    /// and the [`RegisterOption`] it was previously (re)registered with. If
+    /// this would leave no interests the handle is [deregistered] instead.
+    ///
+    /// If no handle is registered with `id` this returns an error with kind
+    /// `NotFound`, if multiple handles are registered with `id` an error with
+    /// kind `InvalidInput` is returned.
+    ///
+    /// [reregisters]: OsQueue::reregister
+    /// [`interests`]: OsQueue::interests
+    /// [deregistered]: OsQueue::deregister
+    #[cfg(unix)]
+    pub fn remove_interest<E>(&mut self, handle: &mut E, id: event::Id, interests: Interests) -> io::Result<()>
+        where E: Evented + ?Sized,
+    {
+        let (current, opt) = self.registration(id)?;
+        match current.difference(interests) {
+            Some(interests) => self.reregister(handle, id, interests, opt),
+            None => self.deregister(handle),
+        }
+    }
+
+    /// Rearm a handle registered using the [oneshot] option.
+    ///
+    /// After a handle registered with the oneshot option returned an event it
+    /// is disabled. This [reregisters] `handle` using the same `id`,
+    /// interests and option it was previously (re)registered with, allowing
+    /// it to return events again.
+    ///
+    /// If no handle is registered with `id` this returns an error with kind
+    /// `NotFound`. If multiple handles are registered with `id`, or if the
+    /// handle wasn't registered with the oneshot option, an error with kind
+    /// `InvalidInput` is returned.
+    ///
+    /// [oneshot]: RegisterOption::ONESHOT
+    /// [reregisters]: OsQueue::reregister
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use std::io::{self, Write};
+    /// use std::time::Duration;
+    ///
+    /// use gaea::event::{self, Event, Ready};
+    /// use gaea::os::{Interests, OsQueue, RegisterOption};
+    /// use gaea::poll;
+    /// use gaea::unix::new_pipe;
+    ///
+    /// let mut os_queue = OsQueue::new()?;
+    /// let mut events = Vec::new();
+    ///
+    /// let (mut sender, mut receiver) = new_pipe()?;
+    /// let id = event::Id(0);
+    /// os_queue.register(&mut receiver, id, Interests::READABLE, RegisterOption::ONESHOT)?;
+    ///
+    /// sender.write_all(b"Hello")?;
+    /// poll::<_, io::Error>(&mut [&mut os_queue], &mut events, None)?;
+    /// assert_eq!(events, vec![Event::new(id, Ready::READABLE)]);
+    ///
+    /// // The handle is disabled after returning an event, rearm it to receive
+    /// // another event.
+    /// os_queue.rearm(&mut receiver, id)?;
+    /// events.clear();
+    /// poll::<_, io::Error>(&mut [&mut os_queue], &mut events, Some(Duration::from_millis(100)))?;
+    /// assert_eq!(events, vec![Event::new(id, Ready::READABLE)]);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    #[cfg(unix)]
+    pub fn rearm<E>(&mut self, handle: &mut E, id: event::Id) -> io::Result<()>
+        where E: Evented + ?Sized,
+    {
+        let (interests, opt) = self.registration(id)?;
+        if !opt.is_oneshot() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                "can't rearm handle not registered with the oneshot option"));
+        }
+        self.reregister(handle, id, interests, opt)
+    }
+
+    /// Returns the number of handles registered.
+    ///
+    /// This can be used to detect handles that are never deregistered, e.g. in
+    /// tests.
+    ///
+    /// # Notes
+    ///
+    /// Only handles registered using their file descriptor are counted, see
+    /// [`interests`] for more.
+    ///
+    /// [`interests`]: OsQueue::interests
+    #[cfg(unix)]
+    pub fn len(&self) -> usize {
+        self.registrations().fds.len()
+    }
+
+    /// Returns true if no handles are registered.
+    ///
+    /// See [`len`] for more.
+    ///
+    /// [`len`]: OsQueue::len
+    #[cfg(unix)]
+    pub fn is_empty(&self) -> bool {
+        self.registrations().fds.is_empty()
+    }
+
+    /// Deregister all registered handles.
+    ///
+    /// This is useful when shutting down, as it doesn't require keeping track
+    /// of all registered handles. Handles that are already closed are ignored.
+    ///
+    /// If deregistering a handle fails the error is returned and the remaining
+    /// handles stay registered.
+    ///
+    /// # Notes
+    ///
+    /// Only handles registered using their file descriptor are deregistered,
+    /// see [`interests`] for more.
+    ///
+    /// [`interests`]: OsQueue::interests
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use gaea::event;
+    /// use gaea::os::{Interests, OsQueue, RegisterOption};
+    /// use gaea::unix::new_pipe;
+    ///
+    /// let mut os_queue = OsQueue::new()?;
+    /// let (mut sender, mut receiver) = new_pipe()?;
+    ///
+    /// os_queue.register(&mut sender, event::Id(0), Interests::WRITABLE, RegisterOption::EDGE)?;
+    /// os_queue.register(&mut receiver, event::Id(1), Interests::READABLE, RegisterOption::EDGE)?;
+    /// assert_eq!(os_queue.len(), 2);
+    ///
+    /// os_queue.clear()?;
+    /// assert!(os_queue.is_empty());
+    /// #     Ok(())
+    /// # }
+    /// ```
+    #[cfg(unix)]
+    pub fn clear(&mut self) -> io::Result<()> {
+        trace!("deregistering all handles");
+        let fds: Vec<RawFd> = self.registrations().fds.keys().cloned().collect();
+        for fd in fds {
+            self.deregister_fd(fd)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the registered interests and option of the handle registered
+    /// with `id`.
+    #[cfg(unix)]
+    fn registration(&self, id: event::Id) -> io::Result<(Interests, RegisterOption)> {
+        let registrations = self.registrations();
+        let mut registrations = registrations.fds.values()
+            .filter(|&&(registered_id, _, _)| registered_id == id);
+        match (registrations.next(), registrations.next()) {
+            (Some(&(_, interests, opt)), None) => Ok((interests, opt)),
+            (Some(_), Some(_)) => Err(io::Error::new(io::ErrorKind::InvalidInput,
+                "multiple handles registered with id")),
+            (None, _) => Err(io::Error::new(io::ErrorKind::NotFound, "no handle registered with id")),
+        }
+    }
+
+    /// Register a file descriptor, keeping track of the registration. Used by
     /// `EventedFd`.
+    #[cfg(unix)]
+    pub(crate) fn register_fd(&mut self, fd: RawFd, id: event::Id, interests: Interests, opt: RegisterOption) -> io::Result<()> {
+        // kqueue silently updates an existing registration, while epoll returns
+        // `EEXIST`. To behave the same on all platforms, e.g. for a oneshot
+        // registration that already fired, only the tracked registrations are
+        // checked.
+        if self.registrations().fds.contains_key(&fd) {
+            return Err(already_registered());
+        }
+        let os_opt = opt.os_option()?;
+        if let Some(ref mut batch) = self.batch {
+            // Registered in `register_many`.
+            if batch.iter().any(|batched| batched.os_registration.0 == fd) {
+                return Err(already_registered());
+            }
+            batch.push(BatchedFd { os_registration: (fd, id, interests, os_opt), opt });
+            return Ok(());
+        }
+        self.selector.register(fd, id, interests, os_opt).map_err(register_error)?;
+        self.registrations().track(fd, id, interests, opt);
+        Ok(())
+    }
+
+    /// Reregister a file descriptor, keeping track of the registration. Used
+    /// by `EventedFd`.
+    #[cfg(unix)]
+    pub(crate) fn reregister_fd(&mut self, fd: RawFd, id: event::Id, interests: Interests, opt: RegisterOption) -> io::Result<()> {
+        let previous = self.registrations().fds.get(&fd).map(|&(_, interests, _)| interests);
+        self.selector.reregister(fd, id, interests, opt.os_option()?, previous)?;
+        self.registrations().track(fd, id, interests, opt);
+        Ok(())
+    }
+
+    /// Deregister a file descriptor, keeping track of the registration. Used
+    /// by `EventedFd`.
+    #[cfg(unix)]
+    pub(crate) fn deregister_fd(&mut self, fd: RawFd) -> io::Result<()> {
+        match self.selector.deregister(fd) {
+            Ok(()) => {},
+            // Closed file descriptors are already removed from the OS queue,
+            // only the tracked registration remains.
+            Err(ref err) if (err.kind() == io::ErrorKind::NotFound ||
+                err.raw_os_error() == Some(libc::EBADF)) &&
+                self.registrations().fds.contains_key(&fd) => {},
+            Err(err) => return Err(err),
+        }
+        self.registrations().untrack(fd);
+        Ok(())
+    }
+
+    /// Lock the registrations.
+    #[cfg(unix)]
+    fn registrations(&self) -> MutexGuard<'_, Registrations> {
+        lock_registrations(&self.registrations)
+    }
+
+    /// Get access to the system selector. Used by platform specific code, e.g.
+    /// `Awakener`.
     pub(crate) fn selector(&self) -> &sys::Selector {
         &self.selector
     }
+
+    /// Add an awakener to drain each time an event with its id is returned,
+    /// so that multiple wake ups collapse into a single event with the
+    /// readiness of all wake ups. Replaces any previous awakener with the same
+    /// id. Used by `Awakener`.
+    pub(crate) fn add_awakener(&mut self, awakener: AwakenerState) {
+        match self.awakeners.iter_mut().find(|entry| entry.id() == awakener.id()) {
+            Some(entry) => *entry = awakener,
+            None => self.awakeners.push(awakener),
+        }
+    }
+}
+
+/// Methods to create and control an `OsQueue` backed by a mock selector, only
+/// available with the `mock_selector` feature enabled.
+///
+/// An `OsQueue` created using [`new_mock`] doesn't use the OS selector (epoll
+/// or kqueue), instead it uses a mock selector that records all registrations
+/// and only returns events injected using [`mock_event`]. Polling never
+/// blocks. This allows testing code that registers handles and handles events
+/// deterministically, without relying on the OS. `OsQueue`s created using
+/// [`new`] or [`with_capacity`] are not affected by the feature.
+///
+/// An [`Awakener`] created for a mock `OsQueue` injects its events into the
+/// mock selector, like [`mock_event`] does. Other handles that rely on the OS
+/// selector to deliver events, such as [`Signals`], don't deliver events when
+/// registered with a mock `OsQueue`.
+///
+/// The mock selector is only available on Unix platforms.
+///
+/// [`new_mock`]: OsQueue::new_mock
+/// [`mock_event`]: OsQueue::mock_event
+/// [`new`]: OsQueue::new
+/// [`with_capacity`]: OsQueue::with_capacity
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::io;
+/// use std::os::unix::io::AsRawFd;
+///
+/// use gaea::{event, poll, Event, OsQueue, Ready};
+/// use gaea::os::{Interests, RegisterOption};
+/// use gaea::unix::new_pipe;
+///
+/// let mut os_queue = OsQueue::new_mock()?;
+/// let (_sender, mut receiver) = new_pipe()?;
+/// os_queue.register(&mut receiver, event::Id(0), Interests::READABLE, RegisterOption::EDGE)?;
+/// assert_eq!(os_queue.mock_registration(receiver.as_raw_fd()),
+///     Some((event::Id(0), Interests::READABLE, RegisterOption::EDGE)));
+///
+/// // Pretend the receiver is readable.
+/// os_queue.mock_event(Event::new(event::Id(0), Ready::READABLE));
+///
+/// let mut events = Vec::new();
+/// poll::<_, io::Error>(&mut [&mut os_queue], &mut events, None)?;
+/// assert_eq!(events, vec![Event::new(event::Id(0), Ready::READABLE)]);
+/// #     Ok(())
+/// # }
+/// ```
+#[cfg(all(unix, feature = "mock_selector"))]
+impl OsQueue {
+    /// Create a new readiness event queue backed by a mock selector.
+    pub fn new_mock() -> io::Result<OsQueue> {
+        sys::Selector::new_mock().map(OsQueue::from_selector)
+    }
+
+    /// Returns true if this `OsQueue` is backed by a mock selector, i.e. it
+    /// was created using [`new_mock`] (or is a clone of one).
+    ///
+    /// [`new_mock`]: OsQueue::new_mock
+    pub fn is_mock(&self) -> bool {
+        self.selector.mock().is_some()
+    }
+
+    /// Returns the id, interests and option with which `fd` is registered with
+    /// the mock selector, or `None` if it's not registered. Always returns
+    /// `None` if this `OsQueue` isn't backed by a mock selector.
+    ///
+    /// The returned option is the option passed to the selector, e.g.
+    /// [`RegisterOption::EMULATED_LEVEL`] is registered as
+    /// [`RegisterOption::EDGE`].
+    pub fn mock_registration(&self, fd: RawFd) -> Option<(event::Id, Interests, RegisterOption)> {
+        self.selector.mock().and_then(|selector| selector.registration(fd))
+    }
+
+    /// Inject `event`, it will be returned by the next poll of the `OsQueue`
+    /// (or any of its clones).
+    ///
+    /// # Panics
+    ///
+    /// This will panic if this `OsQueue` isn't backed by a mock selector.
+    pub fn mock_event(&self, event: Event) {
+        match self.selector.mock() {
+            Some(selector) => selector.inject(event),
+            None => panic!("OsQueue::mock_event: not backed by a mock selector, use `OsQueue::new_mock`"),
+        }
+    }
+}
+
+/// Error returned when registering a handle that is already registered.
+#[cfg(unix)]
+fn already_registered() -> io::Error {
+    io::Error::new(io::ErrorKind::AlreadyExists,
+        "handle already registered, use `reregister` to change the registration")
+}
+
+/// Replaces the error returned by the selector if the file descriptor is
+/// already registered.
+#[cfg(unix)]
+fn register_error(err: io::Error) -> io::Error {
+    if err.kind() == io::ErrorKind::AlreadyExists {
+        already_registered()
+    } else {
+        err
+    }
+}
+
+/// Registrations tracked by `OsQueue`.
+#[cfg(unix)]
+#[derive(Debug)]
+struct Registrations {
+    /// Id, interests and option per registered file descriptor, see
+    /// `OsQueue::interests`.
+    fds: HashMap<RawFd, (event::Id, Interests, RegisterOption)>,
+    /// Emulation of level triggers, see `RegisterOption::EMULATED_LEVEL`.
+    level: LevelEmulation,
+}
+
+/// Lock the registrations, ignoring poisoning as they're always valid.
+#[cfg(unix)]
+fn lock_registrations(registrations: &Mutex<Registrations>) -> MutexGuard<'_, Registrations> {
+    match registrations.lock() {
+        Ok(registrations) => registrations,
+        Err(err) => err.into_inner(),
+    }
+}
+
+#[cfg(unix)]
+impl Registrations {
+    fn new() -> Registrations {
+        Registrations {
+            fds: HashMap::new(),
+            level: LevelEmulation::new(),
+        }
+    }
+
+    /// Keep track of a (re)registered file descriptor.
+    fn track(&mut self, fd: RawFd, id: event::Id, interests: Interests, opt: RegisterOption) {
+        // When reregistering the previous level emulation must be replaced.
+        if self.fds.insert(fd, (id, interests, opt)).is_some() {
+            self.level.deregister(fd);
+        }
+        if opt.is_emulated_level() {
+            self.level.register(fd, id, interests);
+        }
+    }
+
+    /// Stop keeping track of a file descriptor.
+    fn untrack(&mut self, fd: RawFd) {
+        if self.fds.remove(&fd).is_some() {
+            self.level.deregister(fd);
+        }
+    }
+}
+
+/// File descriptor registration delayed until the end of
+/// `OsQueue::register_many`.
+#[cfg(unix)]
+#[derive(Debug)]
+struct BatchedFd {
+    /// Arguments for `Selector::register`, using the OS option.
+    os_registration: (RawFd, event::Id, Interests, RegisterOption),
+    /// Option as passed by the user.
+    opt: RegisterOption,
 }
 
 impl<ES, E> event::Source<ES, E> for OsQueue
@@ -426,13 +1352,268 @@ impl<ES, E> event::Source<ES, E> for OsQueue
         None
     }
 
-    fn poll(&mut self, event_sink: &mut ES) -> Result<(), E> {
+    fn poll(&mut self, event_sink: &mut ES) -> Result<usize, E> {
         self.blocking_poll(event_sink, Some(Duration::from_millis(0)))
     }
 
-    fn blocking_poll(&mut self, event_sink: &mut ES, timeout: Option<Duration>) -> Result<(), E> {
+    fn blocking_poll(&mut self, event_sink: &mut ES, timeout: Option<Duration>) -> Result<usize, E> {
         trace!("polling OS queue: timeout={:?}", timeout);
-        self.selector.select(event_sink, timeout)
-            .map_err(Into::into)
+        #[cfg(unix)]
+        {
+            if !self.registrations().level.is_empty() {
+                return self.level_poll(event_sink, timeout).map_err(Into::into);
+            }
+        }
+        self.select(event_sink, timeout).map_err(Into::into)
+    }
+}
+
+impl OsQueue {
+    /// Poll the selector, coalescing events if enabled.
+    fn select<ES>(&mut self, event_sink: &mut ES, timeout: Option<Duration>) -> io::Result<usize>
+        where ES: event::Sink,
+    {
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        let event_sink = &mut ErrorSink {
+            event_sink,
+            registrations: if self.take_errors { Some(&self.registrations) } else { None },
+        };
+        let mut sink = AwakenerSink { event_sink, awakeners: &self.awakeners };
+        match self.coalescer {
+            Some(ref mut coalescer) => coalescer.select(&mut self.selector, &mut sink, timeout),
+            None => self.selector.select(&mut sink, timeout),
+        }
+    }
+
+    /// Poll the selector, adding events for handles registered using emulated
+    /// level triggers that are still ready.
+    #[cfg(unix)]
+    fn level_poll<ES>(&mut self, event_sink: &mut ES, timeout: Option<Duration>) -> io::Result<usize>
+        where ES: event::Sink,
+    {
+        let ready = self.registrations().level.check_ready()?;
+        // Don't block if we already have events to return.
+        let timeout = if ready.is_empty() { timeout } else { Some(Duration::from_millis(0)) };
+
+        let mut seen = Vec::new();
+        let mut n_events = {
+            let mut sink = LevelSink { event_sink, seen: &mut seen };
+            self.select(&mut sink, timeout)?
+        };
+        {
+            let mut registrations = self.registrations();
+            for id in &seen {
+                registrations.level.mark_ready(*id);
+            }
+        }
+
+        let ready = ready.into_iter().filter(|event| !seen.contains(&event.id()));
+        for event in ready {
+            if event_sink.capacity_left().min(1) == 0 {
+                break;
+            }
+            event_sink.add(event);
+            n_events += 1;
+        }
+        Ok(n_events)
+    }
+}
+
+/// Event sink that drains awakeners when their event is added, see
+/// `OsQueue::add_awakener`.
+struct AwakenerSink<'a, ES> {
+    event_sink: &'a mut ES,
+    awakeners: &'a [AwakenerState],
+}
+
+impl<'a, ES> event::Sink for AwakenerSink<'a, ES>
+    where ES: event::Sink,
+{
+    fn capacity_left(&self) -> event::Capacity {
+        self.event_sink.capacity_left()
+    }
+
+    fn add(&mut self, event: Event) {
+        self.event_sink.add(drain_awakener(self.awakeners, event));
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.event_sink.reserve(additional)
+    }
+
+    fn extend<I>(&mut self, events: I)
+        where I: Iterator<Item = Event>,
+    {
+        let awakeners = self.awakeners;
+        self.event_sink.extend(events.map(|event| drain_awakener(awakeners, event)));
+    }
+}
+
+/// Drain the awakener with the id of `event`, if any, setting the readiness
+/// passed to `Awakener::wake_with`.
+fn drain_awakener(awakeners: &[AwakenerState], event: Event) -> Event {
+    match awakeners.iter().find(|awakener| awakener.id() == event.id()) {
+        Some(awakener) => Event::new(event.id(), awakener.drain()),
+        None => event,
+    }
+}
+
+/// Event sink that adds the error to events with error readiness, as epoll
+/// doesn't return it, see `OsQueue::set_take_errors`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+struct ErrorSink<'a, ES> {
+    event_sink: &'a mut ES,
+    /// `None` if taking errors is disabled.
+    registrations: Option<&'a Mutex<Registrations>>,
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl<'a, ES> event::Sink for ErrorSink<'a, ES>
+    where ES: event::Sink,
+{
+    fn capacity_left(&self) -> event::Capacity {
+        self.event_sink.capacity_left()
+    }
+
+    fn add(&mut self, event: Event) {
+        self.event_sink.add(add_error(self.registrations, event));
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.event_sink.reserve(additional)
+    }
+
+    fn extend<I>(&mut self, events: I)
+        where I: Iterator<Item = Event>,
+    {
+        let registrations = self.registrations;
+        self.event_sink.extend(events.map(|event| add_error(registrations, event)));
+    }
+}
+
+/// Add the pending error of the socket registered with the id of `event`, if
+/// `event` has error readiness. If multiple handles are registered with the id
+/// we can't determine which one returned the event, so `event` is returned
+/// as is.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn add_error(registrations: Option<&Mutex<Registrations>>, event: Event) -> Event {
+    let registrations = match registrations {
+        Some(registrations) if event.readiness().is_error() && event.error().is_none() => registrations,
+        _ => return event,
+    };
+
+    let registrations = lock_registrations(registrations);
+    let mut fds = registrations.fds.iter()
+        .filter(|&(_, &(id, _, _))| id == event.id())
+        .map(|(&fd, _)| fd);
+    match (fds.next(), fds.next()) {
+        (Some(fd), None) => Event::with_error(event.id(), event.readiness(), sys::socket_error(fd)),
+        _ => event,
+    }
+}
+
+/// Buffers used to coalesce events, see `OsQueue::set_coalesce`.
+#[derive(Debug)]
+struct Coalescer {
+    /// Events returned by the selector.
+    events: Vec<Event>,
+    /// Index into `events` of the coalesced event per id.
+    indices: HashMap<event::Id, usize>,
+}
+
+impl Coalescer {
+    fn new() -> Coalescer {
+        Coalescer {
+            events: Vec::new(),
+            indices: HashMap::new(),
+        }
+    }
+
+    /// Select events from `selector`, coalescing them before adding them to
+    /// `event_sink`.
+    fn select<ES>(&mut self, selector: &mut sys::Selector, event_sink: &mut ES, timeout: Option<Duration>) -> io::Result<usize>
+        where ES: event::Sink,
+    {
+        self.events.clear();
+        self.indices.clear();
+        let mut sink = CoalesceSink {
+            coalescer: self,
+            capacity: event_sink.capacity_left(),
+        };
+        let _ = selector.select(&mut sink, timeout)?;
+        let n_events = self.events.len();
+        event_sink.reserve(n_events);
+        event_sink.extend(self.events.drain(..));
+        Ok(n_events)
+    }
+}
+
+/// Event sink used by `Coalescer`, limited to the capacity of the actual event
+/// sink.
+struct CoalesceSink<'a> {
+    coalescer: &'a mut Coalescer,
+    capacity: event::Capacity,
+}
+
+impl<'a> event::Sink for CoalesceSink<'a> {
+    fn capacity_left(&self) -> event::Capacity {
+        self.capacity
+    }
+
+    fn add(&mut self, event: Event) {
+        let events = &mut self.coalescer.events;
+        match self.coalescer.indices.entry(event.id()) {
+            Entry::Occupied(entry) => {
+                let coalesced = &mut events[*entry.get()];
+                let error = coalesced.error().or_else(|| event.error()).unwrap_or(0);
+                *coalesced = Event::with_error(event.id(),
+                    coalesced.readiness() | event.readiness(), error);
+            },
+            Entry::Vacant(entry) => {
+                let _ = entry.insert(events.len());
+                events.push(event);
+            },
+        }
+    }
+    fn reserve(&mut self, additional: usize) {
+        self.coalescer.events.reserve(additional)
+    }
+}
+
+/// Event sink wrapper used with emulated level triggers, keeping track of the
+/// events returned by the OS.
+#[cfg(unix)]
+struct LevelSink<'a, ES> {
+    event_sink: &'a mut ES,
+    seen: &'a mut Vec<event::Id>,
+}
+
+#[cfg(unix)]
+impl<'a, ES> event::Sink for LevelSink<'a, ES>
+    where ES: event::Sink,
+{
+    fn capacity_left(&self) -> event::Capacity {
+        self.event_sink.capacity_left()
+    }
+
+    fn add(&mut self, event: Event) {
+        self.seen.push(event.id());
+        self.event_sink.add(event);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.seen.reserve(additional);
+        self.event_sink.reserve(additional)
+    }
+}
+
+/// Returns the underlying epoll or kqueue file descriptor.
+///
+/// This can be used to monitor the `OsQueue` itself, e.g. by registering it
+/// with another `OsQueue`. It will be readable once events are available.
+#[cfg(unix)]
+impl AsRawFd for OsQueue {
+    fn as_raw_fd(&self) -> RawFd {
+        self.selector.as_raw_fd()
     }
 }
@@ -1,4 +1,5 @@
 use std::fmt;
+use std::io;
 use std::ops::BitOr;
 
 /// Option supplied when [registering] an [`Evented`] handle with [`OsQueue`].
@@ -83,9 +84,46 @@ use std::ops::BitOr;
 /// [`WouldBlock`]: std::io::ErrorKind::WouldBlock
 /// [`reregister`]: crate::os::OsQueue::reregister
 ///
+/// # Exclusive wake ups
+///
+/// When multiple [`OsQueue`]s, for example one per thread, have the same
+/// `Evented` handle registered (e.g. a `TcpListener`) all of them will be woken
+/// when the handle becomes ready, while only one of them can actually accept
+/// the incoming connection. To avoid this thundering herd problem the
+/// [exclusive] option can be used, which makes the OS wake only one (or more)
+/// of the `OsQueue`s, instead of all of them.
+///
+/// [exclusive]: crate::os::RegisterOption::EXCLUSIVE
+///
+/// # Emulated level triggers
+///
+/// Not all handles behave the same using level-triggered events on all
+/// platforms. To get consistent level-triggered events across platforms the
+/// [emulated level] option can be used. With it the handle is registered using
+/// edge-triggered events and `OsQueue` itself keeps returning events for the
+/// handle, using the same id, until it's no longer ready (e.g. until all data
+/// is read from a socket). This requires an additional system call per poll if
+/// any handles registered using the option are ready, so it should only be used
+/// if needed.
+///
+/// [emulated level]: crate::os::RegisterOption::EMULATED_LEVEL
+///
 /// # Notes
 ///
 /// It is not possible to combine edge and level triggers.
+///
+/// The emulated level option can't be combined with the edge or oneshot
+/// options, doing so will return an [`InvalidInput`] error.
+///
+/// The exclusive option can't be combined with the oneshot option, registering
+/// a handle with both options will return an [`InvalidInput`] error. The same
+/// is true for registering with the exclusive option and [priority] interest.
+/// Furthermore exclusive can only be used when [registering], using it in
+/// [reregistering] will also return an error.
+///
+/// [`InvalidInput`]: std::io::ErrorKind::InvalidInput
+/// [priority]: crate::os::Interests::PRIORITY
+/// [reregistering]: crate::os::OsQueue::reregister
 #[derive(Copy, Clone, Eq, PartialEq)]
 #[repr(transparent)]
 pub struct RegisterOption(u8);
@@ -93,6 +131,9 @@ pub struct RegisterOption(u8);
 // Level trigger is 0.
 const EDGE: u8    = 1;
 const ONESHOT: u8 = 1 << 1;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const EXCLUSIVE: u8 = 1 << 2;
+const EMULATED_LEVEL: u8 = 1 << 3;
 
 impl RegisterOption {
     /// Level-triggered notifications.
@@ -104,7 +145,58 @@ impl RegisterOption {
     /// Oneshot notifications.
     pub const ONESHOT: RegisterOption = RegisterOption(ONESHOT);
 
+    /// Exclusive wake up notifications, this option is Linux specific.
+    ///
+    /// See [exclusive wake ups] for more information.
+    ///
+    /// [exclusive wake ups]: #exclusive-wake-ups
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub const EXCLUSIVE: RegisterOption = RegisterOption(EXCLUSIVE);
+
+    /// Level-triggered notifications emulated by `OsQueue`.
+    ///
+    /// See [emulated level triggers] for more information.
+    ///
+    /// [emulated level triggers]: #emulated-level-triggers
+    pub const EMULATED_LEVEL: RegisterOption = RegisterOption(EMULATED_LEVEL);
+
+    /// Edge-triggered oneshot notifications, same as `EDGE | ONESHOT`.
+    pub const EDGE_ONESHOT: RegisterOption = RegisterOption(EDGE | ONESHOT);
+
+    /// Level-triggered notifications, same as [`RegisterOption::LEVEL`].
+    ///
+    /// Can be combined with [`oneshot`], e.g.
+    /// `RegisterOption::level().oneshot()`.
+    ///
+    /// [`oneshot`]: RegisterOption::oneshot
+    #[inline]
+    pub const fn level() -> RegisterOption {
+        RegisterOption::LEVEL
+    }
+
+    /// Edge-triggered notifications, same as [`RegisterOption::EDGE`].
+    ///
+    /// Can be combined with [`oneshot`], e.g.
+    /// `RegisterOption::edge().oneshot()`.
+    ///
+    /// [`oneshot`]: RegisterOption::oneshot
+    #[inline]
+    pub const fn edge() -> RegisterOption {
+        RegisterOption::EDGE
+    }
+
+    /// Returns the option with oneshot notification added, same as combining
+    /// it with [`RegisterOption::ONESHOT`].
+    #[inline]
+    pub const fn oneshot(self) -> RegisterOption {
+        RegisterOption(self.0 | ONESHOT)
+    }
+
     /// Returns true if the value includes level trigger.
+    ///
+    /// This includes [emulated level triggers].
+    ///
+    /// [emulated level triggers]: RegisterOption::EMULATED_LEVEL
     #[inline]
     pub const fn is_level(self) -> bool {
         !self.is_edge()
@@ -121,6 +213,32 @@ impl RegisterOption {
     pub const fn is_oneshot(self) -> bool {
         self.0 & ONESHOT != 0
     }
+
+    /// Returns true if the value includes exclusive wake up notification.
+    #[inline]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub const fn is_exclusive(self) -> bool {
+        self.0 & EXCLUSIVE != 0
+    }
+
+    /// Returns true if the value includes emulated level trigger.
+    #[inline]
+    pub const fn is_emulated_level(self) -> bool {
+        self.0 & EMULATED_LEVEL != 0
+    }
+
+    /// Returns the option to register the handle with the OS, i.e. edge
+    /// triggers if level triggers are emulated.
+    pub(crate) fn os_option(self) -> io::Result<RegisterOption> {
+        if !self.is_emulated_level() {
+            Ok(self)
+        } else if self.is_edge() || self.is_oneshot() {
+            Err(io::Error::new(io::ErrorKind::InvalidInput,
+                "can't combine emulated level option with edge or oneshot options"))
+        } else {
+            Ok(RegisterOption((self.0 & !EMULATED_LEVEL) | EDGE))
+        }
+    }
 }
 
 impl BitOr for RegisterOption {
@@ -133,17 +251,32 @@ impl BitOr for RegisterOption {
 
 impl fmt::Debug for RegisterOption {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.pad(match (self.is_edge(), self.is_oneshot()) {
+        // Emulated level can't be combined with edge, or oneshot, but we still
+        // show all options that are set.
+        let mut options = String::from(match (self.is_emulated_level(), self.is_edge()) {
             (false, false) => "LEVEL",
-            (true, false) => "EDGE",
-            (false, true) => "LEVEL | ONESHOT",
-            (true, true) => "EDGE | ONESHOT",
-        })
+            (false, true) => "EDGE",
+            (true, false) => "EMULATED_LEVEL",
+            (true, true) => "EMULATED_LEVEL | EDGE",
+        });
+        if self.is_oneshot() {
+            options.push_str(" | ONESHOT");
+        }
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        {
+            if self.is_exclusive() {
+                options.push_str(" | EXCLUSIVE");
+            }
+        }
+        // Use `pad` to support width and alignment.
+        f.pad(&options)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::io;
+
     use crate::os::RegisterOption;
 
     #[test]
@@ -174,6 +307,21 @@ mod tests {
         assert!(opt.is_oneshot());
     }
 
+    #[test]
+    fn constructors() {
+        assert_eq!(RegisterOption::level(), RegisterOption::LEVEL);
+        assert_eq!(RegisterOption::edge(), RegisterOption::EDGE);
+        assert_eq!(RegisterOption::level().oneshot(), RegisterOption::LEVEL | RegisterOption::ONESHOT);
+        assert_eq!(RegisterOption::edge().oneshot(), RegisterOption::EDGE | RegisterOption::ONESHOT);
+        assert_eq!(RegisterOption::EDGE_ONESHOT, RegisterOption::edge().oneshot());
+
+        let opt = RegisterOption::edge().oneshot();
+        assert!(opt.is_edge());
+        assert!(!opt.is_level());
+        assert!(opt.is_oneshot());
+        assert_eq!(format!("{:?}", opt), "EDGE | ONESHOT");
+    }
+
     #[test]
     fn fmt_debug() {
         assert_eq!(format!("{:?}", RegisterOption::LEVEL), "LEVEL");
@@ -181,5 +329,48 @@ mod tests {
         assert_eq!(format!("{:?}", RegisterOption::ONESHOT), "LEVEL | ONESHOT");
         assert_eq!(format!("{:?}", RegisterOption::LEVEL | RegisterOption::ONESHOT), "LEVEL | ONESHOT");
         assert_eq!(format!("{:?}", RegisterOption::EDGE | RegisterOption::ONESHOT), "EDGE | ONESHOT");
+        assert_eq!(format!("{:>20?}", RegisterOption::EDGE | RegisterOption::ONESHOT), "      EDGE | ONESHOT");
+        assert_eq!(format!("{:<8?}|", RegisterOption::EDGE), "EDGE    |");
+    }
+
+    #[test]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    fn exclusive() {
+        assert!(!RegisterOption::LEVEL.is_exclusive());
+        assert!(!RegisterOption::EDGE.is_exclusive());
+        assert!(!RegisterOption::ONESHOT.is_exclusive());
+
+        let opt = RegisterOption::EDGE | RegisterOption::EXCLUSIVE;
+        assert!(opt.is_edge());
+        assert!(!opt.is_oneshot());
+        assert!(opt.is_exclusive());
+
+        assert_eq!(format!("{:?}", RegisterOption::EXCLUSIVE), "LEVEL | EXCLUSIVE");
+        assert_eq!(format!("{:?}", opt), "EDGE | EXCLUSIVE");
+    }
+
+    #[test]
+    fn emulated_level() {
+        assert!(!RegisterOption::LEVEL.is_emulated_level());
+        assert!(!RegisterOption::EDGE.is_emulated_level());
+        assert!(!RegisterOption::ONESHOT.is_emulated_level());
+
+        let opt = RegisterOption::EMULATED_LEVEL;
+        assert!(opt.is_level());
+        assert!(!opt.is_edge());
+        assert!(!opt.is_oneshot());
+        assert!(opt.is_emulated_level());
+        assert_eq!(format!("{:?}", opt), "EMULATED_LEVEL");
+        assert_eq!(format!("{:?}", opt | RegisterOption::EDGE), "EMULATED_LEVEL | EDGE");
+        assert_eq!(format!("{:?}", opt | RegisterOption::ONESHOT), "EMULATED_LEVEL | ONESHOT");
+
+        assert_eq!(opt.os_option().unwrap(), RegisterOption::EDGE);
+        assert_eq!(RegisterOption::LEVEL.os_option().unwrap(), RegisterOption::LEVEL);
+        assert!(RegisterOption::EDGE.os_option().unwrap().is_edge());
+
+        let err = (opt | RegisterOption::EDGE).os_option().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err = (opt | RegisterOption::ONESHOT).os_option().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
     }
 }
@@ -0,0 +1,246 @@
+use std::io;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use log::trace;
+
+use crate::event::{self, Event, Ready};
+use crate::os::Awakener;
+
+/// User space event source that can be notified from other threads.
+///
+/// A `Registration` is created together with a [`Notifier`]. Each call to
+/// [`Notifier::notify`] adds an event with the id of the registration, which
+/// is returned when polling the `Registration`, alternatively
+/// [`Notifier::set_readiness`] can be used to combine multiple notifications
+/// into a single event. Furthermore it wakes the [`OsQueue`] associated with
+/// the [`Awakener`], so that a call to [`poll`] blocked on the `OsQueue`
+/// returns.
+///
+/// [`OsQueue`]: crate::os::OsQueue
+/// [`poll`]: crate::poll
+///
+/// # Notes
+///
+/// Waking the `OsQueue` returns an event with the id of the `Awakener`, not of
+/// the `Registration`. The event with the id of the `Registration` is only
+/// returned when the `Registration` itself is polled, so it should be passed
+/// to [`poll`] together with the `OsQueue`.
+///
+/// Polling a `Registration` never returns an error.
+///
+/// # Dropping
+///
+/// Dropping the `Registration` removes all pending events and marks the
+/// notifiers as dead, after which [`Notifier::notify`] will return an error.
+/// This way no events are returned for a registration that no longer exists.
+///
+/// The `Registration` should be dropped before the `OsQueue` it's associated
+/// with (via the `Awakener`) is dropped, or the id of the registration reused.
+/// Otherwise a notifier could wake an `OsQueue` that is no longer polled, or
+/// add events for an id that is already used by another handle.
+///
+/// # Examples
+///
+/// Notifying the registration from another thread, while blocking in poll
+/// without a timeout.
+///
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::io;
+/// use std::thread;
+///
+/// use gaea::{event, poll, Event, OsQueue, Ready};
+/// use gaea::os::{Awakener, Registration};
+///
+/// const WAKE_ID: event::Id = event::Id(0);
+/// const REGISTRATION_ID: event::Id = event::Id(1);
+///
+/// let mut os_queue = OsQueue::new()?;
+/// let awakener = Awakener::new(&mut os_queue, WAKE_ID)?;
+/// let (mut registration, notifier) = Registration::new(&awakener, REGISTRATION_ID)?;
+///
+/// let handle = thread::spawn(move || {
+///     notifier.notify(Ready::READABLE).expect("unable to notify");
+/// });
+///
+/// let mut events = Vec::new();
+/// poll::<_, io::Error>(&mut [&mut os_queue, &mut registration], &mut events, None)?;
+/// assert!(events.contains(&Event::new(REGISTRATION_ID, Ready::READABLE)));
+/// # handle.join().unwrap();
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Registration {
+    shared: Arc<Shared>,
+}
+
+/// Notifier of a [`Registration`].
+///
+/// The notifier can be cloned and sent to other threads to notify the
+/// `Registration` from there.
+#[derive(Clone, Debug)]
+pub struct Notifier {
+    shared: Arc<Shared>,
+}
+
+/// State shared between `Registration` and `Notifier`.
+#[derive(Debug)]
+struct Shared {
+    id: event::Id,
+    /// Pending notifications, `None` if the `Registration` is dropped.
+    pending: Mutex<Option<Pending>>,
+    awakener: Awakener,
+}
+
+/// Notifications not yet returned by polling the `Registration`.
+#[derive(Debug)]
+struct Pending {
+    /// Events added by `Notifier::notify`.
+    events: Vec<Event>,
+    /// Readiness accumulated by `Notifier::set_readiness`.
+    readiness: Ready,
+}
+
+impl Pending {
+    fn is_empty(&self) -> bool {
+        self.events.is_empty() && self.readiness == Ready::EMPTY
+    }
+}
+
+impl Registration {
+    /// Create a new `Registration` with the provided `id`.
+    ///
+    /// Calling [`Notifier::notify`] will wake the `OsQueue` associated with
+    /// `awakener`, the awakener is cloned.
+    pub fn new(awakener: &Awakener, id: event::Id) -> io::Result<(Registration, Notifier)> {
+        let shared = Arc::new(Shared {
+            id,
+            pending: Mutex::new(Some(Pending {
+                events: Vec::new(),
+                readiness: Ready::EMPTY,
+            })),
+            awakener: awakener.try_clone()?,
+        });
+        let notifier = Notifier { shared: Arc::clone(&shared) };
+        Ok((Registration { shared }, notifier))
+    }
+
+    /// Returns the id of the registration.
+    pub fn id(&self) -> event::Id {
+        self.shared.id
+    }
+}
+
+impl<ES, E> event::Source<ES, E> for Registration
+    where ES: event::Sink,
+{
+    fn max_timeout(&self) -> Option<Duration> {
+        match *self.shared.pending() {
+            Some(ref pending) if !pending.is_empty() => Some(Duration::from_millis(0)),
+            _ => None,
+        }
+    }
+
+    fn poll(&mut self, event_sink: &mut ES) -> Result<usize, E> {
+        trace!("polling registration: id={}", self.shared.id);
+        let mut pending = self.shared.pending();
+        let pending = match *pending {
+            Some(ref mut pending) => pending,
+            None => return Ok(0),
+        };
+
+        let mut n_events = 0;
+        if pending.readiness != Ready::EMPTY && event_sink.capacity_left().min(1) == 1 {
+            event_sink.add(Event::new(self.shared.id, pending.readiness));
+            pending.readiness = Ready::EMPTY;
+            n_events += 1;
+        }
+        let n = event_sink.capacity_left().min(pending.events.len());
+        event_sink.extend(pending.events.drain(..n));
+        Ok(n_events + n)
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        trace!("dropping registration: id={}", self.shared.id);
+        *self.shared.pending() = None;
+    }
+}
+
+impl Notifier {
+    /// Notify the [`Registration`] of `readiness`.
+    ///
+    /// This adds an event with the `readiness` and the id of the registration
+    /// and wakes the associated `OsQueue`. Each call adds a new event, use
+    /// [`set_readiness`] to combine notifications into a single event.
+    ///
+    /// If the `Registration` is dropped this returns an error with kind
+    /// [`NotConnected`], without waking the `OsQueue`.
+    ///
+    /// [`set_readiness`]: Notifier::set_readiness
+    /// [`NotConnected`]: std::io::ErrorKind::NotConnected
+    pub fn notify(&self, readiness: Ready) -> io::Result<()> {
+        trace!("notifying registration: id={}, readiness={:?}", self.shared.id, readiness);
+        match *self.shared.pending() {
+            Some(ref mut pending) => pending.events.push(Event::new(self.shared.id, readiness)),
+            None => return Err(dropped_registration()),
+        }
+        self.shared.awakener.wake()
+    }
+
+    /// Add `readiness` to the pending readiness of the [`Registration`].
+    ///
+    /// Different from [`notify`] this doesn't add a new event for each call,
+    /// instead the readiness is accumulated until the `Registration` is polled,
+    /// returning a single event with all readiness set. For example setting
+    /// readable and then writable readiness, before polling, returns a single
+    /// event with both readable and writable readiness.
+    ///
+    /// The `OsQueue` is only woken if no readiness was pending.
+    ///
+    /// If the `Registration` is dropped this returns an error with kind
+    /// [`NotConnected`].
+    ///
+    /// [`notify`]: Notifier::notify
+    /// [`NotConnected`]: std::io::ErrorKind::NotConnected
+    pub fn set_readiness(&self, readiness: Ready) -> io::Result<()> {
+        trace!("setting registration readiness: id={}, readiness={:?}", self.shared.id, readiness);
+        let wake = match *self.shared.pending() {
+            Some(ref mut pending) => {
+                let wake = pending.readiness == Ready::EMPTY;
+                pending.readiness |= readiness;
+                wake
+            },
+            None => return Err(dropped_registration()),
+        };
+        if wake {
+            self.shared.awakener.wake()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns `true` if the `Registration` is dropped.
+    pub fn is_dead(&self) -> bool {
+        self.shared.pending().is_none()
+    }
+}
+
+impl Shared {
+    /// Lock the pending notifications, ignoring poisoning as the state is
+    /// always valid.
+    fn pending(&self) -> MutexGuard<'_, Option<Pending>> {
+        match self.pending.lock() {
+            Ok(pending) => pending,
+            Err(err) => err.into_inner(),
+        }
+    }
+}
+
+/// Error returned when notifying a dropped `Registration`.
+fn dropped_registration() -> io::Error {
+    io::Error::new(io::ErrorKind::NotConnected, "registration is dropped")
+}
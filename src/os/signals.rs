@@ -17,13 +17,21 @@ use crate::sys;
 /// using `sigprocmask`. This means that the program is not interrupt, or in any
 /// way notified of signal until the assiocated [`OsQueue`] is [polled].
 ///
+/// `sigprocmask` only blocks the signals for the calling thread. A signal send
+/// to the process can be delivered to any thread that doesn't block it, in
+/// which case the default action of the signal is taken, e.g. terminating the
+/// process. To prevent this create `Signals` before spawning any threads, as
+/// threads inherit the signal mask, or block (or ignore, using `SIG_IGN`) the
+/// signals in all other threads.
+///
 /// [polled]: crate::poll
 ///
 /// # Implementation notes
 ///
 /// On platforms that support kqueue this will use the `EVFILT_SIGNAL` event
 /// filter, see [implementation notes of the `os` module] to see what platform
-/// supports kqueue. On Linux it uses [signalfd].
+/// supports kqueue. `EVFILT_SIGNAL` records all attempts to deliver the signal,
+/// even if it's blocked, without consuming it. On Linux it uses [signalfd].
 ///
 /// [implementation notes of the `os` module]: ../index.html#implementation-notes
 /// [signalfd]: http://man7.org/linux/man-pages/man2/signalfd.2.html
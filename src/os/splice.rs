@@ -0,0 +1,93 @@
+use std::io;
+use std::os::unix::io::AsRawFd;
+
+use crate::sys;
+
+/// Moves data between two file descriptors without copying it through user
+/// space.
+///
+/// This is useful for proxies, which only relay data between two connections.
+///
+/// `splice(2)` requires one side to be a pipe, so data is moved through a pipe
+/// owned by the `Splicer`. Use a single `Splicer` per direction of a
+/// connection, as data left in the pipe is always written to the `to` file
+/// descriptor of the next call to [`splice`].
+///
+/// [`splice`]: Splicer::splice
+///
+/// # Notes
+///
+/// This is only available on Android and Linux.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::io::{Read, Write};
+/// use std::net::{TcpListener, TcpStream};
+///
+/// use gaea::os::Splicer;
+///
+/// // Create two connections, the data written into `client1` should be
+/// // relayed to `client2`.
+/// let listener = TcpListener::bind("127.0.0.1:0")?;
+/// let address = listener.local_addr()?;
+/// let mut client1 = TcpStream::connect(address)?;
+/// let (server1, _) = listener.accept()?;
+/// let mut client2 = TcpStream::connect(address)?;
+/// let (server2, _) = listener.accept()?;
+///
+/// client1.write_all(b"Hello world")?;
+///
+/// // Relay the data.
+/// let mut splicer = Splicer::new()?;
+/// let n = splicer.splice(&server1, &server2, 1024)?;
+/// assert_eq!(n, 11);
+///
+/// let mut buf = [0; 11];
+/// client2.read_exact(&mut buf)?;
+/// assert_eq!(&buf, b"Hello world");
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Splicer {
+    inner: sys::Splicer,
+}
+
+impl Splicer {
+    /// Create a new `Splicer`, creating a new pipe.
+    pub fn new() -> io::Result<Splicer> {
+        sys::Splicer::new().map(|inner| Splicer { inner })
+    }
+
+    /// Move up to `len` bytes from `from` to `to`.
+    ///
+    /// On success it returns the number of bytes written to `to`, zero meaning
+    /// that `from` reached the end of the file (e.g. the peer closed the
+    /// connection) and all data is written.
+    ///
+    /// Data that was read from `from`, but couldn't be written to `to`, is
+    /// kept in the pipe and written before any new data is read on the next
+    /// call. See [`buffered`] to get the number of bytes kept.
+    ///
+    /// This never blocks. If `from` has no data available, or `to` isn't
+    /// writable, this returns a [`WouldBlock`] error. When used with
+    /// [edge-triggered] readiness call this once either side is ready, until
+    /// it returns `WouldBlock`.
+    ///
+    /// [`buffered`]: Splicer::buffered
+    /// [`WouldBlock`]: std::io::ErrorKind::WouldBlock
+    /// [edge-triggered]: crate::os::RegisterOption::EDGE
+    pub fn splice<F, T>(&mut self, from: &F, to: &T, len: usize) -> io::Result<usize>
+        where F: AsRawFd + ?Sized,
+              T: AsRawFd + ?Sized,
+    {
+        self.inner.splice(from.as_raw_fd(), to.as_raw_fd(), len)
+    }
+
+    /// Returns the number of bytes kept in the pipe, waiting to be written.
+    pub fn buffered(&self) -> usize {
+        self.inner.buffered()
+    }
+}
@@ -0,0 +1,82 @@
+use std::io;
+use std::time::Duration;
+
+use crate::event;
+use crate::os::OsQueue;
+
+/// Timer backed by the OS.
+///
+/// When created it will cause an event with [`Ready::TIMER`] and the provided
+/// `id` once the `duration` has elapsed. A periodic timer, created with
+/// [`Timer::periodic`], will keep triggering an event each time the `interval`
+/// has elapsed until it's [cancelled].
+///
+/// Different to [`Timers`] the timer accounting is done by the kernel, rather
+/// then in user space.
+///
+/// [`Ready::TIMER`]: crate::event::Ready::TIMER
+/// [cancelled]: Timer::cancel
+/// [`Timers`]: crate::Timers
+///
+/// # Notes
+///
+/// Only a single timer per id can be active per [`OsQueue`]. Creating a new
+/// timer with the same id as a timer that is still active will replace it.
+///
+/// # Implementation notes
+///
+/// This is only available on platforms that support kqueue, it uses the
+/// `EVFILT_TIMER` event filter. See [implementation notes of the `os` module]
+/// to see what platform supports kqueue. On FreeBSD and macOS the timer has a
+/// precision of microseconds, on NetBSD and OpenBSD milliseconds are used.
+///
+/// [implementation notes of the `os` module]: ../index.html#implementation-notes
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::io;
+/// use std::time::Duration;
+///
+/// use gaea::{event, poll};
+/// use gaea::event::{Event, Ready};
+/// use gaea::os::{OsQueue, Timer};
+///
+/// const TIMER_ID: event::Id = event::Id(10);
+///
+/// let mut os_queue = OsQueue::new()?;
+/// let mut events = Vec::new();
+///
+/// let _timer = Timer::new(&mut os_queue, TIMER_ID, Duration::from_millis(10))?;
+///
+/// poll::<_, io::Error>(&mut [&mut os_queue], &mut events, None)?;
+/// assert_eq!(events[0], Event::new(TIMER_ID, Ready::TIMER));
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Timer {
+    id: event::Id,
+}
+
+impl Timer {
+    /// Create a new oneshot `Timer`, triggering once after `duration`.
+    pub fn new(os_queue: &mut OsQueue, id: event::Id, duration: Duration) -> io::Result<Timer> {
+        os_queue.selector().register_timer(id, duration, false)
+            .map(|()| Timer { id })
+    }
+
+    /// Create a new periodic `Timer`, triggering after each `interval`.
+    pub fn periodic(os_queue: &mut OsQueue, id: event::Id, interval: Duration) -> io::Result<Timer> {
+        os_queue.selector().register_timer(id, interval, true)
+            .map(|()| Timer { id })
+    }
+
+    /// Cancel the timer.
+    ///
+    /// After this call no more events will be triggered by this timer.
+    pub fn cancel(self, os_queue: &mut OsQueue) -> io::Result<()> {
+        os_queue.selector().deregister_timer(self.id)
+    }
+}
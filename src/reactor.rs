@@ -0,0 +1,155 @@
+//! Module with the reactor, combining all event sources.
+
+use std::io;
+use std::time::{Duration, Instant};
+
+use log::trace;
+
+use crate::event::{self, Event};
+use crate::os::{Evented, Interests, OsQueue, RegisterOption};
+use crate::{poll, Queue, Timers};
+
+/// Reactor combining [`OsQueue`], [`Queue`] and [`Timers`].
+///
+/// Most applications use all three event sources, polling them together in a
+/// single call to [`poll`]. `Reactor` owns all three and provides a single
+/// [`poll`] method to poll them, ensuring the timeout used is clamped to the
+/// next deadline.
+///
+/// The inner event sources can still be accessed for advanced use, see
+/// [`os_queue`], [`queue`] and [`timers`].
+///
+/// [`poll`]: Reactor::poll
+/// [`os_queue`]: Reactor::os_queue
+/// [`queue`]: Reactor::queue
+/// [`timers`]: Reactor::timers
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::time::Instant;
+///
+/// use gaea::{event, Event, Reactor, Ready};
+///
+/// let mut reactor = Reactor::new()?;
+/// let mut events = Vec::new();
+///
+/// // Add a user space event and a deadline.
+/// let event = Event::new(event::Id(0), Ready::READABLE);
+/// reactor.notify(event);
+/// reactor.add_deadline(event::Id(1), Instant::now());
+///
+/// // Even though we didn't provide a timeout `poll` will return without
+/// // blocking because events are ready.
+/// reactor.poll(&mut events, None)?;
+/// assert_eq!(events, vec![event, Event::new(event::Id(1), Ready::TIMER)]);
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Reactor {
+    os_queue: OsQueue,
+    queue: Queue,
+    timers: Timers,
+}
+
+impl Reactor {
+    /// Create a new `Reactor`.
+    ///
+    /// This creates a new [`OsQueue`], which may fail.
+    pub fn new() -> io::Result<Reactor> {
+        OsQueue::new().map(|os_queue| Reactor {
+            os_queue,
+            queue: Queue::new(),
+            timers: Timers::new(),
+        })
+    }
+
+    /// Register an [`Evented`] handle, see [`OsQueue::register`].
+    pub fn register<E>(&mut self, handle: &mut E, id: event::Id, interests: Interests, opt: RegisterOption) -> io::Result<()>
+        where E: Evented + ?Sized,
+    {
+        self.os_queue.register(handle, id, interests, opt)
+    }
+
+    /// Re-register an [`Evented`] handle, see [`OsQueue::reregister`].
+    pub fn reregister<E>(&mut self, handle: &mut E, id: event::Id, interests: Interests, opt: RegisterOption) -> io::Result<()>
+        where E: Evented + ?Sized,
+    {
+        self.os_queue.reregister(handle, id, interests, opt)
+    }
+
+    /// Deregister an [`Evented`] handle, see [`OsQueue::deregister`].
+    pub fn deregister<E>(&mut self, handle: &mut E) -> io::Result<()>
+        where E: Evented + ?Sized,
+    {
+        self.os_queue.deregister(handle)
+    }
+
+    /// Add a user space event, see [`Queue::add`].
+    pub fn notify(&mut self, event: Event) {
+        self.queue.add(event)
+    }
+
+    /// Add a new deadline, see [`Timers::add_deadline`].
+    pub fn add_deadline(&mut self, id: event::Id, deadline: Instant) {
+        self.timers.add_deadline(id, deadline)
+    }
+
+    /// Add a new timeout, see [`Timers::add_timeout`].
+    pub fn add_timeout(&mut self, id: event::Id, timeout: Duration) {
+        self.timers.add_timeout(id, timeout)
+    }
+
+    /// Remove a previously added deadline, see [`Timers::remove_deadline`].
+    pub fn remove_deadline(&mut self, id: event::Id) {
+        self.timers.remove_deadline(id)
+    }
+
+    /// Poll all event sources for readiness events.
+    ///
+    /// This calls [`poll`] with all event sources, blocking on the
+    /// [`OsQueue`]. The `timeout` is clamped to the next deadline or zero if
+    /// user space events are available. Returns the number of events added to
+    /// `event_sink`.
+    ///
+    /// [`poll`]: crate::poll
+    pub fn poll<ES>(&mut self, event_sink: &mut ES, timeout: Option<Duration>) -> io::Result<usize>
+        where ES: event::Sink,
+    {
+        trace!("polling reactor: timeout={:?}", timeout);
+        poll::<_, io::Error>(&mut [&mut self.os_queue, &mut self.queue, &mut self.timers],
+            event_sink, timeout)
+    }
+
+    /// Returns a reference to the [`OsQueue`].
+    pub fn os_queue(&self) -> &OsQueue {
+        &self.os_queue
+    }
+
+    /// Returns a mutable reference to the [`OsQueue`].
+    pub fn os_queue_mut(&mut self) -> &mut OsQueue {
+        &mut self.os_queue
+    }
+
+    /// Returns a reference to the user space [`Queue`].
+    pub fn queue(&self) -> &Queue {
+        &self.queue
+    }
+
+    /// Returns a mutable reference to the user space [`Queue`].
+    pub fn queue_mut(&mut self) -> &mut Queue {
+        &mut self.queue
+    }
+
+    /// Returns a reference to the [`Timers`].
+    pub fn timers(&self) -> &Timers {
+        &self.timers
+    }
+
+    /// Returns a mutable reference to the [`Timers`].
+    pub fn timers_mut(&mut self) -> &mut Timers {
+        &mut self.timers
+    }
+}
@@ -0,0 +1,296 @@
+//! Mock selector, enabled by the `mock_selector` feature.
+//!
+//! The mock selector doesn't use the OS, it records all registrations and only
+//! returns the events injected using `inject`. State is shared between cloned
+//! selectors, like a duplicated epoll or kqueue file descriptor.
+//!
+//! With the feature enabled `Selector` is either the OS selector or the mock
+//! selector, the latter is only used by `OsQueue::new_mock`. Likewise
+//! `Awakener` is either the OS awakener or injects its events into the mock
+//! selector, as the OS awakener would never be returned by the mock selector.
+
+use std::collections::HashMap;
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use log::trace;
+
+use crate::event::{self, Event};
+use crate::os::{Interests, RegisterOption, SignalSet};
+use crate::sys::unix;
+
+/// Either the OS selector or the mock selector.
+#[derive(Debug)]
+pub enum Selector {
+    Os(unix::Selector),
+    Mock(MockSelector),
+}
+
+impl Selector {
+    pub fn new() -> io::Result<Selector> {
+        unix::Selector::new().map(Selector::Os)
+    }
+
+    pub fn with_capacity(capacity: usize) -> io::Result<Selector> {
+        unix::Selector::with_capacity(capacity).map(Selector::Os)
+    }
+
+    pub fn new_mock() -> io::Result<Selector> {
+        Ok(Selector::Mock(MockSelector::new()))
+    }
+
+    pub fn try_clone(&self) -> io::Result<Selector> {
+        match self {
+            Selector::Os(selector) => selector.try_clone().map(Selector::Os),
+            Selector::Mock(selector) => Ok(Selector::Mock(selector.try_clone())),
+        }
+    }
+
+    pub fn select<ES>(&mut self, event_sink: &mut ES, timeout: Option<Duration>) -> io::Result<usize>
+        where ES: event::Sink,
+    {
+        match self {
+            Selector::Os(selector) => selector.select(event_sink, timeout),
+            Selector::Mock(selector) => Ok(selector.select(event_sink)),
+        }
+    }
+
+    pub fn set_sigmask(&mut self, signals: SignalSet) -> io::Result<()> {
+        match self {
+            Selector::Os(selector) => selector.set_sigmask(signals),
+            Selector::Mock(_) => Ok(()),
+        }
+    }
+
+    pub fn register(&self, fd: RawFd, id: event::Id, interests: Interests, opt: RegisterOption) -> io::Result<()> {
+        match self {
+            Selector::Os(selector) => selector.register(fd, id, interests, opt),
+            Selector::Mock(selector) => selector.register(fd, id, interests, opt),
+        }
+    }
+
+    pub fn register_many(&self, registrations: &[(RawFd, event::Id, Interests, RegisterOption)]) -> Vec<io::Result<()>> {
+        match self {
+            Selector::Os(selector) => selector.register_many(registrations),
+            Selector::Mock(selector) => registrations.iter()
+                .map(|&(fd, id, interests, opt)| selector.register(fd, id, interests, opt))
+                .collect(),
+        }
+    }
+
+    pub fn reregister(&self, fd: RawFd, id: event::Id, interests: Interests, opt: RegisterOption, previous: Option<Interests>) -> io::Result<()> {
+        match self {
+            Selector::Os(selector) => selector.reregister(fd, id, interests, opt, previous),
+            Selector::Mock(selector) => selector.reregister(fd, id, interests, opt),
+        }
+    }
+
+    pub fn deregister(&self, fd: RawFd) -> io::Result<()> {
+        match self {
+            Selector::Os(selector) => selector.deregister(fd),
+            Selector::Mock(selector) => selector.deregister(fd),
+        }
+    }
+
+    // Used by the OS `Awakener`, which is never created for the mock selector.
+    #[cfg(any(target_os = "freebsd", target_os = "macos"))]
+    pub fn setup_awakener(&self, id: event::Id) -> io::Result<()> {
+        match self {
+            Selector::Os(selector) => selector.setup_awakener(id),
+            Selector::Mock(_) => Ok(()),
+        }
+    }
+
+    // Used by the OS `Awakener`, which is never created for the mock selector.
+    #[cfg(any(target_os = "freebsd", target_os = "macos"))]
+    pub fn wake(&self, id: event::Id) -> io::Result<()> {
+        match self {
+            Selector::Os(selector) => selector.wake(id),
+            Selector::Mock(_) => Ok(()),
+        }
+    }
+
+    // Used by `Timer`.
+    #[cfg(any(target_os = "freebsd", target_os = "macos",
+              target_os = "netbsd", target_os = "openbsd"))]
+    pub fn register_timer(&self, id: event::Id, duration: Duration, periodic: bool) -> io::Result<()> {
+        match self {
+            Selector::Os(selector) => selector.register_timer(id, duration, periodic),
+            Selector::Mock(_) => Ok(()),
+        }
+    }
+
+    // Used by `Timer`.
+    #[cfg(any(target_os = "freebsd", target_os = "macos",
+              target_os = "netbsd", target_os = "openbsd"))]
+    pub fn deregister_timer(&self, id: event::Id) -> io::Result<()> {
+        match self {
+            Selector::Os(selector) => selector.deregister_timer(id),
+            Selector::Mock(_) => Ok(()),
+        }
+    }
+
+    // Used by `Signals`.
+    #[cfg(any(target_os = "freebsd", target_os = "macos",
+              target_os = "netbsd", target_os = "openbsd"))]
+    pub fn register_signals(&self, id: event::Id, signals: SignalSet) -> io::Result<()> {
+        match self {
+            Selector::Os(selector) => selector.register_signals(id, signals),
+            Selector::Mock(_) => Ok(()),
+        }
+    }
+
+    /// Returns the mock selector, or `None` if this is the OS selector.
+    pub fn mock(&self) -> Option<&MockSelector> {
+        match self {
+            Selector::Os(_) => None,
+            Selector::Mock(selector) => Some(selector),
+        }
+    }
+}
+
+/// Either the OS awakener or an awakener for the mock selector.
+#[derive(Debug)]
+pub enum Awakener {
+    Os(unix::Awakener),
+    Mock(MockSelector, event::Id),
+}
+
+impl Awakener {
+    pub fn new(selector: &Selector, id: event::Id) -> io::Result<Awakener> {
+        match selector {
+            Selector::Os(_) => unix::Awakener::new(selector, id).map(Awakener::Os),
+            Selector::Mock(selector) => Ok(Awakener::Mock(selector.try_clone(), id)),
+        }
+    }
+
+    pub fn try_clone(&self) -> io::Result<Awakener> {
+        match self {
+            Awakener::Os(awakener) => awakener.try_clone().map(Awakener::Os),
+            Awakener::Mock(selector, id) => Ok(Awakener::Mock(selector.try_clone(), *id)),
+        }
+    }
+
+    pub fn wake(&self) -> io::Result<()> {
+        match self {
+            Awakener::Os(awakener) => awakener.wake(),
+            Awakener::Mock(selector, id) => {
+                selector.inject(Event::new(*id, event::Ready::READABLE));
+                Ok(())
+            },
+        }
+    }
+
+    /// The mock awakener doesn't need draining.
+    pub fn drainer(&self) -> io::Result<Option<Awakener>> {
+        match self {
+            Awakener::Os(awakener) => awakener.drainer().map(|drainer| drainer.map(Awakener::Os)),
+            Awakener::Mock(..) => Ok(None),
+        }
+    }
+
+    pub fn drain(&self) -> io::Result<()> {
+        match self {
+            Awakener::Os(awakener) => awakener.drain(),
+            Awakener::Mock(..) => Ok(()),
+        }
+    }
+}
+
+impl AsRawFd for Selector {
+    /// The mock selector has no file descriptor, this returns `-1` for it.
+    fn as_raw_fd(&self) -> RawFd {
+        match self {
+            Selector::Os(selector) => selector.as_raw_fd(),
+            Selector::Mock(_) => -1,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct MockSelector {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug)]
+struct State {
+    registrations: HashMap<RawFd, (event::Id, Interests, RegisterOption)>,
+    /// Events injected, returned by the next call to `select`.
+    events: Vec<Event>,
+}
+
+impl MockSelector {
+    fn new() -> MockSelector {
+        MockSelector {
+            state: Arc::new(Mutex::new(State {
+                registrations: HashMap::new(),
+                events: Vec::new(),
+            })),
+        }
+    }
+
+    fn try_clone(&self) -> MockSelector {
+        MockSelector { state: Arc::clone(&self.state) }
+    }
+
+    /// Never blocks, returns the injected events.
+    fn select<ES>(&self, event_sink: &mut ES) -> usize
+        where ES: event::Sink,
+    {
+        let mut state = self.state();
+        let n = event_sink.capacity_left().min(state.events.len());
+        event_sink.reserve(n);
+        event_sink.extend(state.events.drain(..n));
+        n
+    }
+
+    fn register(&self, fd: RawFd, id: event::Id, interests: Interests, opt: RegisterOption) -> io::Result<()> {
+        trace!("mock registering: fd={}, id={}, interests={:?}, opt={:?}", fd, id, interests, opt);
+        let mut state = self.state();
+        if state.registrations.contains_key(&fd) {
+            return Err(io::Error::from_raw_os_error(libc::EEXIST));
+        }
+        let _ = state.registrations.insert(fd, (id, interests, opt));
+        Ok(())
+    }
+
+    fn reregister(&self, fd: RawFd, id: event::Id, interests: Interests, opt: RegisterOption) -> io::Result<()> {
+        trace!("mock reregistering: fd={}, id={}, interests={:?}, opt={:?}", fd, id, interests, opt);
+        match self.state().registrations.get_mut(&fd) {
+            Some(registration) => {
+                *registration = (id, interests, opt);
+                Ok(())
+            },
+            None => Err(io::Error::from_raw_os_error(libc::ENOENT)),
+        }
+    }
+
+    fn deregister(&self, fd: RawFd) -> io::Result<()> {
+        trace!("mock deregistering: fd={}", fd);
+        match self.state().registrations.remove(&fd) {
+            Some(_) => Ok(()),
+            None => Err(io::Error::from_raw_os_error(libc::ENOENT)),
+        }
+    }
+
+    /// Returns the registration of `fd`, if any.
+    pub fn registration(&self, fd: RawFd) -> Option<(event::Id, Interests, RegisterOption)> {
+        self.state().registrations.get(&fd).cloned()
+    }
+
+    /// Add `event` to be returned by the next call to `select`.
+    pub fn inject(&self, event: Event) {
+        trace!("mock injecting event: {:?}", event);
+        self.state().events.push(event);
+    }
+
+    /// Lock the state, ignoring poisoning as the state is always valid.
+    fn state(&self) -> MutexGuard<'_, State> {
+        match self.state.lock() {
+            Ok(state) => state,
+            Err(err) => err.into_inner(),
+        }
+    }
+}
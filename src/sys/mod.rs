@@ -3,11 +3,14 @@
 //! Each platform must have at least the following types:
 //!
 //! - `Selector`: system selector, e.g. `kqueue` or `epoll`, used by `OsQueue`.
-//! - `TcpStream`: TCP stream, used in the net module.
-//! - `TcpListener`: TCP listener, used in the net module.
-//! - `UdpSocket`: UDP socket, used in the net module.
+//! - `TcpStream`: TCP stream, used in the net module (`net` feature only).
+//! - `TcpListener`: TCP listener, used in the net module (`net` feature only).
+//! - `UdpSocket`: UDP socket, used in the net module (`net` feature only).
 //! - `Awakener`: cross-thread awakener, used by `Awakener`.
 //! - `Signals`: process signal handler, used in `Signals`.
+//!
+//! With the `mock_selector` feature enabled `Selector` is either the system
+//! selector or a mock selector, see the `mock` module.
 
 #[cfg(unix)]
 mod unix;
@@ -15,5 +18,11 @@ mod unix;
 #[cfg(unix)]
 pub use self::unix::*;
 
-/// Size of stack allocated system events array.
+#[cfg(all(unix, feature = "mock_selector"))]
+mod mock;
+
+#[cfg(all(unix, feature = "mock_selector"))]
+pub use self::mock::{Awakener, Selector};
+
+/// Default size of the system events buffer, see `OsQueue::with_capacity`.
 const EVENTS_CAP: usize = 128;
@@ -44,15 +44,20 @@ mod eventfd {
                 Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {
                     // Writing only blocks if the counter is going to overflow.
                     // So we'll reset the counter to 0 and wake it again.
-                    self.reset()?;
+                    self.drain()?;
                     self.wake()
                 },
                 Err(err) => Err(err),
             }
         }
 
-        /// Reset the eventfd object, only need to call this if `wake` fails.
-        fn reset(&self) -> io::Result<()> {
+        /// Returns a clone used by `OsQueue` to drain the awakener.
+        pub fn drainer(&self) -> io::Result<Option<Awakener>> {
+            self.try_clone().map(Some)
+        }
+
+        /// Reset the eventfd object's counter to 0.
+        pub fn drain(&self) -> io::Result<()> {
             let mut buf: [u8; 8] = [0; 8];
             match (&self.fd).read(&mut buf) {
                 Ok(_) => Ok(()),
@@ -105,6 +110,16 @@ mod kqueue {
         pub fn wake(&self) -> io::Result<()> {
             self.selector.wake(self.id)
         }
+
+        /// The user event is registered with `EV_CLEAR`, which resets its
+        /// state once it's returned, so it never needs to be drained.
+        pub fn drainer(&self) -> io::Result<Option<Awakener>> {
+            Ok(None)
+        }
+
+        pub fn drain(&self) -> io::Result<()> {
+            Ok(())
+        }
     }
 }
 
@@ -155,7 +170,7 @@ mod pipe {
                 Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {
                     // The reading end is full so we'll empty the buffer and try
                     // again.
-                    self.empty();
+                    self.drain()?;
                     self.wake()
                 },
                 Err(ref err) if err.kind() == io::ErrorKind::Interrupted => self.wake(),
@@ -163,14 +178,21 @@ mod pipe {
             }
         }
 
-        /// Empty the pipe's buffer, only need to call this if `wake` fails.
-        /// This ignores any errors.
-        fn empty(&self)  {
+        /// Returns a clone used by `OsQueue` to drain the awakener.
+        pub fn drainer(&self) -> io::Result<Option<Awakener>> {
+            self.try_clone().map(Some)
+        }
+
+        /// Empty the pipe's buffer.
+        pub fn drain(&self) -> io::Result<()> {
             let mut buf = [0; 4096];
             loop {
                 match (&self.receiver).read(&mut buf) {
                     Ok(n) if n > 0 => continue,
-                    _ => return,
+                    Ok(_) => return Ok(()),
+                    Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                    Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(err) => return Err(err),
                 }
             }
         }
@@ -0,0 +1,31 @@
+//! Coarse monotonic clock, see `CoarseClock`.
+
+use std::io;
+use std::mem::zeroed;
+use std::time::Duration;
+
+/// Returns the current time of `CLOCK_MONOTONIC_COARSE`, as the duration since
+/// an unspecified starting point.
+pub fn now() -> Duration {
+    let mut ts: libc::timespec = unsafe { zeroed() };
+    // Like `Instant::now` we panic if the clock can't be read, which only
+    // happens if the clock isn't supported, i.e. on Linux older then 2.6.32.
+    if unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC_COARSE, &mut ts) } == -1 {
+        panic!("unable to read CLOCK_MONOTONIC_COARSE: {}", io::Error::last_os_error());
+    }
+    to_duration(ts)
+}
+
+/// Returns the resolution of `CLOCK_MONOTONIC_COARSE`.
+pub fn resolution() -> Duration {
+    let mut ts: libc::timespec = unsafe { zeroed() };
+    if unsafe { libc::clock_getres(libc::CLOCK_MONOTONIC_COARSE, &mut ts) } == -1 {
+        panic!("unable to get resolution of CLOCK_MONOTONIC_COARSE: {}",
+            io::Error::last_os_error());
+    }
+    to_duration(ts)
+}
+
+fn to_duration(ts: libc::timespec) -> Duration {
+    Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32)
+}
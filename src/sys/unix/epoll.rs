@@ -1,62 +1,136 @@
 use std::cmp::min;
-use std::os::unix::io::RawFd;
-use std::time::Duration;
-use std::{io, mem, ptr};
+use std::mem::size_of;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::time::{Duration, Instant};
+use std::{io, ptr};
 
 use log::error;
 
 use crate::event::{self, Event, Ready};
-use crate::os::{Interests, RegisterOption};
+use crate::os::{Interests, RegisterOption, SignalSet};
+use crate::sys::unix::remaining_timeout;
+use crate::sys::unix::signals::create_sigset;
 use crate::sys::EVENTS_CAP;
 
 #[derive(Debug)]
 pub struct Selector {
     epfd: RawFd,
+    /// Signal mask to use while blocking, see `set_sigmask`.
+    sigmask: Option<libc::sigset_t>,
+    /// Buffer used in `select`, its capacity determines the maximum number of
+    /// events returned by a single call.
+    ep_events: Vec<libc::epoll_event>,
 }
 
 impl Selector {
     pub fn new() -> io::Result<Selector> {
+        Selector::with_capacity(EVENTS_CAP)
+    }
+
+    pub fn with_capacity(capacity: usize) -> io::Result<Selector> {
         let epfd = unsafe { libc::epoll_create1(libc::EPOLL_CLOEXEC) };
         if epfd == -1 {
             Err(io::Error::last_os_error())
         } else {
-            Ok(Selector { epfd })
+            Ok(Selector { epfd, sigmask: None, ep_events: Vec::with_capacity(capacity) })
+        }
+    }
+
+    pub fn try_clone(&self) -> io::Result<Selector> {
+        let new_epfd = unsafe { libc::fcntl(self.epfd, libc::F_DUPFD_CLOEXEC, 0) };
+        if new_epfd == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(Selector {
+                epfd: new_epfd,
+                sigmask: self.sigmask,
+                ep_events: Vec::with_capacity(self.ep_events.capacity()),
+            })
         }
     }
 
-    pub fn select<ES>(&self, event_sink: &mut ES, timeout: Option<Duration>) -> io::Result<()>
+    pub fn select<ES>(&mut self, event_sink: &mut ES, timeout: Option<Duration>) -> io::Result<usize>
         where ES: event::Sink,
     {
-        let mut ep_events: [libc::epoll_event; EVENTS_CAP] = unsafe { mem::uninitialized() };
-        let events_cap = event_sink.capacity_left().min(EVENTS_CAP) as libc::c_int;
+        self.ep_events.clear();
+        let events_cap = event_sink.capacity_left().min(self.ep_events.capacity()) as libc::c_int;
         if events_cap == 0 {
             // epoll can't deal with 0 capacity event arrays.
-            return Ok(())
+            return Ok(0)
         }
 
-        let timeout_ms = timeout.map(duration_to_millis).unwrap_or(-1);
+        #[allow(trivial_casts)]
+        let sigmask_ptr = self.sigmask.as_ref()
+            .map(|sigmask| sigmask as *const libc::sigset_t)
+            .unwrap_or(ptr::null());
 
-        let n_events = unsafe {
-            libc::epoll_wait(self.epfd, ep_events.as_mut_ptr(), events_cap, timeout_ms)
+        let start = Instant::now();
+        let mut timeout_ms = timeout.map(duration_to_millis).unwrap_or(-1);
+        let n_events = loop {
+            let n_events = unsafe {
+                libc::epoll_pwait(self.epfd, self.ep_events.as_mut_ptr(), events_cap,
+                    timeout_ms, sigmask_ptr)
+            };
+            if n_events == -1 {
+                let err = io::Error::last_os_error();
+                if err.kind() == io::ErrorKind::Interrupted {
+                    // Interrupted by a signal, try again with the time left.
+                    timeout_ms = remaining_timeout(timeout, start)
+                        .map(duration_to_millis).unwrap_or(-1);
+                    continue;
+                }
+                return Err(err);
+            }
+            break n_events;
         };
+
         match n_events {
-            -1 => Err(io::Error::last_os_error()),
-            0 => Ok(()), // Reached the time limit, no events are pulled.
+            0 => Ok(0), // Reached the time limit, no events are pulled.
             n => {
-                let ep_events = ep_events[..n as usize].iter()
-                    .map(ep_event_to_event);
+                // Safety: the kernel initialised the first `n` events.
+                unsafe { self.ep_events.set_len(n as usize) };
+                event_sink.reserve(n as usize);
+                let ep_events = self.ep_events.iter().map(ep_event_to_event);
                 event_sink.extend(ep_events);
-                Ok(())
+                Ok(n as usize)
             },
         }
     }
 
+    /// Set the signal mask to atomically apply while blocking in `select`,
+    /// using `epoll_pwait`.
+    pub fn set_sigmask(&mut self, signals: SignalSet) -> io::Result<()> {
+        create_sigset(signals).map(|set| self.sigmask = Some(set))
+    }
+
     pub fn register(&self, fd: RawFd, id: event::Id, interests: Interests, opt: RegisterOption) -> io::Result<()> {
+        if opt.is_exclusive() && opt.is_oneshot() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                "can't combine exclusive and oneshot register options"));
+        } else if opt.is_exclusive() && interests.is_priority() {
+            // `EPOLLEXCLUSIVE` can't be combined with `EPOLLPRI`.
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                "can't combine exclusive register option and priority interest"));
+        }
         let mut epoll_event = new_epoll_event(interests, opt, id);
         epoll_ctl(self.epfd, libc::EPOLL_CTL_ADD, fd, &mut epoll_event)
     }
 
-    pub fn reregister(&self, fd: RawFd, id: event::Id, interests: Interests, opt: RegisterOption) -> io::Result<()> {
+    /// `epoll_ctl` only registers a single file descriptor, so this calls
+    /// `register` for each registration.
+    pub fn register_many(&self, registrations: &[(RawFd, event::Id, Interests, RegisterOption)]) -> Vec<io::Result<()>> {
+        registrations.iter()
+            .map(|&(fd, id, interests, opt)| self.register(fd, id, interests, opt))
+            .collect()
+    }
+
+    /// The `previous` interests are not used, `EPOLL_CTL_MOD` replaces the
+    /// entire registration.
+    pub fn reregister(&self, fd: RawFd, id: event::Id, interests: Interests, opt: RegisterOption, _previous: Option<Interests>) -> io::Result<()> {
+        if opt.is_exclusive() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                "can't use the exclusive register option when reregistering"));
+        }
         let mut epoll_event = new_epoll_event(interests, opt, id);
         epoll_ctl(self.epfd, libc::EPOLL_CTL_MOD, fd, &mut epoll_event)
     }
@@ -66,25 +140,40 @@ impl Selector {
     }
 }
 
-/// Convert a `Duration` to milliseconds.
+/// Convert a `Duration` to milliseconds, rounding up.
+///
+/// Rounding up ensures we don't wake up before a deadline has passed, which
+/// would cause us to return without the timer event.
 ///
 /// # Notes
 ///
 /// Uses 24 hours as maximum to match kqueue.
 pub fn duration_to_millis(duration: Duration) -> libc::c_int {
-    min(duration.as_millis(), 24 * 60 * 60 * 1_000) as libc::c_int
+    let mut millis = duration.as_millis();
+    if duration.subsec_nanos() > duration.subsec_millis() * 1_000_000 {
+        millis += 1;
+    }
+    min(millis, 24 * 60 * 60 * 1_000) as libc::c_int
 }
 
 /// Convert a `epoll_event` into an `Event`.
+///
+/// epoll doesn't return the error for events with `EPOLLERR`, and the event
+/// only contains the id, so the error is retrieved using `socket_error` by
+/// `OsQueue` (if enabled), which knows the file descriptor.
 fn ep_event_to_event(ep_event: &libc::epoll_event) -> Event {
     let id = event::Id(ep_event.u64 as usize);
     let epoll = ep_event.events;
     let mut readiness = Ready::EMPTY;
 
-    if contains_flag(epoll, libc::EPOLLIN | libc::EPOLLPRI) {
+    if contains_flag(epoll, libc::EPOLLIN) {
         readiness |= Ready::READABLE;
     }
 
+    if contains_flag(epoll, libc::EPOLLPRI) {
+        readiness |= Ready::PRIORITY;
+    }
+
     if contains_flag(epoll, libc::EPOLLOUT) {
         readiness |= Ready::WRITABLE;
     }
@@ -93,13 +182,37 @@ fn ep_event_to_event(ep_event: &libc::epoll_event) -> Event {
         readiness |= Ready::ERROR;
     }
 
-    if contains_flag(epoll, libc::EPOLLRDHUP | libc::EPOLLHUP) {
+    if contains_flag(epoll, libc::EPOLLHUP) {
         readiness |= Ready::HUP;
     }
 
+    if contains_flag(epoll, libc::EPOLLRDHUP) {
+        readiness |= Ready::READ_HUP;
+    }
+
     Event::new(id, readiness)
 }
 
+/// Take the pending error of the socket `fd` using `SO_ERROR`, this clears
+/// the error. Used for events with error readiness, see
+/// `OsQueue::set_take_errors`.
+///
+/// Returns 0 if there is no error, or if `fd` isn't a socket.
+pub fn socket_error(fd: RawFd) -> i32 {
+    let mut error: libc::c_int = 0;
+    let mut length = size_of::<libc::c_int>() as libc::socklen_t;
+    #[allow(trivial_casts)]
+    let res = unsafe {
+        libc::getsockopt(fd, libc::SOL_SOCKET, libc::SO_ERROR,
+            &mut error as *mut libc::c_int as *mut libc::c_void, &mut length)
+    };
+    if res == -1 {
+        0
+    } else {
+        error
+    }
+}
+
 /// Whether or not the provided `flags` contains the provided `flag`.
 const fn contains_flag(flags: u32, flag: libc::c_int) -> bool {
     (flags & flag as u32) != 0
@@ -114,7 +227,12 @@ fn new_epoll_event(interests: Interests, opt: RegisterOption, id: event::Id) ->
 }
 
 fn to_epoll_events(interests: Interests, opt: RegisterOption) -> u32 {
-    let mut events = libc::EPOLLPRI | libc::EPOLLRDHUP;
+    let mut events = if opt.is_exclusive() {
+        // `EPOLLEXCLUSIVE` can't be combined with `EPOLLRDHUP`.
+        libc::EPOLLEXCLUSIVE
+    } else {
+        libc::EPOLLRDHUP
+    };
 
     if interests.is_readable() {
         events |= libc::EPOLLIN;
@@ -124,6 +242,10 @@ fn to_epoll_events(interests: Interests, opt: RegisterOption) -> u32 {
         events |= libc::EPOLLOUT;
     }
 
+    if interests.is_priority() {
+        events |= libc::EPOLLPRI;
+    }
+
     // NOTE: level is the default.
     if opt.is_edge() {
         events |= libc::EPOLLET;
@@ -146,6 +268,13 @@ fn epoll_ctl(epfd: RawFd, op: libc::c_int, fd: RawFd, event: *mut libc::epoll_ev
     }
 }
 
+// Used in `OsQueue`.
+impl AsRawFd for Selector {
+    fn as_raw_fd(&self) -> RawFd {
+        self.epfd
+    }
+}
+
 impl Drop for Selector {
     fn drop(&mut self) {
         if unsafe { libc::close(self.epfd) } == -1 {
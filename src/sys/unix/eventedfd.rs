@@ -79,19 +79,116 @@ use crate::os::{Evented, Interests, OsQueue, RegisterOption};
 ///     }
 /// }
 /// ```
+///
+/// If the type implements `AsRawFd` the [`evented_via_fd!`] macro can be used
+/// to generate the implementation above.
+///
+/// [`evented_via_fd!`]: crate::evented_via_fd
 #[derive(Debug)]
 pub struct EventedFd<'a>(pub &'a RawFd);
 
 impl<'a> Evented for EventedFd<'a> {
     fn register(&mut self, os_queue: &mut OsQueue, id: event::Id, interests: Interests, opt: RegisterOption) -> io::Result<()> {
-        os_queue.selector().register(*self.0, id, interests, opt)
+        os_queue.register_fd(*self.0, id, interests, opt)
     }
 
     fn reregister(&mut self, os_queue: &mut OsQueue, id: event::Id, interests: Interests, opt: RegisterOption) -> io::Result<()> {
-        os_queue.selector().reregister(*self.0, id, interests, opt)
+        os_queue.reregister_fd(*self.0, id, interests, opt)
     }
 
     fn deregister(&mut self, os_queue: &mut OsQueue) -> io::Result<()> {
-        os_queue.selector().deregister(*self.0)
+        os_queue.deregister_fd(*self.0)
     }
 }
+
+/// Implement [`Evented`] for a type that implements `AsRawFd`.
+///
+/// The generated implementation uses [`EventedFd`] to register, reregister
+/// and deregister the file descriptor returned by `AsRawFd::as_raw_fd`. Any
+/// attributes, e.g. documentation, before the type are added to the
+/// implementation.
+///
+/// A blanket implementation of `Evented` for all `AsRawFd` types is not
+/// possible, as it would conflict with other implementations of `Evented`.
+///
+/// [`Evented`]: crate::os::Evented
+/// [`EventedFd`]: crate::unix::EventedFd
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::os::unix::io::{AsRawFd, RawFd};
+///
+/// use gaea::{event, evented_via_fd};
+/// use gaea::os::{Interests, RegisterOption, OsQueue};
+///
+/// # #[allow(dead_code)]
+/// pub struct MyIo {
+///     fd: RawFd,
+/// }
+///
+/// impl AsRawFd for MyIo {
+///     fn as_raw_fd(&self) -> RawFd {
+///         self.fd
+///     }
+/// }
+///
+/// // Implement `Evented` using the file descriptor.
+/// evented_via_fd!(MyIo);
+///
+/// # let (_sender, receiver) = gaea::unix::new_pipe()?;
+/// # let fd = receiver.as_raw_fd();
+/// let mut my_io = MyIo { fd };
+///
+/// let mut os_queue = OsQueue::new()?;
+/// os_queue.register(&mut my_io, event::Id(0), Interests::READABLE, RegisterOption::EDGE)?;
+/// #     Ok(())
+/// # }
+/// ```
+#[macro_export]
+macro_rules! evented_via_fd {
+    ($(#[$meta: meta])* $type: ty) => {
+        $(#[$meta])*
+        impl $crate::os::Evented for $type {
+            fn register(&mut self, os_queue: &mut $crate::os::OsQueue, id: $crate::event::Id, interests: $crate::os::Interests, opt: $crate::os::RegisterOption) -> ::std::io::Result<()> {
+                let fd = ::std::os::unix::io::AsRawFd::as_raw_fd(self);
+                $crate::os::Evented::register(&mut $crate::unix::EventedFd(&fd), os_queue, id, interests, opt)
+            }
+
+            fn reregister(&mut self, os_queue: &mut $crate::os::OsQueue, id: $crate::event::Id, interests: $crate::os::Interests, opt: $crate::os::RegisterOption) -> ::std::io::Result<()> {
+                let fd = ::std::os::unix::io::AsRawFd::as_raw_fd(self);
+                $crate::os::Evented::reregister(&mut $crate::unix::EventedFd(&fd), os_queue, id, interests, opt)
+            }
+
+            fn deregister(&mut self, os_queue: &mut $crate::os::OsQueue) -> ::std::io::Result<()> {
+                let fd = ::std::os::unix::io::AsRawFd::as_raw_fd(self);
+                $crate::os::Evented::deregister(&mut $crate::unix::EventedFd(&fd), os_queue)
+            }
+        }
+    };
+}
+
+evented_via_fd!(
+    /// The caller must ensure the stream is in non-blocking mode, e.g. using
+    /// [`TcpStream::set_nonblocking`].
+    ///
+    /// [`TcpStream::set_nonblocking`]: std::net::TcpStream::set_nonblocking
+    std::net::TcpStream
+);
+
+evented_via_fd!(
+    /// The caller must ensure the listener is in non-blocking mode, e.g. using
+    /// [`TcpListener::set_nonblocking`].
+    ///
+    /// [`TcpListener::set_nonblocking`]: std::net::TcpListener::set_nonblocking
+    std::net::TcpListener
+);
+
+evented_via_fd!(
+    /// The caller must ensure the socket is in non-blocking mode, e.g. using
+    /// [`UdpSocket::set_nonblocking`].
+    ///
+    /// [`UdpSocket::set_nonblocking`]: std::net::UdpSocket::set_nonblocking
+    std::net::UdpSocket
+);
@@ -1,12 +1,13 @@
 use std::cmp::min;
 use std::os::unix::io::{AsRawFd, RawFd};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::{io, mem, ptr};
 
 use log::error;
 
 use crate::event::{self, Event, Ready};
 use crate::os::{Interests, RegisterOption, SignalSet};
+use crate::sys::unix::remaining_timeout;
 use crate::sys::EVENTS_CAP;
 
 // Of course each OS that implements kqueue has chosen to go for different types
@@ -61,48 +62,88 @@ type kevent_udata_t = libc::intptr_t;
 #[derive(Debug)]
 pub struct Selector {
     kq: RawFd,
+    /// Buffer used in `select`, its capacity determines the maximum number of
+    /// events returned by a single call.
+    kevents: Vec<libc::kevent>,
 }
 
 impl Selector {
     pub fn new() -> io::Result<Selector> {
+        Selector::with_capacity(EVENTS_CAP)
+    }
+
+    pub fn with_capacity(capacity: usize) -> io::Result<Selector> {
         let kq = unsafe { libc::kqueue() };
         if kq == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // Create the selector first to ensure the kqueue is closed in case of
+        // an error.
+        let selector = Selector { kq, kevents: Vec::with_capacity(capacity) };
+        // Not all platforms support `kqueue1`, so we set close-on-exec
+        // separately.
+        if unsafe { libc::fcntl(kq, libc::F_SETFD, libc::FD_CLOEXEC) } == -1 {
             Err(io::Error::last_os_error())
         } else {
-            Ok(Selector { kq })
+            Ok(selector)
         }
     }
 
-    pub fn select<ES>(&self, event_sink: &mut ES, timeout: Option<Duration>) -> io::Result<()>
+    pub fn select<ES>(&mut self, event_sink: &mut ES, timeout: Option<Duration>) -> io::Result<usize>
         where ES: event::Sink,
     {
-        let mut kevents: [libc::kevent; EVENTS_CAP] = unsafe { mem::uninitialized() };
+        self.kevents.clear();
         #[allow(trivial_numeric_casts)]
-        let events_cap = event_sink.capacity_left().min(EVENTS_CAP) as nchanges_t;
-
-        let timespec = timeout.map(timespec_from_duration);
-        #[allow(trivial_casts)]
-        let timespec_ptr = timespec
-            .as_ref()
-            .map(|t| t as *const libc::timespec)
-            .unwrap_or(ptr::null());
-
-        let n_events = unsafe {
-            libc::kevent(self.kq, ptr::null(), 0,
-                kevents.as_mut_ptr(), events_cap, timespec_ptr)
+        let events_cap = event_sink.capacity_left().min(self.kevents.capacity()) as nchanges_t;
+
+        let start = Instant::now();
+        let mut timespec = timeout.map(timespec_from_duration);
+        let n_events = loop {
+            #[allow(trivial_casts)]
+            let timespec_ptr = timespec
+                .as_ref()
+                .map(|t| t as *const libc::timespec)
+                .unwrap_or(ptr::null());
+
+            let n_events = unsafe {
+                libc::kevent(self.kq, ptr::null(), 0,
+                    self.kevents.as_mut_ptr(), events_cap, timespec_ptr)
+            };
+            if n_events == -1 {
+                let err = io::Error::last_os_error();
+                if err.kind() == io::ErrorKind::Interrupted {
+                    // Interrupted by a signal, try again with the time left.
+                    timespec = remaining_timeout(timeout, start)
+                        .map(timespec_from_duration);
+                    continue;
+                }
+                return Err(err);
+            }
+            break n_events;
         };
+
         match n_events {
-            -1 => Err(io::Error::last_os_error()),
-            0 => Ok(()), // Reached the time limit, no events are pulled.
+            0 => Ok(0), // Reached the time limit, no events are pulled.
             n => {
-                let kevents = kevents[..n as usize].iter().map(kevent_to_event);
+                // Safety: the kernel initialised the first `n` events.
+                unsafe { self.kevents.set_len(n as usize) };
+                event_sink.reserve(n as usize);
+                let kevents = self.kevents.iter().map(kevent_to_event);
                 event_sink.extend(kevents);
-                Ok(())
+                Ok(n as usize)
             },
         }
     }
 
+    /// Applying a signal mask while blocking is not supported by kqueue.
+    pub fn set_sigmask(&mut self, _signals: SignalSet) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Other,
+            "setting a signal mask while polling is not supported on this platform"))
+    }
+
     pub fn register(&self, fd: RawFd, id: event::Id, interests: Interests, opt: RegisterOption) -> io::Result<()> {
+        check_interests(interests)?;
         let flags = opt_to_flags(opt) | libc::EV_ADD;
         // At most we need two changes, but maybe we only need 1.
         let mut changes: [libc::kevent; 2] = unsafe { mem::uninitialized() };
@@ -123,25 +164,101 @@ impl Selector {
         kevent_register(self.kq, &mut changes[0..n_changes], &[])
     }
 
-    pub fn reregister(&self, fd: RawFd, id: event::Id, interests: Interests, opt: RegisterOption) -> io::Result<()> {
-        let flags = opt_to_flags(opt);
-        let write_flags = if interests.is_writable() {
-            flags | libc::EV_ADD
-        } else {
-            flags | libc::EV_DELETE
-        };
-        let read_flags = if interests.is_readable() {
-            flags | libc::EV_ADD
-        } else {
-            flags | libc::EV_DELETE
+    /// Register all `registrations` using a single `kevent` call, returns the
+    /// result per registration.
+    pub fn register_many(&self, registrations: &[(RawFd, event::Id, Interests, RegisterOption)]) -> Vec<io::Result<()>> {
+        let mut results: Vec<io::Result<()>> = registrations.iter()
+            .map(|&(_, _, interests, _)| check_interests(interests))
+            .collect();
+        let mut changes = Vec::with_capacity(registrations.len() * 2);
+        for (&(fd, id, interests, opt), result) in registrations.iter().zip(&results) {
+            if result.is_err() {
+                continue;
+            }
+            let flags = opt_to_flags(opt) | libc::EV_ADD;
+            if interests.is_writable() {
+                changes.push(new_kevent(fd as libc::uintptr_t, libc::EVFILT_WRITE, flags, id));
+            }
+            if interests.is_readable() {
+                changes.push(new_kevent(fd as libc::uintptr_t, libc::EVFILT_READ, flags, id));
+            }
+        }
+        if changes.is_empty() {
+            return results;
+        }
+
+        let n = unsafe {
+            #[allow(trivial_numeric_casts)]
+            libc::kevent(self.kq, changes.as_ptr(), changes.len() as nchanges_t,
+                changes.as_mut_ptr(), changes.len() as nchanges_t, ptr::null())
         };
+        if n == -1 {
+            // See `kevent_register` for the possible errors, on `EINTR` all
+            // changes have been applied.
+            let err = io::Error::last_os_error();
+            return results.into_iter().map(|result| match (result, err.raw_os_error()) {
+                (result @ Err(_), _) => result,
+                (Ok(()), Some(libc::EINTR)) => Ok(()),
+                (Ok(()), Some(code)) => Err(io::Error::from_raw_os_error(code)),
+                (Ok(()), None) => Err(io::Error::new(err.kind(), err.to_string())),
+            }).collect();
+        }
 
-        let mut changes: [libc::kevent; 2] = [
-            new_kevent(fd as libc::uintptr_t, libc::EVFILT_WRITE, write_flags, id),
-            new_kevent(fd as libc::uintptr_t, libc::EVFILT_READ, read_flags, id),
-        ];
+        // Because of `EV_RECEIPT` each change is returned with `EV_ERROR` set,
+        // with the actual error (if any) in the `data` field.
+        for change in &changes[..n as usize] {
+            // We can't use reference to packed structs, so we copy the data
+            // out before use.
+            let data = change.data;
+            if !contains_flag(change.flags, libc::EV_ERROR) || data == 0 {
+                continue;
+            }
+            let fd = change.ident as RawFd;
+            if let Some(index) = registrations.iter().position(|&(r_fd, ..)| r_fd == fd) {
+                if results[index].is_ok() {
+                    results[index] = Err(io::Error::from_raw_os_error(data as i32));
+                }
+            }
+        }
+        results
+    }
+
+    /// `previous` are the interests the file descriptor was registered with,
+    /// if known. It's used to only delete the filters that were added.
+    pub fn reregister(&self, fd: RawFd, id: event::Id, interests: Interests, opt: RegisterOption, previous: Option<Interests>) -> io::Result<()> {
+        check_interests(interests)?;
+        let flags = opt_to_flags(opt);
+        let fd = fd as libc::uintptr_t;
+        // If we don't know what filters were added we need to delete both.
+        let was_writable = previous.map_or(true, Interests::is_writable);
+        let was_readable = previous.map_or(true, Interests::is_readable);
+
+        // At most we need two changes, but maybe we only need 1.
+        let mut changes: [libc::kevent; 2] = [new_kevent(fd, libc::EVFILT_WRITE, flags, id); 2];
+        let mut n_changes = 0;
+
+        if interests.is_writable() {
+            changes[n_changes] = new_kevent(fd, libc::EVFILT_WRITE, flags | libc::EV_ADD, id);
+            n_changes += 1;
+        } else if was_writable {
+            changes[n_changes] = new_kevent(fd, libc::EVFILT_WRITE, flags | libc::EV_DELETE, id);
+            n_changes += 1;
+        }
 
-        kevent_register(self.kq, &mut changes, &[libc::ENOENT as kevent_data_t])
+        if interests.is_readable() {
+            changes[n_changes] = new_kevent(fd, libc::EVFILT_READ, flags | libc::EV_ADD, id);
+            n_changes += 1;
+        } else if was_readable {
+            changes[n_changes] = new_kevent(fd, libc::EVFILT_READ, flags | libc::EV_DELETE, id);
+            n_changes += 1;
+        }
+
+        if previous.is_some() {
+            kevent_register(self.kq, &mut changes[0..n_changes], &[])
+        } else {
+            // One of the filters we're deleting might not have been added.
+            kevent_register(self.kq, &mut changes[0..n_changes], &[libc::ENOENT as kevent_data_t])
+        }
     }
 
     pub fn deregister(&self, fd: RawFd) -> io::Result<()> {
@@ -152,37 +269,76 @@ impl Selector {
             new_kevent(fd as libc::uintptr_t, libc::EVFILT_READ, flags, event::Id(::std::usize::MAX)),
         ];
 
-        kevent_register(self.kq, &mut changes, &[libc::ENOENT as kevent_data_t])
+        // Only one of the filters may be registered, so `ENOENT` is ignored
+        // for a single filter. But if neither filter is registered the file
+        // descriptor wasn't registered, same as `EPOLL_CTL_DEL` returns.
+        kevent_register(self.kq, &mut changes, &[libc::ENOENT as kevent_data_t])?;
+        let not_found = changes.iter().all(|change| {
+            let data = change.data;
+            contains_flag(change.flags, libc::EV_ERROR) && data == libc::ENOENT as kevent_data_t
+        });
+        if not_found {
+            Err(io::Error::from_raw_os_error(libc::ENOENT))
+        } else {
+            Ok(())
+        }
     }
 
     // Used by `Awakener`.
     #[cfg(any(target_os = "freebsd", target_os = "macos"))]
     pub fn setup_awakener(&self, id: event::Id) -> io::Result<()> {
-        // First attempt to accept user space notifications.
-        let kevent = new_kevent(0, libc::EVFILT_USER,
+        // First attempt to accept user space notifications. The identifier of
+        // a user event is unique per kqueue, we use the id for it so that
+        // multiple awakeners can be used.
+        let kevent = new_kevent(id.0 as libc::uintptr_t, libc::EVFILT_USER,
             libc::EV_ADD | libc::EV_CLEAR | libc::EV_RECEIPT, id);
         kevent_register(self.kq, &mut [kevent], &[])
     }
 
-    // Used by `Awakener`.
-    #[cfg(any(target_os = "freebsd", target_os = "macos"))]
+    // Used by `OsQueue` and `Awakener`.
     pub fn try_clone(&self) -> io::Result<Selector> {
-        let new_kq = unsafe { libc::dup(self.kq) };
+        let new_kq = unsafe { libc::fcntl(self.kq, libc::F_DUPFD_CLOEXEC, 0) };
         if new_kq == -1 {
             Err(io::Error::last_os_error())
         } else {
-            Ok(Selector { kq: new_kq })
+            Ok(Selector {
+                kq: new_kq,
+                kevents: Vec::with_capacity(self.kevents.capacity()),
+            })
         }
     }
 
     // Used by `Awakener`.
     #[cfg(any(target_os = "freebsd", target_os = "macos"))]
     pub fn wake(&self, id: event::Id) -> io::Result<()> {
-        let mut kevent = new_kevent(0, libc::EVFILT_USER, libc::EV_ADD | libc::EV_RECEIPT, id);
+        let mut kevent = new_kevent(id.0 as libc::uintptr_t, libc::EVFILT_USER, libc::EV_ADD | libc::EV_RECEIPT, id);
         kevent.fflags = libc::NOTE_TRIGGER;
         kevent_register(self.kq, &mut [kevent], &[])
     }
 
+    // Used by `Timer`.
+    pub fn register_timer(&self, id: event::Id, duration: Duration, periodic: bool) -> io::Result<()> {
+        let mut flags = libc::EV_ADD | libc::EV_RECEIPT;
+        if !periodic {
+            flags |= libc::EV_ONESHOT;
+        }
+        // The identifier of a timer is unique per kqueue, we use the id for it.
+        let mut kevent = new_kevent(id.0 as libc::uintptr_t, libc::EVFILT_TIMER, flags, id);
+        let (fflags, data) = timer_data(duration);
+        kevent.fflags = fflags;
+        kevent.data = data;
+        kevent_register(self.kq, &mut [kevent], &[])
+    }
+
+    // Used by `Timer`.
+    pub fn deregister_timer(&self, id: event::Id) -> io::Result<()> {
+        let flags = libc::EV_DELETE | libc::EV_RECEIPT;
+        let kevent = new_kevent(id.0 as libc::uintptr_t, libc::EVFILT_TIMER, flags, id);
+        // A oneshot timer that already expired is removed by the kernel, so
+        // `ENOENT` is expected.
+        kevent_register(self.kq, &mut [kevent], &[libc::ENOENT as kevent_data_t])
+    }
+
     // Used by `Signals`.
     pub fn register_signals(&self, id: event::Id, signals: SignalSet) -> io::Result<()> {
         let mut changes: [libc::kevent; SignalSet::all().size()] = unsafe { mem::uninitialized() };
@@ -200,9 +356,19 @@ impl Selector {
 }
 
 /// Create a `timespec` from a duration.
+///
+/// If the duration is too large to be represented the seconds are saturated
+/// and the nanoseconds are set to zero, as adding sub-second precision to a
+/// saturated value is meaningless (and could overflow the `timespec`).
 fn timespec_from_duration(duration: Duration) -> libc::timespec {
+    #[allow(clippy::legacy_numeric_constants)] // `time_t::MAX` requires Rust 1.43.
+    let max_secs = libc::time_t::max_value();
+    if duration.as_secs() > max_secs as u64 {
+        return libc::timespec { tv_sec: max_secs, tv_nsec: 0 };
+    }
+
     libc::timespec {
-        tv_sec: min(duration.as_secs(), libc::time_t::max_value() as u64) as libc::time_t,
+        tv_sec: duration.as_secs() as libc::time_t,
         // `Duration::subsec_nanos` is guaranteed to be less than one
         // billion (the number of nanoseconds in a second), making the
         // cast to i32 safe. The cast itself is needed for platforms
@@ -211,31 +377,58 @@ fn timespec_from_duration(duration: Duration) -> libc::timespec {
     }
 }
 
+/// Create the `fflags` and `data` fields for a `EVFILT_TIMER` kevent.
+#[cfg(any(target_os = "freebsd", target_os = "macos"))]
+fn timer_data(duration: Duration) -> (u32, kevent_data_t) {
+    let micros = min(duration.as_micros(), kevent_data_t::max_value() as u128);
+    (libc::NOTE_USECONDS, micros as kevent_data_t)
+}
+
+/// Create the `fflags` and `data` fields for a `EVFILT_TIMER` kevent.
+///
+/// NetBSD and OpenBSD only support timers in milliseconds.
+#[cfg(any(target_os = "netbsd", target_os = "openbsd"))]
+fn timer_data(duration: Duration) -> (u32, kevent_data_t) {
+    let millis = min(duration.as_millis(), kevent_data_t::max_value() as u128);
+    (0, millis as kevent_data_t)
+}
+
 /// Convert a `kevent` into an `Event`.
 fn kevent_to_event(kevent: &libc::kevent) -> Event {
     let id = event::Id(kevent.udata as usize);
     let mut readiness = Ready::EMPTY;
+    let mut error = 0;
 
     if contains_flag(kevent.flags, libc::EV_ERROR) {
-        // The actual error is stored in `kevent.data`, but we can't pass it
-        // to the user from here. So the user needs to try and retrieve the
-        // error themselves.
+        // The actual error is stored in `kevent.data`.
         readiness |= Ready::ERROR;
+        error = kevent.data as i32;
     }
 
     if contains_flag(kevent.flags, libc::EV_EOF) {
-        readiness |= Ready::HUP;
+        // On the read filter this means the peer closed its writing side, but
+        // we might still be able to write.
+        if kevent.filter == libc::EVFILT_READ {
+            readiness |= Ready::READ_HUP;
+        } else {
+            readiness |= Ready::HUP;
+        }
 
         // When the read end of the socket is closed, EV_EOF is set on
         // flags, and fflags contains the error if there is one.
         if kevent.fflags != 0 {
             readiness |= Ready::ERROR;
+            if error == 0 {
+                error = kevent.fflags as i32;
+            }
         }
     }
 
     match kevent.filter {
         libc::EVFILT_READ => readiness |= Ready::READABLE,
         libc::EVFILT_WRITE => readiness |= Ready::WRITABLE,
+        // Used by `Timer`.
+        libc::EVFILT_TIMER => readiness |= Ready::TIMER,
         // Used by the `Awakener`. On platforms that use `eventfd` or a unix
         // pipe it will emit a readable event so we'll fake that here as well.
         #[cfg(any(target_os = "freebsd", target_os = "macos"))]
@@ -243,7 +436,7 @@ fn kevent_to_event(kevent: &libc::kevent) -> Event {
         _ => {},
     }
 
-    Event::new(id, readiness)
+    Event::with_error(id, readiness, error)
 }
 
 /// Convert poll options into `kevent` flags.
@@ -259,6 +452,17 @@ fn opt_to_flags(opt: RegisterOption) -> kevent_flags_t {
     flags
 }
 
+/// Returns an error if `interests` only contains interests not supported by
+/// kqueue, i.e. `Interests::PRIORITY`, as no filter would be added.
+fn check_interests(interests: Interests) -> io::Result<()> {
+    if interests.is_readable() || interests.is_writable() {
+        Ok(())
+    } else {
+        Err(io::Error::new(io::ErrorKind::InvalidInput,
+            "priority interest is not supported on this platform, use readable or writable interests"))
+    }
+}
+
 /// Create a new `kevent`.
 const fn new_kevent(ident: libc::uintptr_t, filter: kevent_filter_t, flags: kevent_flags_t, id: event::Id) -> libc::kevent {
     libc::kevent {
@@ -342,3 +546,32 @@ impl Drop for Selector {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::timespec_from_duration;
+
+    #[test]
+    #[allow(clippy::legacy_numeric_constants)] // `MAX` requires Rust 1.43.
+    fn timespec_from_duration_saturates() {
+        let timespec = timespec_from_duration(Duration::new(u64::max_value(), 999_999_999));
+        assert_eq!(timespec.tv_sec, libc::time_t::max_value());
+        assert_eq!(timespec.tv_nsec, 0);
+    }
+
+    #[test]
+    fn timespec_from_duration_zero() {
+        let timespec = timespec_from_duration(Duration::from_millis(0));
+        assert_eq!(timespec.tv_sec, 0);
+        assert_eq!(timespec.tv_nsec, 0);
+    }
+
+    #[test]
+    fn timespec_from_duration_subsec() {
+        let timespec = timespec_from_duration(Duration::new(10, 123_456_789));
+        assert_eq!(timespec.tv_sec, 10);
+        assert_eq!(timespec.tv_nsec, 123_456_789);
+    }
+}
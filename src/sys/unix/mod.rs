@@ -1,16 +1,24 @@
+use std::time::{Duration, Instant};
+
 mod awakener;
 mod eventedfd;
+#[cfg(feature = "net")]
+mod net;
 mod signals;
+#[cfg(feature = "net")]
 mod tcp;
+#[cfg(feature = "net")]
 mod udp;
 
 pub mod pipe;
+pub mod pty;
+pub mod ucred;
 
 #[cfg(any(target_os = "android", target_os = "linux"))]
 mod epoll;
 
 #[cfg(any(target_os = "android", target_os = "linux"))]
-pub use self::epoll::Selector;
+pub use self::epoll::{socket_error, Selector};
 
 #[cfg(any(target_os = "freebsd", target_os = "macos",
           target_os = "netbsd", target_os = "openbsd"))]
@@ -20,8 +28,26 @@ mod kqueue;
           target_os = "netbsd", target_os = "openbsd"))]
 pub use self::kqueue::Selector;
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub mod coarse_clock;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod splice;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use self::splice::Splicer;
+
 pub use self::awakener::Awakener;
 pub use self::eventedfd::EventedFd;
 pub use self::signals::Signals;
+#[cfg(feature = "net")]
 pub use self::tcp::{TcpListener, TcpStream};
+#[cfg(feature = "net")]
 pub use self::udp::UdpSocket;
+
+/// Returns the time left of `timeout` since `start`, used when retrying a
+/// system call after it was interrupted.
+fn remaining_timeout(timeout: Option<Duration>, start: Instant) -> Option<Duration> {
+    timeout.map(|timeout| timeout.checked_sub(start.elapsed())
+        .unwrap_or_else(|| Duration::from_millis(0)))
+}
@@ -0,0 +1,143 @@
+//! Helpers shared by the TCP and UDP implementations.
+
+use std::io;
+use std::mem::{self, size_of};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::os::unix::io::RawFd;
+
+/// Convert `address` into a socket address the OS understands.
+///
+/// Returns the address and its length, to be used in e.g. `bind(2)` or
+/// `connect(2)`.
+#[allow(trivial_casts)]
+pub fn raw_address(address: &SocketAddr) -> (libc::sockaddr_storage, libc::socklen_t) {
+    let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    let length = match *address {
+        SocketAddr::V4(ref address) => {
+            let raw = unsafe { &mut *(&mut storage as *mut _ as *mut libc::sockaddr_in) };
+            raw.sin_family = libc::AF_INET as libc::sa_family_t;
+            raw.sin_port = address.port().to_be();
+            raw.sin_addr.s_addr = u32::from(*address.ip()).to_be();
+            size_of::<libc::sockaddr_in>()
+        },
+        SocketAddr::V6(ref address) => {
+            let raw = unsafe { &mut *(&mut storage as *mut _ as *mut libc::sockaddr_in6) };
+            raw.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+            raw.sin6_port = address.port().to_be();
+            raw.sin6_flowinfo = address.flowinfo();
+            raw.sin6_addr.s6_addr = address.ip().octets();
+            raw.sin6_scope_id = address.scope_id();
+            size_of::<libc::sockaddr_in6>()
+        },
+    };
+    (storage, length as libc::socklen_t)
+}
+
+/// Convert a socket address filled in by the OS into a `SocketAddr`.
+#[allow(trivial_casts)]
+pub fn to_socket_address(storage: &libc::sockaddr_storage) -> io::Result<SocketAddr> {
+    match libc::c_int::from(storage.ss_family) {
+        libc::AF_INET => {
+            let address = unsafe { &*(storage as *const _ as *const libc::sockaddr_in) };
+            let ip = Ipv4Addr::from(u32::from_be(address.sin_addr.s_addr));
+            let port = u16::from_be(address.sin_port);
+            Ok(SocketAddr::V4(SocketAddrV4::new(ip, port)))
+        },
+        libc::AF_INET6 => {
+            let address = unsafe { &*(storage as *const _ as *const libc::sockaddr_in6) };
+            let ip = Ipv6Addr::from(address.sin6_addr.s6_addr);
+            let port = u16::from_be(address.sin6_port);
+            Ok(SocketAddr::V6(SocketAddrV6::new(ip, port, address.sin6_flowinfo, address.sin6_scope_id)))
+        },
+        _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid socket address family")),
+    }
+}
+
+/// Set a socket option via `setsockopt(2)`.
+///
+/// # Unsafety
+///
+/// `T` must be the type the OS expects for the option.
+#[allow(trivial_casts)]
+pub unsafe fn set_socket_option<T>(fd: RawFd, level: libc::c_int, name: libc::c_int, value: T) -> io::Result<()> {
+    let res = libc::setsockopt(fd, level, name, &value as *const T as *const libc::c_void,
+        size_of::<T>() as libc::socklen_t);
+    if res == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+/// Get a socket option via `getsockopt(2)`.
+///
+/// # Unsafety
+///
+/// `T` must be the type the OS expects for the option.
+#[allow(trivial_casts)]
+pub unsafe fn socket_option<T>(fd: RawFd, level: libc::c_int, name: libc::c_int) -> io::Result<T> {
+    let mut value: T = mem::zeroed();
+    let mut length = size_of::<T>() as libc::socklen_t;
+    let res = libc::getsockopt(fd, level, name, &mut value as *mut T as *mut libc::c_void,
+        &mut length);
+    if res == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(value)
+    }
+}
+
+/// Set a socket option via `setsockopt(2)`, using the raw bytes of `value`.
+pub fn set_socket_option_bytes(fd: RawFd, level: libc::c_int, name: libc::c_int, value: &[u8]) -> io::Result<()> {
+    let res = unsafe {
+        libc::setsockopt(fd, level, name, value.as_ptr() as *const libc::c_void,
+            value.len() as libc::socklen_t)
+    };
+    if res == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+/// Get a socket option via `getsockopt(2)`, writing the raw bytes into `buf`.
+/// Returns the length of the option.
+pub fn socket_option_bytes(fd: RawFd, level: libc::c_int, name: libc::c_int, buf: &mut [u8]) -> io::Result<usize> {
+    let mut length = buf.len() as libc::socklen_t;
+    let res = unsafe {
+        libc::getsockopt(fd, level, name, buf.as_mut_ptr() as *mut libc::c_void, &mut length)
+    };
+    if res == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(length as usize)
+    }
+}
+
+/// Set the `IP_TOS` option, or `IPV6_TCLASS` for IPv6 sockets.
+pub fn set_tos(fd: RawFd, address: &SocketAddr, tos: u32) -> io::Result<()> {
+    let (level, name) = tos_option(address);
+    unsafe { set_socket_option(fd, level, name, tos as libc::c_int) }
+}
+
+/// Get the `IP_TOS` option, or `IPV6_TCLASS` for IPv6 sockets.
+pub fn tos(fd: RawFd, address: &SocketAddr) -> io::Result<u32> {
+    let (level, name) = tos_option(address);
+    unsafe { socket_option::<libc::c_int>(fd, level, name).map(|tos| tos as u32) }
+}
+
+/// Returns the level and name of the type of service option, based on the
+/// address family of the (local) `address` of the socket.
+fn tos_option(address: &SocketAddr) -> (libc::c_int, libc::c_int) {
+    match *address {
+        SocketAddr::V4(..) => (libc::IPPROTO_IP, libc::IP_TOS),
+        SocketAddr::V6(..) => (libc::IPPROTO_IPV6, libc::IPV6_TCLASS),
+    }
+}
+
+/// Set the `SO_BINDTODEVICE` option, `None` removes the binding.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn set_bind_device(fd: RawFd, interface: Option<&str>) -> io::Result<()> {
+    let interface = interface.unwrap_or("").as_bytes();
+    set_socket_option_bytes(fd, libc::SOL_SOCKET, libc::SO_BINDTODEVICE, interface)
+}
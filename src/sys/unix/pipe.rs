@@ -2,7 +2,6 @@ use std::fs::File;
 use std::io::{self, Read, Write};
 #[cfg(feature = "nightly")]
 use std::io::{IoSlice, IoSliceMut};
-use std::mem;
 use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 
 use crate::event;
@@ -11,8 +10,10 @@ use crate::sys::unix::EventedFd;
 
 /// Create a new non-blocking unix pipe.
 ///
-/// This is a wrapper around unix's `pipe` system call and can be used as
-/// interprocess communication channel.
+/// This is a wrapper around unix's `pipe2` system call and can be used as
+/// interprocess communication channel. Both ends of the pipe are in
+/// non-blocking and close-on-exec mode. On macOS, which doesn't support
+/// `pipe2`, `pipe` is used and the flags are set using `fcntl`.
 ///
 /// This channel may be created before forking the process and then one end used
 /// in each process, e.g. the parent process has the sending end to send command
@@ -72,20 +73,32 @@ use crate::sys::unix::EventedFd;
 /// # }
 /// ```
 pub fn new_pipe() -> io::Result<(Sender, Receiver)> {
-    let mut fds: [RawFd; 2] = unsafe { mem::uninitialized() };
-
-    if unsafe { libc::pipe(fds.as_mut_ptr()) } == -1 {
-        Err(io::Error::last_os_error())
-    } else {
-        for fd in &fds {
-            if unsafe { libc::fcntl(*fd, libc::F_SETFL, libc::O_NONBLOCK) } == -1 {
-                return Err(io::Error::last_os_error());
-            }
+    let mut fds: [RawFd; 2] = [-1, -1];
+
+    #[cfg(not(target_os = "macos"))]
+    let res = unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_NONBLOCK | libc::O_CLOEXEC) };
+    #[cfg(target_os = "macos")]
+    let res = unsafe { libc::pipe(fds.as_mut_ptr()) };
+
+    if res == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // Create the types first to ensure the file descriptors are closed in case
+    // of an error below.
+    let r = Receiver { inner: unsafe { File::from_raw_fd(fds[0]) } };
+    let w = Sender { inner: unsafe { File::from_raw_fd(fds[1]) } };
+
+    #[cfg(target_os = "macos")]
+    for fd in &fds {
+        if unsafe { libc::fcntl(*fd, libc::F_SETFL, libc::O_NONBLOCK) } == -1 ||
+            unsafe { libc::fcntl(*fd, libc::F_SETFD, libc::FD_CLOEXEC) } == -1
+        {
+            return Err(io::Error::last_os_error());
         }
-        let r = Receiver { inner: unsafe { File::from_raw_fd(fds[0]) } };
-        let w = Sender { inner: unsafe { File::from_raw_fd(fds[1]) } };
-        Ok((w, r))
     }
+
+    Ok((w, r))
 }
 
 /// Receiving end of an unix pipe.
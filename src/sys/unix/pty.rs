@@ -0,0 +1,293 @@
+use std::ffi::{CStr, OsStr};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+#[cfg(feature = "nightly")]
+use std::io::{IoSlice, IoSliceMut};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+use std::thread;
+
+use crate::event;
+use crate::os::{Evented, Interests, OsQueue, RegisterOption};
+use crate::sys::unix::EventedFd;
+
+/// Create a new pseudo-terminal.
+///
+/// This opens a new pseudo-terminal using `posix_openpt`, `grantpt` and
+/// `unlockpt`, and opens the slave side of it. The master side is in
+/// non-blocking mode and can be registered with [`OsQueue`], the slave side is
+/// in blocking mode as it's meant to be used by a child process, e.g. as its
+/// standard input and output. Both are in close-on-exec mode.
+///
+/// # Notes
+///
+/// Once all file descriptors of the slave side are closed reading from the
+/// master side returns an error, on Linux this is an error with kind `Other`
+/// (`EIO`), rather than returning zero bytes.
+///
+/// By default the terminal echos all input and converts newlines (`\n`) in the
+/// output to `\r\n`.
+///
+/// On platforms other than Android and Linux the slave path is retrieved using
+/// `ptsname`, which isn't thread-safe. Calls made by this function are
+/// serialised, but calling `ptsname` concurrently from elsewhere isn't safe.
+///
+/// # Examples
+///
+/// Spawning a child process with the slave side as standard input and output.
+///
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::io::{self, Read};
+/// use std::process::Command;
+///
+/// use gaea::os::{OsQueue, RegisterOption};
+/// use gaea::unix::{new_pty, PtyMaster};
+/// use gaea::{event, poll};
+///
+/// let mut os_queue = OsQueue::new()?;
+/// let mut events = Vec::new();
+///
+/// let (mut master, slave) = new_pty()?;
+/// os_queue.register(&mut master, event::Id(0), PtyMaster::INTERESTS, RegisterOption::LEVEL)?;
+/// println!("slave: {}", slave.path().display());
+///
+/// let mut child = Command::new("echo")
+///     .arg("Hello world")
+///     .stdin(slave.try_clone()?)
+///     .stdout(slave)
+///     .spawn()?;
+///
+/// let mut output = Vec::new();
+/// let mut buf = [0; 32];
+/// while !output.ends_with(b"\r\n") {
+///     poll::<_, io::Error>(&mut [&mut os_queue], &mut events, None)?;
+///     for event in events.drain(..) {
+///         if event.readiness().is_readable() {
+///             match master.read(&mut buf) {
+///                 Ok(n) => output.extend_from_slice(&buf[..n]),
+///                 Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {},
+///                 Err(err) => return Err(err.into()),
+///             }
+///         }
+///     }
+/// }
+/// assert_eq!(output, b"Hello world\r\n");
+/// child.wait()?;
+/// #     Ok(())
+/// # }
+/// ```
+pub fn new_pty() -> io::Result<(PtyMaster, PtySlave)> {
+    let fd = unsafe { libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY) };
+    if fd == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    // Create the type first to ensure the file descriptor is closed in case of
+    // an error below.
+    let master = PtyMaster { inner: unsafe { File::from_raw_fd(fd) } };
+
+    if unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) } == -1 ||
+        unsafe { libc::fcntl(fd, libc::F_SETFL, libc::O_NONBLOCK) } == -1 ||
+        unsafe { libc::grantpt(fd) } == -1 ||
+        unsafe { libc::unlockpt(fd) } == -1
+    {
+        return Err(io::Error::last_os_error());
+    }
+
+    let path = slave_path(fd)?;
+    let inner = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(libc::O_NOCTTY | libc::O_CLOEXEC)
+        .open(&path)?;
+    Ok((master, PtySlave { inner, path }))
+}
+
+/// Returns the path to the slave side of the pseudo-terminal `fd`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn slave_path(fd: RawFd) -> io::Result<PathBuf> {
+    let mut buf = [0; 128];
+    let res = unsafe { libc::ptsname_r(fd, buf.as_mut_ptr(), buf.len()) };
+    if res != 0 {
+        // Returns the error, rather than setting `errno`.
+        return Err(io::Error::from_raw_os_error(res));
+    }
+    let path = unsafe { CStr::from_ptr(buf.as_ptr()) };
+    Ok(PathBuf::from(OsStr::from_bytes(path.to_bytes())))
+}
+
+/// Lock held while calling `ptsname`, see `slave_path`.
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+static PTSNAME_LOCK: AtomicBool = AtomicBool::new(false);
+
+/// Returns the path to the slave side of the pseudo-terminal `fd`.
+///
+/// # Notes
+///
+/// `ptsname_r` isn't available on this platform, `ptsname` is used instead.
+/// It returns a pointer to a static buffer, which isn't thread-safe, so calls
+/// are serialised using `PTSNAME_LOCK` and the path is copied before the lock
+/// is released.
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+fn slave_path(fd: RawFd) -> io::Result<PathBuf> {
+    while PTSNAME_LOCK.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).is_err() {
+        thread::yield_now();
+    }
+    let ptr = unsafe { libc::ptsname(fd) };
+    let res = if ptr.is_null() {
+        Err(io::Error::last_os_error())
+    } else {
+        let path = unsafe { CStr::from_ptr(ptr) };
+        Ok(PathBuf::from(OsStr::from_bytes(path.to_bytes())))
+    };
+    PTSNAME_LOCK.store(false, Ordering::Release);
+    res
+}
+
+/// Master side of a pseudo-terminal.
+///
+/// Data written to the master side is input for the terminal, i.e. read from
+/// the slave side, and data written to the slave side can be read from the
+/// master side.
+///
+/// See [`new_pty`] for documentation, including examples.
+#[derive(Debug)]
+pub struct PtyMaster {
+    inner: File,
+}
+
+impl PtyMaster {
+    /// The interests to use when registering to receive both readable and
+    /// writable events.
+    pub const INTERESTS: Interests = Interests::BOTH;
+}
+
+impl Evented for PtyMaster {
+    fn register(&mut self, os_queue: &mut OsQueue, id: event::Id, interests: Interests, opt: RegisterOption) -> io::Result<()> {
+        EventedFd(&self.inner.as_raw_fd()).register(os_queue, id, interests, opt)
+    }
+
+    fn reregister(&mut self, os_queue: &mut OsQueue, id: event::Id, interests: Interests, opt: RegisterOption) -> io::Result<()> {
+        EventedFd(&self.inner.as_raw_fd()).reregister(os_queue, id, interests, opt)
+    }
+
+    fn deregister(&mut self, os_queue: &mut OsQueue) -> io::Result<()> {
+        EventedFd(&self.inner.as_raw_fd()).deregister(os_queue)
+    }
+}
+
+impl AsRawFd for PtyMaster {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for PtyMaster {
+    fn into_raw_fd(self) -> RawFd {
+        self.inner.into_raw_fd()
+    }
+}
+
+impl Read for PtyMaster {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+
+    #[cfg(feature = "nightly")]
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        self.inner.read_vectored(bufs)
+    }
+}
+
+impl Write for PtyMaster {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    #[cfg(feature = "nightly")]
+    fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+        self.inner.write_vectored(bufs)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Slave side of a pseudo-terminal.
+///
+/// The slave side is in blocking mode, it's meant to be used by a child
+/// process. It can be converted into [`Stdio`] to be used as standard input,
+/// output or error of a child process, use [`try_clone`] to use it for more
+/// than one.
+///
+/// See [`new_pty`] for documentation, including examples.
+///
+/// [`try_clone`]: PtySlave::try_clone
+#[derive(Debug)]
+pub struct PtySlave {
+    inner: File,
+    path: PathBuf,
+}
+
+impl PtySlave {
+    /// Returns the path to the slave side, e.g. `/dev/pts/1`.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Create a new independently owned handle to the slave side.
+    pub fn try_clone(&self) -> io::Result<PtySlave> {
+        self.inner.try_clone().map(|inner| PtySlave { inner, path: self.path.clone() })
+    }
+}
+
+impl AsRawFd for PtySlave {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for PtySlave {
+    fn into_raw_fd(self) -> RawFd {
+        self.inner.into_raw_fd()
+    }
+}
+
+impl From<PtySlave> for Stdio {
+    fn from(slave: PtySlave) -> Stdio {
+        Stdio::from(slave.inner)
+    }
+}
+
+impl Read for PtySlave {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+
+    #[cfg(feature = "nightly")]
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        self.inner.read_vectored(bufs)
+    }
+}
+
+impl Write for PtySlave {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    #[cfg(feature = "nightly")]
+    fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+        self.inner.write_vectored(bufs)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -123,7 +123,7 @@ mod kqueue {
 pub use self::kqueue::Signals;
 
 /// Create a `libc::sigset_t` from `SignalSet`.
-fn create_sigset(signals: SignalSet) -> io::Result<libc::sigset_t> {
+pub(crate) fn create_sigset(signals: SignalSet) -> io::Result<libc::sigset_t> {
     let mut set: libc::sigset_t = unsafe { mem::uninitialized() };
     if unsafe { libc::sigemptyset(&mut set) } == -1 {
         return Err(io::Error::last_os_error());
@@ -0,0 +1,115 @@
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::ptr;
+
+use crate::sys::unix::pipe::{new_pipe, Receiver, Sender};
+
+#[derive(Debug)]
+pub struct Splicer {
+    sender: Sender,
+    receiver: Receiver,
+    /// Number of bytes in the pipe that still need to be moved into `to`.
+    buffered: usize,
+}
+
+impl Splicer {
+    pub fn new() -> io::Result<Splicer> {
+        let (sender, receiver) = new_pipe()?;
+        Ok(Splicer { sender, receiver, buffered: 0 })
+    }
+
+    pub fn buffered(&self) -> usize {
+        self.buffered
+    }
+
+    pub fn splice(&mut self, from: RawFd, to: RawFd, len: usize) -> io::Result<usize> {
+        // Data left in the pipe from a previous call must be moved first,
+        // otherwise it would be reordered.
+        let mut written = 0;
+        if self.buffered != 0 {
+            written = self.flush(to)?;
+            if self.buffered != 0 {
+                return Ok(written);
+            }
+        }
+
+        // Don't read any data if we can't write it.
+        if !poll_writable(to)? {
+            return would_block(written);
+        }
+
+        match splice_fd(from, self.sender.as_raw_fd(), len) {
+            // `from` reached the end, report it once all buffered data is
+            // written.
+            Ok(0) => return Ok(written),
+            Ok(n) => self.buffered = n,
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => return would_block(written),
+            Err(err) => return Err(err),
+        }
+
+        match self.flush(to) {
+            Ok(n) => Ok(written + n),
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => would_block(written),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Move as many buffered bytes as possible from the pipe into `to`, without
+    /// blocking. Returns a `WouldBlock` error if no bytes could be moved.
+    fn flush(&mut self, to: RawFd) -> io::Result<usize> {
+        let mut written = 0;
+        while self.buffered != 0 {
+            match splice_fd(self.receiver.as_raw_fd(), to, self.buffered) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(n) => {
+                    self.buffered -= n;
+                    written += n;
+                },
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => return would_block(written),
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(written)
+    }
+}
+
+/// Returns `written` if it's not zero, or a `WouldBlock` error otherwise.
+fn would_block(written: usize) -> io::Result<usize> {
+    if written == 0 {
+        Err(io::ErrorKind::WouldBlock.into())
+    } else {
+        Ok(written)
+    }
+}
+
+/// Wrapper around the `splice(2)` system call.
+fn splice_fd(from: RawFd, to: RawFd, len: usize) -> io::Result<usize> {
+    let flags = libc::SPLICE_F_MOVE | libc::SPLICE_F_NONBLOCK;
+    loop {
+        let n = unsafe { libc::splice(from, ptr::null_mut(), to, ptr::null_mut(), len, flags) };
+        if n != -1 {
+            return Ok(n as usize);
+        }
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+}
+
+/// Returns true if `fd` is writable, without blocking.
+fn poll_writable(fd: RawFd) -> io::Result<bool> {
+    let mut pollfd = libc::pollfd { fd, events: libc::POLLOUT, revents: 0 };
+    loop {
+        match unsafe { libc::poll(&mut pollfd, 1, 0) } {
+            -1 => {
+                let err = io::Error::last_os_error();
+                if err.kind() != io::ErrorKind::Interrupted {
+                    return Err(err);
+                }
+            },
+            // Errors, e.g. `POLLERR`, are returned by the actual I/O.
+            _ => return Ok(pollfd.revents != 0),
+        }
+    }
+}
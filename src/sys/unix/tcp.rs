@@ -1,13 +1,25 @@
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::cmp::min;
 use std::io::{self, Read, Write};
 #[cfg(feature = "nightly")]
 use std::io::{IoSlice, IoSliceMut};
-use std::mem::size_of_val;
+#[cfg(any(target_os = "android", target_os = "linux",
+          target_os = "freebsd", target_os = "openbsd"))]
+use std::mem;
+use std::mem::{size_of_val, MaybeUninit};
 use std::net::{self, SocketAddr};
 use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+use std::time::Duration;
 
 use crate::event;
 use crate::os::{Evented, Interests, OsQueue, RegisterOption};
 use crate::sys::unix::eventedfd::EventedFd;
+#[cfg(any(target_os = "android", target_os = "linux",
+          target_os = "freebsd", target_os = "openbsd"))]
+use crate::sys::unix::net::to_socket_address;
+use crate::sys::unix::net::{raw_address, set_socket_option_bytes, set_tos, socket_option_bytes, tos};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use crate::sys::unix::net::{set_bind_device, set_socket_option, socket_option};
 
 #[derive(Debug)]
 pub struct TcpStream {
@@ -16,33 +28,60 @@ pub struct TcpStream {
 
 impl TcpStream {
     pub fn connect(address: SocketAddr) -> io::Result<TcpStream> {
-        // Create a raw socket file descriptor.
-        let socket_family = match address {
-            SocketAddr::V4(..) => libc::AF_INET,
-            SocketAddr::V6(..) => libc::AF_INET6,
-        };
-        let socket_fd = unsafe { libc::socket(socket_family, libc::SOCK_STREAM, 0) };
-        if socket_fd == -1 {
-            return Err(io::Error::last_os_error());
-        }
+        let socket_fd = new_socket(&address)?;
+        // Ensure the socket is closed in case of an error.
+        let stream = unsafe { net::TcpStream::from_raw_fd(socket_fd) };
+        connect(socket_fd, &address)?;
+        Ok(TcpStream { stream })
+    }
 
-        // Set non blocking mode.
-        if unsafe { libc::fcntl(socket_fd, libc::F_SETFL, libc::O_NONBLOCK) } == -1 {
-            return Err(io::Error::last_os_error());
-        }
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn connect_with_device(address: SocketAddr, interface: &str) -> io::Result<TcpStream> {
+        let socket_fd = new_socket(&address)?;
+        // Ensure the socket is closed in case of an error.
+        let stream = unsafe { net::TcpStream::from_raw_fd(socket_fd) };
+        // Must be set before connecting, otherwise the route is already
+        // determined.
+        set_bind_device(socket_fd, Some(interface))?;
+        connect(socket_fd, &address)?;
+        Ok(TcpStream { stream })
+    }
+
+    /// Connect using TCP Fast Open, sending `data` in the SYN packet if
+    /// possible, by calling `sendto(2)` with `MSG_FASTOPEN`.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    #[allow(trivial_casts)]
+    pub fn connect_fastopen(address: SocketAddr, data: &[u8]) -> io::Result<(TcpStream, usize)> {
+        let socket_fd = new_socket(&address)?;
+        // Ensure the socket is closed in case of an error.
+        let stream = unsafe { net::TcpStream::from_raw_fd(socket_fd) };
 
-        // Connect to the provided address. If this would block it will return
-        // `EINPROGRESS`, which we don't consider an error here.
         let (raw_address, raw_address_length) = raw_address(&address);
-        if unsafe { libc::connect(socket_fd, raw_address, raw_address_length) } == -1 {
-            let err = io::Error::last_os_error();
-            if err.raw_os_error() != Some(libc::EINPROGRESS) {
-                return Err(err);
-            }
+        let n = unsafe {
+            libc::sendto(socket_fd, data.as_ptr() as *const _, data.len(), libc::MSG_FASTOPEN,
+                &raw_address as *const _ as *const _, raw_address_length)
+        };
+        if n != -1 {
+            return Ok((TcpStream { stream }, n as usize));
         }
 
-        let stream = unsafe { net::TcpStream::from_raw_fd(socket_fd) };
-        Ok(TcpStream { stream })
+        let err = io::Error::last_os_error();
+        match err.raw_os_error() {
+            // No Fast Open cookie for the server yet, a regular SYN (requesting
+            // a cookie) is send and none of the data.
+            Some(libc::EINPROGRESS) => Ok((TcpStream { stream }, 0)),
+            // Client side Fast Open is disabled, use a regular connect.
+            Some(libc::EOPNOTSUPP) => {
+                drop(stream);
+                TcpStream::connect(address).map(|stream| (stream, 0))
+            },
+            _ => Err(err),
+        }
+    }
+
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    pub fn connect_fastopen(address: SocketAddr, _: &[u8]) -> io::Result<(TcpStream, usize)> {
+        TcpStream::connect(address).map(|stream| (stream, 0))
     }
 
     pub fn peer_addr(&self) -> io::Result<SocketAddr> {
@@ -61,6 +100,16 @@ impl TcpStream {
         self.stream.ttl()
     }
 
+    pub fn set_tos(&mut self, tos: u32) -> io::Result<()> {
+        let address = self.stream.local_addr()?;
+        set_tos(self.stream.as_raw_fd(), &address, tos)
+    }
+
+    pub fn tos(&mut self) -> io::Result<u32> {
+        let address = self.stream.local_addr()?;
+        tos(self.stream.as_raw_fd(), &address)
+    }
+
     pub fn set_nodelay(&mut self, nodelay: bool) -> io::Result<()> {
         self.stream.set_nodelay(nodelay)
     }
@@ -69,31 +118,107 @@ impl TcpStream {
         self.stream.nodelay()
     }
 
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    #[allow(clippy::legacy_numeric_constants)] // `u32::MAX` requires Rust 1.43.
+    pub fn set_user_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+        let millis = min(timeout.as_millis(), u128::from(libc::c_uint::max_value())) as libc::c_uint;
+        unsafe { set_socket_option(self.stream.as_raw_fd(), libc::IPPROTO_TCP, libc::TCP_USER_TIMEOUT, millis) }
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn user_timeout(&mut self) -> io::Result<Duration> {
+        unsafe { socket_option::<libc::c_uint>(self.stream.as_raw_fd(), libc::IPPROTO_TCP, libc::TCP_USER_TIMEOUT) }
+            .map(|millis| Duration::from_millis(u64::from(millis)))
+    }
+
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    pub fn set_user_timeout(&mut self, _: Duration) -> io::Result<()> {
+        Err(io::Error::from_raw_os_error(libc::ENOPROTOOPT))
+    }
+
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    pub fn user_timeout(&mut self) -> io::Result<Duration> {
+        Err(io::Error::from_raw_os_error(libc::ENOPROTOOPT))
+    }
+
     pub fn peek(&self, buf: &mut [u8]) -> io::Result<usize> {
         self.stream.peek(buf)
     }
 
+    pub fn read_uninit(&self, buf: &mut [MaybeUninit<u8>]) -> io::Result<usize> {
+        let n = unsafe { libc::read(self.stream.as_raw_fd(), buf.as_mut_ptr() as *mut _, buf.len()) };
+        if n == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(n as usize)
+        }
+    }
+
     pub fn shutdown(&self, how: net::Shutdown) -> io::Result<()> {
         self.stream.shutdown(how)
     }
 
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        self.stream.set_nonblocking(nonblocking)
+    }
+
     pub fn take_error(&self) -> io::Result<Option<io::Error>> {
         self.stream.take_error()
     }
+
+    pub fn set_sockopt(&self, level: libc::c_int, name: libc::c_int, value: &[u8]) -> io::Result<()> {
+        set_socket_option_bytes(self.stream.as_raw_fd(), level, name, value)
+    }
+
+    pub fn get_sockopt(&self, level: libc::c_int, name: libc::c_int, buf: &mut [u8]) -> io::Result<usize> {
+        socket_option_bytes(self.stream.as_raw_fd(), level, name, buf)
+    }
 }
 
-// Implementation taken from the Rust standard library.
-// Copyright 2015 The Rust Project Developers.
+/// Connect `socket_fd` to `address`. If this would block it will return
+/// `EINPROGRESS`, which we don't consider an error here.
 #[allow(trivial_casts)]
-fn raw_address(address: &SocketAddr) -> (*const libc::sockaddr, libc::socklen_t) {
-    match *address {
-        SocketAddr::V4(ref address) => {
-            (address as *const _ as *const _, size_of_val(address) as libc::socklen_t)
+fn connect(socket_fd: RawFd, address: &SocketAddr) -> io::Result<()> {
+    let (raw_address, raw_address_length) = raw_address(address);
+    if unsafe { libc::connect(socket_fd, &raw_address as *const _ as *const _, raw_address_length) } == -1 {
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() != Some(libc::EINPROGRESS) {
+            return Err(err);
         }
-        SocketAddr::V6(ref address) => {
-            (address as *const _ as *const _, size_of_val(address) as libc::socklen_t)
+    }
+    Ok(())
+}
+
+/// Create a new non-blocking, close-on-exec TCP socket for `address`.
+fn new_socket(address: &SocketAddr) -> io::Result<RawFd> {
+    let socket_family = match address {
+        SocketAddr::V4(..) => libc::AF_INET,
+        SocketAddr::V6(..) => libc::AF_INET6,
+    };
+
+    #[cfg(not(target_os = "macos"))]
+    let socket_type = libc::SOCK_STREAM | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC;
+    // macOS doesn't support setting the flags when creating the socket.
+    #[cfg(target_os = "macos")]
+    let socket_type = libc::SOCK_STREAM;
+
+    let socket_fd = unsafe { libc::socket(socket_family, socket_type, 0) };
+    if socket_fd == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        if unsafe { libc::fcntl(socket_fd, libc::F_SETFL, libc::O_NONBLOCK) } == -1 ||
+            unsafe { libc::fcntl(socket_fd, libc::F_SETFD, libc::FD_CLOEXEC) } == -1
+        {
+            let err = io::Error::last_os_error();
+            let _ = unsafe { libc::close(socket_fd) };
+            return Err(err);
         }
     }
+
+    Ok(socket_fd)
 }
 
 impl Read for TcpStream {
@@ -168,16 +293,11 @@ pub struct TcpListener {
 }
 
 impl TcpListener {
-    pub fn bind(address: SocketAddr) -> io::Result<TcpListener> {
-        // Create a raw socket file descriptor.
-        let socket_family = match address {
-            SocketAddr::V4(..) => libc::AF_INET,
-            SocketAddr::V6(..) => libc::AF_INET6,
-        };
-        let socket_fd = unsafe { libc::socket(socket_family, libc::SOCK_STREAM, 0) };
-        if socket_fd == -1 {
-            return Err(io::Error::last_os_error());
-        }
+    #[allow(trivial_casts)]
+    pub fn bind(address: SocketAddr, backlog: i32) -> io::Result<TcpListener> {
+        let socket_fd = new_socket(&address)?;
+        // Ensure the socket is closed in case of an error.
+        let listener = unsafe { net::TcpListener::from_raw_fd(socket_fd) };
 
         // Set the `SO_REUSEPORT` and `SO_REUSEADDR` options.
         unsafe {
@@ -185,23 +305,17 @@ impl TcpListener {
             enable_socket_option(socket_fd, libc::SOL_SOCKET, libc::SO_REUSEADDR)?;
         }
 
-        // Set non blocking mode.
-        if unsafe { libc::fcntl(socket_fd, libc::F_SETFL, libc::O_NONBLOCK) } == -1 {
-            return Err(io::Error::last_os_error());
-        }
-
         // Bind to the address
         let (raw_address, raw_address_length) = raw_address(&address);
-        if unsafe { libc::bind(socket_fd, raw_address, raw_address_length) } == -1 {
+        if unsafe { libc::bind(socket_fd, &raw_address as *const _ as *const _, raw_address_length) } == -1 {
             return Err(io::Error::last_os_error());
         }
 
         // Mark the socket as passive.
-        if unsafe { libc::listen(socket_fd, 128) } == -1 {
+        if unsafe { libc::listen(socket_fd, backlog) } == -1 {
             return Err(io::Error::last_os_error());
         }
 
-        let listener = unsafe { net::TcpListener::from_raw_fd(socket_fd) };
         Ok(TcpListener { listener })
     }
 
@@ -209,6 +323,33 @@ impl TcpListener {
         self.listener.try_clone().map(|listener| TcpListener { listener })
     }
 
+    /// Accept a new connection, using `accept4(2)` to create the socket in
+    /// non-blocking and close-on-exec mode in a single system call.
+    #[cfg(any(target_os = "android", target_os = "linux",
+              target_os = "freebsd", target_os = "openbsd"))]
+    #[allow(trivial_casts)]
+    pub fn accept(&self) -> io::Result<(TcpStream, SocketAddr)> {
+        let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+        let mut length = size_of_val(&storage) as libc::socklen_t;
+        let socket_fd = unsafe {
+            libc::accept4(self.listener.as_raw_fd(),
+                &mut storage as *mut _ as *mut _, &mut length,
+                libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC)
+        };
+        if socket_fd == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // Ensure the socket is closed in case of an error.
+        let stream = unsafe { net::TcpStream::from_raw_fd(socket_fd) };
+        let address = to_socket_address(&storage)?;
+        Ok((TcpStream { stream }, address))
+    }
+
+    /// Accept a new connection, `accept4(2)` isn't available on this platform
+    /// so we need to set the socket to non-blocking mode after accepting it.
+    #[cfg(not(any(target_os = "android", target_os = "linux",
+                  target_os = "freebsd", target_os = "openbsd")))]
     pub fn accept(&self) -> io::Result<(TcpStream, SocketAddr)> {
         let (stream, address) = self.listener.accept()?;
         stream.set_nonblocking(true)?;
@@ -227,9 +368,34 @@ impl TcpListener {
         self.listener.ttl()
     }
 
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_bind_device(&mut self, interface: Option<&str>) -> io::Result<()> {
+        set_bind_device(self.listener.as_raw_fd(), interface)
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_fastopen(&mut self, queue_length: u32) -> io::Result<()> {
+        unsafe {
+            set_socket_option(self.listener.as_raw_fd(), libc::IPPROTO_TCP, libc::TCP_FASTOPEN,
+                queue_length as libc::c_int)
+        }
+    }
+
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        self.listener.set_nonblocking(nonblocking)
+    }
+
     pub fn take_error(&self) -> io::Result<Option<io::Error>> {
         self.listener.take_error()
     }
+
+    pub fn set_sockopt(&self, level: libc::c_int, name: libc::c_int, value: &[u8]) -> io::Result<()> {
+        set_socket_option_bytes(self.listener.as_raw_fd(), level, name, value)
+    }
+
+    pub fn get_sockopt(&self, level: libc::c_int, name: libc::c_int, buf: &mut [u8]) -> io::Result<usize> {
+        socket_option_bytes(self.listener.as_raw_fd(), level, name, buf)
+    }
 }
 
 /// Enable a socket option via `setsockopt`.
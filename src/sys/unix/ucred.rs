@@ -0,0 +1,94 @@
+//! Peer credentials of Unix domain sockets.
+
+use std::io;
+use std::os::unix::io::AsRawFd;
+
+/// Credentials of the process on the other side of a Unix domain socket.
+///
+/// See [`peer_cred`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct UCred {
+    /// Process id of the peer, not available on all platforms.
+    pub pid: Option<libc::pid_t>,
+    /// User id of the peer.
+    pub uid: libc::uid_t,
+    /// Group id of the peer.
+    pub gid: libc::gid_t,
+}
+
+/// Returns the credentials of the peer of the Unix domain `socket`.
+///
+/// This can be used to authorize local clients, for example to only allow
+/// connections from processes running as root. The credentials are those of
+/// the peer at the time the connection was established (or the socket pair was
+/// created).
+///
+/// # Notes
+///
+/// On Linux and Android this uses `SO_PEERCRED`. On the BSDs and macOS this
+/// uses `getpeereid`, which doesn't provide the process id of the peer, so
+/// [`UCred::pid`] is always `None` there.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::os::unix::net::UnixStream;
+///
+/// use gaea::unix::peer_cred;
+///
+/// let (stream1, _stream2) = UnixStream::pair()?;
+/// let cred = peer_cred(&stream1)?;
+///
+/// if cred.uid != 0 {
+///     println!("peer not running as root");
+/// }
+/// #     Ok(())
+/// # }
+/// ```
+pub fn peer_cred<S>(socket: &S) -> io::Result<UCred>
+    where S: AsRawFd,
+{
+    imp::peer_cred(socket.as_raw_fd())
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod imp {
+    use std::io;
+    use std::mem::{size_of, zeroed};
+    use std::os::unix::io::RawFd;
+
+    use super::UCred;
+
+    #[allow(trivial_casts)]
+    pub fn peer_cred(fd: RawFd) -> io::Result<UCred> {
+        let mut cred: libc::ucred = unsafe { zeroed() };
+        let mut len = size_of::<libc::ucred>() as libc::socklen_t;
+        if unsafe { libc::getsockopt(fd, libc::SOL_SOCKET, libc::SO_PEERCRED,
+            &mut cred as *mut _ as *mut libc::c_void, &mut len) } == -1
+        {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(UCred { pid: Some(cred.pid), uid: cred.uid, gid: cred.gid })
+        }
+    }
+}
+
+#[cfg(any(target_os = "freebsd", target_os = "macos",
+          target_os = "netbsd", target_os = "openbsd"))]
+mod imp {
+    use std::io;
+    use std::os::unix::io::RawFd;
+
+    use super::UCred;
+
+    pub fn peer_cred(fd: RawFd) -> io::Result<UCred> {
+        let mut uid = 0;
+        let mut gid = 0;
+        if unsafe { libc::getpeereid(fd, &mut uid, &mut gid) } == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(UCred { pid: None, uid, gid })
+        }
+    }
+}
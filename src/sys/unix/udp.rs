@@ -1,10 +1,18 @@
 use std::io;
-use std::net::{self, SocketAddr};
+#[cfg(feature = "nightly")]
+use std::io::IoSliceMut;
+use std::mem::{self, size_of, size_of_val, MaybeUninit};
+use std::net::{self, IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+use std::ptr;
 
 use crate::event;
 use crate::os::{Evented, Interests, OsQueue, RegisterOption};
 use crate::sys::unix::EventedFd;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use crate::sys::unix::net::{raw_address, set_bind_device};
+use crate::sys::unix::net::{set_socket_option, set_socket_option_bytes, set_tos, socket_option_bytes};
+use crate::sys::unix::net::{to_socket_address, tos};
 
 #[derive(Debug)]
 pub struct UdpSocket {
@@ -46,13 +54,304 @@ impl UdpSocket {
         self.socket.recv(buf)
     }
 
+    pub fn recv_uninit(&self, buf: &mut [MaybeUninit<u8>]) -> io::Result<usize> {
+        let n = unsafe { libc::recv(self.socket.as_raw_fd(), buf.as_mut_ptr() as *mut _, buf.len(), 0) };
+        if n == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(n as usize)
+        }
+    }
+
     pub fn peek(&self, buf: &mut [u8]) -> io::Result<usize> {
         self.socket.peek(buf)
     }
 
+    pub fn set_tos(&self, tos: u32) -> io::Result<()> {
+        let address = self.socket.local_addr()?;
+        set_tos(self.socket.as_raw_fd(), &address, tos)
+    }
+
+    pub fn tos(&self) -> io::Result<u32> {
+        let address = self.socket.local_addr()?;
+        tos(self.socket.as_raw_fd(), &address)
+    }
+
+    pub fn set_pktinfo(&self, pktinfo: bool) -> io::Result<()> {
+        let (level, name) = match self.socket.local_addr()? {
+            SocketAddr::V4(..) => (libc::IPPROTO_IP, IP_RECV_DST_ADDR),
+            SocketAddr::V6(..) => (libc::IPPROTO_IPV6, libc::IPV6_RECVPKTINFO),
+        };
+        unsafe { set_socket_option(self.socket.as_raw_fd(), level, name, pktinfo as libc::c_int) }
+    }
+
+    #[allow(trivial_casts, trivial_numeric_casts)]
+    #[allow(clippy::io_other_error)] // `io::Error::other` requires Rust 1.74.
+    pub fn recv_from_with_info(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr, IpAddr)> {
+        let mut address: libc::sockaddr_storage = unsafe { mem::zeroed() };
+        let mut iovec = libc::iovec {
+            iov_base: buf.as_mut_ptr() as *mut _,
+            iov_len: buf.len(),
+        };
+        // Large enough for a single `in6_pktinfo` control message, using
+        // `u64` to ensure the alignment of `cmsghdr` is correct.
+        let mut control = [0u64; 8];
+
+        let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+        msg.msg_name = &mut address as *mut _ as *mut _;
+        msg.msg_namelen = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+        msg.msg_iov = &mut iovec;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.as_mut_ptr() as *mut _;
+        msg.msg_controllen = size_of_val(&control) as _;
+
+        let n = unsafe { libc::recvmsg(self.socket.as_raw_fd(), &mut msg, 0) };
+        if n == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        if msg.msg_flags & libc::MSG_CTRUNC != 0 {
+            // The destination address might be in the discarded control
+            // messages, so we can't tell where the datagram was send to.
+            return Err(io::Error::new(io::ErrorKind::Other,
+                "control messages truncated, are other control messages enabled?"));
+        }
+
+        let source = to_socket_address(&address)?;
+        match unsafe { destination_address(&msg) } {
+            Some(destination) => Ok((n as usize, source, destination)),
+            None => Err(io::Error::new(io::ErrorKind::Other,
+                "missing destination address, is pktinfo enabled?")),
+        }
+    }
+
+    #[cfg(feature = "nightly")]
+    #[allow(trivial_casts, trivial_numeric_casts)]
+    pub fn recv_from_vectored(&self, bufs: &mut [IoSliceMut]) -> io::Result<(usize, SocketAddr)> {
+        let mut address: libc::sockaddr_storage = unsafe { mem::zeroed() };
+        let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+        msg.msg_name = &mut address as *mut _ as *mut _;
+        msg.msg_namelen = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+        // `IoSliceMut` is ABI compatible with `iovec` on Unix.
+        msg.msg_iov = bufs.as_mut_ptr() as *mut libc::iovec;
+        msg.msg_iovlen = bufs.len() as _;
+
+        let n = unsafe { libc::recvmsg(self.socket.as_raw_fd(), &mut msg, 0) };
+        if n == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            to_socket_address(&address).map(|source| (n as usize, source))
+        }
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_bind_device(&self, interface: Option<&str>) -> io::Result<()> {
+        set_bind_device(self.socket.as_raw_fd(), interface)
+    }
+
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        self.socket.set_nonblocking(nonblocking)
+    }
+
     pub fn take_error(&self) -> io::Result<Option<io::Error>> {
         self.socket.take_error()
     }
+
+    pub fn set_sockopt(&self, level: libc::c_int, name: libc::c_int, value: &[u8]) -> io::Result<()> {
+        set_socket_option_bytes(self.socket.as_raw_fd(), level, name, value)
+    }
+
+    pub fn get_sockopt(&self, level: libc::c_int, name: libc::c_int, buf: &mut [u8]) -> io::Result<usize> {
+        socket_option_bytes(self.socket.as_raw_fd(), level, name, buf)
+    }
+
+    /// Send multiple datagrams using a single `sendmmsg(2)` call.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    #[allow(trivial_casts)]
+    pub fn send_mmsg(&self, bufs: &[(&[u8], SocketAddr)]) -> io::Result<usize> {
+        if bufs.is_empty() {
+            return Ok(0);
+        }
+
+        let addresses: Vec<_> = bufs.iter()
+            .map(|(_, address)| raw_address(address))
+            .collect();
+        let mut iovecs: Vec<libc::iovec> = bufs.iter()
+            .map(|&(buf, _)| libc::iovec {
+                iov_base: buf.as_ptr() as *mut _,
+                iov_len: buf.len(),
+            })
+            .collect();
+        let mut msgs: Vec<libc::mmsghdr> = iovecs.iter_mut().zip(addresses.iter())
+            .map(|(iovec, &(ref address, length))| {
+                let mut msg: libc::mmsghdr = unsafe { mem::zeroed() };
+                msg.msg_hdr.msg_name = address as *const _ as *mut _;
+                msg.msg_hdr.msg_namelen = length;
+                msg.msg_hdr.msg_iov = iovec;
+                msg.msg_hdr.msg_iovlen = 1;
+                msg
+            })
+            .collect();
+
+        let n = unsafe {
+            libc::sendmmsg(self.socket.as_raw_fd(), msgs.as_mut_ptr(),
+                msgs.len() as libc::c_uint, 0)
+        };
+        if n == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(n as usize)
+        }
+    }
+
+    /// Fallback for platforms that don't support `sendmmsg(2)`, sending the
+    /// datagrams one by one.
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    pub fn send_mmsg(&self, bufs: &[(&[u8], SocketAddr)]) -> io::Result<usize> {
+        for (n, &(buf, ref address)) in bufs.iter().enumerate() {
+            match self.socket.send_to(buf, address) {
+                Ok(_) => {},
+                Err(ref err) if n != 0 && err.kind() == io::ErrorKind::WouldBlock => return Ok(n),
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(bufs.len())
+    }
+
+    /// Receive multiple datagrams using a single `recvmmsg(2)` call.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    #[allow(trivial_casts)]
+    pub fn recv_mmsg(&self, bufs: &mut [(&mut [u8], SocketAddr, bool)]) -> io::Result<usize> {
+        if bufs.is_empty() {
+            return Ok(0);
+        }
+
+        let mut addresses: Vec<libc::sockaddr_storage> = vec![unsafe { mem::zeroed() }; bufs.len()];
+        let mut iovecs: Vec<libc::iovec> = bufs.iter_mut()
+            .map(|&mut (ref mut buf, _, _)| libc::iovec {
+                iov_base: buf.as_mut_ptr() as *mut _,
+                iov_len: buf.len(),
+            })
+            .collect();
+        let mut msgs: Vec<libc::mmsghdr> = iovecs.iter_mut().zip(addresses.iter_mut())
+            .map(|(iovec, address)| {
+                let mut msg: libc::mmsghdr = unsafe { mem::zeroed() };
+                msg.msg_hdr.msg_name = address as *mut _ as *mut _;
+                msg.msg_hdr.msg_namelen = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+                msg.msg_hdr.msg_iov = iovec;
+                msg.msg_hdr.msg_iovlen = 1;
+                msg
+            })
+            .collect();
+
+        let n = unsafe {
+            libc::recvmmsg(self.socket.as_raw_fd(), msgs.as_mut_ptr(),
+                msgs.len() as libc::c_uint, 0, ptr::null_mut())
+        };
+        if n == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let n = n as usize;
+        for ((entry, msg), address) in bufs.iter_mut().zip(msgs.iter()).zip(addresses.iter()).take(n) {
+            entry.1 = to_socket_address(address)?;
+            entry.2 = msg.msg_hdr.msg_flags & libc::MSG_TRUNC != 0;
+            truncate(&mut entry.0, msg.msg_len as usize);
+        }
+        Ok(n)
+    }
+
+    /// Fallback for platforms that don't support `recvmmsg(2)`, receiving the
+    /// datagrams one by one.
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    pub fn recv_mmsg(&self, bufs: &mut [(&mut [u8], SocketAddr, bool)]) -> io::Result<usize> {
+        for (n, entry) in bufs.iter_mut().enumerate() {
+            match self.recv_msg(&mut *entry.0) {
+                Ok((length, address, truncated)) => {
+                    entry.1 = address;
+                    entry.2 = truncated;
+                    truncate(&mut entry.0, length);
+                },
+                Err(ref err) if n != 0 && err.kind() == io::ErrorKind::WouldBlock => return Ok(n),
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(bufs.len())
+    }
+
+    /// Receive a single datagram using `recvmsg(2)`, also returning whether or
+    /// not the datagram was truncated.
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    #[allow(trivial_casts)]
+    fn recv_msg(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr, bool)> {
+        let mut address: libc::sockaddr_storage = unsafe { mem::zeroed() };
+        let mut iovec = libc::iovec {
+            iov_base: buf.as_mut_ptr() as *mut _,
+            iov_len: buf.len(),
+        };
+        let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+        msg.msg_name = &mut address as *mut _ as *mut _;
+        msg.msg_namelen = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+        msg.msg_iov = &mut iovec;
+        msg.msg_iovlen = 1;
+
+        let n = unsafe { libc::recvmsg(self.socket.as_raw_fd(), &mut msg, 0) };
+        if n == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        let source = to_socket_address(&address)?;
+        Ok((n as usize, source, msg.msg_flags & libc::MSG_TRUNC != 0))
+    }
+}
+
+/// Socket option to receive the destination address of IPv4 datagrams.
+#[cfg(any(target_os = "android", target_os = "linux", target_os = "macos",
+          target_os = "netbsd"))]
+const IP_RECV_DST_ADDR: libc::c_int = libc::IP_PKTINFO;
+#[cfg(any(target_os = "freebsd", target_os = "openbsd"))]
+const IP_RECV_DST_ADDR: libc::c_int = libc::IP_RECVDSTADDR;
+
+/// Get the destination address from the control messages in `msg`, if any.
+///
+/// # Unsafety
+///
+/// `msg` must be filled by `recvmsg(2)`.
+#[allow(clippy::cast_ptr_alignment)]
+unsafe fn destination_address(msg: &libc::msghdr) -> Option<IpAddr> {
+    let mut cmsg = libc::CMSG_FIRSTHDR(msg);
+    while !cmsg.is_null() {
+        let data = libc::CMSG_DATA(cmsg);
+        match ((*cmsg).cmsg_level, (*cmsg).cmsg_type) {
+            #[cfg(any(target_os = "android", target_os = "linux", target_os = "macos",
+                      target_os = "netbsd"))]
+            (libc::IPPROTO_IP, libc::IP_PKTINFO) => {
+                let info = ptr::read_unaligned(data as *const libc::in_pktinfo);
+                let ip = Ipv4Addr::from(u32::from_be(info.ipi_addr.s_addr));
+                return Some(IpAddr::V4(ip));
+            },
+            #[cfg(any(target_os = "freebsd", target_os = "openbsd"))]
+            (libc::IPPROTO_IP, libc::IP_RECVDSTADDR) => {
+                let addr = ptr::read_unaligned(data as *const libc::in_addr);
+                let ip = Ipv4Addr::from(u32::from_be(addr.s_addr));
+                return Some(IpAddr::V4(ip));
+            },
+            (libc::IPPROTO_IPV6, libc::IPV6_PKTINFO) => {
+                let info = ptr::read_unaligned(data as *const libc::in6_pktinfo);
+                let ip = Ipv6Addr::from(info.ipi6_addr.s6_addr);
+                return Some(IpAddr::V6(ip));
+            },
+            _ => {},
+        }
+        cmsg = libc::CMSG_NXTHDR(msg, cmsg);
+    }
+    None
+}
+
+/// Truncate `buf` to `length` bytes.
+#[allow(clippy::mem_replace_with_default)] // `mem::take` requires Rust 1.40.
+fn truncate(buf: &mut &mut [u8], length: usize) {
+    let b = mem::replace(buf, &mut []);
+    *buf = &mut b[..length];
 }
 
 impl Evented for UdpSocket {
@@ -2,17 +2,32 @@
 
 use std::cmp::Reverse;
 use std::collections::BinaryHeap;
+use std::iter::FusedIterator;
 use std::mem::replace;
 use std::time::{Duration, Instant};
+use std::vec;
 
 use log::trace;
 
 use crate::event::{self, Event, Ready};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use crate::sys;
 
 /// Timer readiness queue.
 ///
 /// Polling this event source never returns an error.
 ///
+/// # Complexity
+///
+/// The deadlines are stored in a binary heap, ordered by deadline. Adding a
+/// deadline is `O(log n)`, determining the next deadline (e.g. in
+/// [`next_deadline`]) is `O(1)` and each expired deadline is removed in `O(log
+/// n)` when polling. Removing a deadline before it expired is `O(n)`, see
+/// [`remove_deadline`].
+///
+/// [`next_deadline`]: Timers::next_deadline
+/// [`remove_deadline`]: Timers::remove_deadline
+///
 /// # Examples
 ///
 /// ```
@@ -36,9 +51,143 @@ use crate::event::{self, Event, Ready};
 /// #     Ok(())
 /// # }
 /// ```
+///
+/// # Clock
+///
+/// By default the current time is determined using [`SystemClock`], i.e.
+/// [`Instant::now`]. Using [`Timers::with_clock`] a different [`Clock`] can be
+/// used, for example to control time in tests, or [`CoarseClock`] to trade
+/// precision for cheaper reads of the current time (Linux only).
 #[derive(Debug)]
-pub struct Timers {
+pub struct Timers<C = SystemClock> {
     deadlines: BinaryHeap<Reverse<Deadline>>,
+    clock: C,
+}
+
+/// Source of the current time, used by [`Timers`].
+///
+/// # Examples
+///
+/// A clock that is advanced manually, useful in testing.
+///
+/// ```
+/// use std::cell::Cell;
+/// use std::time::{Duration, Instant};
+///
+/// use gaea::{event, poll, Clock, Event, Ready, Timers};
+///
+/// #[derive(Debug)]
+/// struct MockClock(Cell<Instant>);
+///
+/// impl Clock for MockClock {
+///     fn now(&self) -> Instant {
+///         self.0.get()
+///     }
+/// }
+///
+/// let start = Instant::now();
+/// let mut timers = Timers::with_clock(MockClock(Cell::new(start)));
+/// let mut events = Vec::new();
+///
+/// let id = event::Id(0);
+/// timers.add_timeout(id, Duration::from_secs(10));
+///
+/// // The deadline hasn't passed yet.
+/// poll::<_, ()>(&mut [&mut timers], &mut events, Some(Duration::from_millis(0))).unwrap();
+/// assert!(events.is_empty());
+///
+/// // Advance the clock past the deadline.
+/// timers.clock().0.set(start + Duration::from_secs(10));
+/// poll::<_, ()>(&mut [&mut timers], &mut events, None).unwrap();
+/// assert_eq!(events, vec![Event::new(id, Ready::TIMER)]);
+/// ```
+pub trait Clock {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+}
+
+/// [`Clock`] using the system's monotonic clock, i.e. [`Instant::now`].
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// [`Clock`] using the coarse monotonic clock of the OS,
+/// `CLOCK_MONOTONIC_COARSE`.
+///
+/// Reading the coarse clock is much cheaper than reading the clock used by
+/// [`SystemClock`], at the cost of precision: the coarse clock is only updated
+/// once per tick of the kernel, typically every 1 to 4 milliseconds. This
+/// makes it a good fit for timers with a granularity of (tens of) milliseconds
+/// or more under heavy timer churn, but not for precise timers.
+///
+/// The returned time lags behind the actual time by up to twice the
+/// resolution of the clock, which means deadlines never trigger early but can
+/// trigger that much late. The resolution can be retrieved using
+/// [`CoarseClock::resolution`].
+///
+/// # Notes
+///
+/// This is only available on Linux and Android.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use gaea::{event, CoarseClock, Timers};
+///
+/// let mut timers = Timers::with_clock(CoarseClock::new());
+/// timers.add_timeout(event::Id(0), Duration::from_secs(1));
+/// ```
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Copy, Clone, Debug)]
+pub struct CoarseClock {
+    /// `Instant` at the time `coarse_base` was read, minus the resolution of
+    /// the coarse clock.
+    base: Instant,
+    /// Time of the coarse clock when the `CoarseClock` was created.
+    coarse_base: Duration,
+    resolution: Duration,
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl CoarseClock {
+    /// Create a new `CoarseClock`.
+    pub fn new() -> CoarseClock {
+        let coarse_base = sys::coarse_clock::now();
+        let now = Instant::now();
+        let resolution = sys::coarse_clock::resolution();
+        // The coarse clock lags behind the actual time by up to its
+        // resolution. Subtracting the resolution ensures we never return a
+        // time that is ahead of `Instant::now`.
+        let base = now.checked_sub(resolution).unwrap_or(now);
+        CoarseClock { base, coarse_base, resolution }
+    }
+
+    /// Returns the resolution of the clock.
+    pub fn resolution(&self) -> Duration {
+        self.resolution
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl Clock for CoarseClock {
+    fn now(&self) -> Instant {
+        // The coarse clock is monotonic, so this never underflows.
+        self.base + (sys::coarse_clock::now() - self.coarse_base)
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl Default for CoarseClock {
+    fn default() -> CoarseClock {
+        CoarseClock::new()
+    }
 }
 
 /// A deadline.
@@ -48,23 +197,105 @@ pub struct Timers {
 struct Deadline {
     deadline: Instant,
     id: event::Id,
+    /// Readiness of the event triggered once the deadline has passed.
+    readiness: Ready,
+    /// Interval used to reschedule the deadline after it passed, if any.
+    interval: Option<Duration>,
+}
+
+impl Deadline {
+    /// Returns the next deadline of an interval deadline, skipping all ticks
+    /// that were missed, i.e. ticks before `now`.
+    fn next(&self, interval: Duration, now: Instant) -> Instant {
+        if interval == Duration::from_millis(0) {
+            return now;
+        }
+
+        // We advance from the scheduled deadline, not from `now`, to prevent
+        // drifting.
+        let next = self.deadline + interval;
+        if next > now {
+            next
+        } else {
+            // The deadline was missed more then once, skip the missed ticks.
+            // This is calculated in nanoseconds as the number of missed ticks
+            // can overflow the `u32` used in multiplying a `Duration`.
+            let interval_nanos = interval.as_nanos();
+            let elapsed = (now - self.deadline).as_nanos() % interval_nanos;
+            // Always smaller than or equal to `interval`, so this fits in a
+            // `Duration`.
+            let left = interval_nanos - elapsed;
+            now + Duration::new((left / NANOS_PER_SEC) as u64, (left % NANOS_PER_SEC) as u32)
+        }
+    }
 }
 
 impl Timers {
     /// Create a new time event source.
     pub fn new() -> Timers {
+        Timers::with_clock(SystemClock)
+    }
+}
+
+impl<C> Timers<C>
+    where C: Clock,
+{
+    /// Create a new time event source using `clock` to determine the current
+    /// time.
+    pub fn with_clock(clock: C) -> Timers<C> {
         Timers {
             deadlines: BinaryHeap::new(),
+            clock,
         }
     }
 
+    /// Returns a reference to the [`Clock`].
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Returns a mutable reference to the [`Clock`].
+    pub fn clock_mut(&mut self) -> &mut C {
+        &mut self.clock
+    }
+
     /// Add a new deadline.
     ///
     /// This will cause an event to trigger after the `deadline` has passed with
     /// the [`Ready::TIMER`] readiness and provided `id`.
     pub fn add_deadline(&mut self, id: event::Id, deadline: Instant) {
-        trace!("adding deadline: id={}, deadline={:?}", id, deadline);
-        self.deadlines.push(Reverse(Deadline { id, deadline }));
+        self.add_deadline_with(id, deadline, Ready::TIMER);
+    }
+
+    /// Add a new deadline with custom readiness.
+    ///
+    /// This is the same as [`add_deadline`], but the event triggered after the
+    /// `deadline` has passed will have the provided `readiness`, rather then
+    /// [`Ready::TIMER`]. This allows, for example, to differentiate between a
+    /// read and write timeout for the same id.
+    ///
+    /// [`add_deadline`]: Timers::add_deadline
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Instant;
+    ///
+    /// use gaea::{event, poll, Event, Ready, Timers};
+    ///
+    /// let mut timers = Timers::new();
+    /// let mut events = Vec::new();
+    ///
+    /// // Timeout for writing.
+    /// let id = event::Id(0);
+    /// timers.add_deadline_with(id, Instant::now(), Ready::TIMER | Ready::WRITABLE);
+    ///
+    /// poll::<_, ()>(&mut [&mut timers], &mut events, None).unwrap();
+    /// assert_eq!(events.get(0), Some(&Event::new(id, Ready::TIMER | Ready::WRITABLE)));
+    /// ```
+    pub fn add_deadline_with(&mut self, id: event::Id, deadline: Instant, readiness: Ready) {
+        trace!("adding deadline: id={}, deadline={:?}, readiness={:?}", id, deadline, readiness);
+        self.deadlines.push(Reverse(Deadline { id, deadline, readiness, interval: None }));
     }
 
     /// Add a new timeout.
@@ -72,68 +303,335 @@ impl Timers {
     /// This is the same as [`add_deadline`], but then using a `Duration`, see
     /// [`add_deadline`] for more information.
     ///
+    /// If `timeout` is too large to be represented as an `Instant` it will be
+    /// saturated to a deadline far in the future, rather then panicking.
+    ///
     /// [`add_deadline`]: `Timers::add_deadline`
     pub fn add_timeout(&mut self, id: event::Id, timeout: Duration) {
-        self.add_deadline(id, Instant::now() + timeout);
+        let deadline = self.deadline_from_timeout(timeout);
+        self.add_deadline(id, deadline);
+    }
+
+    /// Add a new interval.
+    ///
+    /// This will cause an event to trigger each time `interval` has passed
+    /// with the [`Ready::TIMER`] readiness and provided `id`, until it is
+    /// removed using [`remove_interval`].
+    ///
+    /// The next deadline is computed from the previous (scheduled) deadline,
+    /// not from the time at which the deadline was polled, to prevent drifting.
+    /// If polling stalled for longer then `interval`, the missed ticks are
+    /// skipped and only a single event is triggered.
+    ///
+    /// [`remove_interval`]: Timers::remove_interval
+    ///
+    /// # Notes
+    ///
+    /// `interval` must not be zero.
+    pub fn add_interval(&mut self, id: event::Id, interval: Duration) {
+        trace!("adding interval: id={}, interval={:?}", id, interval);
+        debug_assert!(interval != Duration::from_millis(0), "can't add an interval of zero");
+        let deadline = self.deadline_from_timeout(interval);
+        self.deadlines.push(Reverse(Deadline { id, deadline, readiness: Ready::TIMER, interval: Some(interval) }));
     }
 
     /// Remove a previously added deadline.
     ///
     /// # Notes
     ///
-    /// Removing a deadline is a costly operation. For better performance it is
-    /// advised to not bother with removing and instead ignore the event when it
-    /// comes up.
+    /// Removing a deadline is a costly operation, `O(n)` as the binary heap
+    /// has to be rebuilt. For better performance it is advised to not bother
+    /// with removing and instead ignore the event when it comes up.
     pub fn remove_deadline(&mut self, id: event::Id) {
         trace!("removing deadline: id={}", id);
+        self.remove(|deadline| deadline.id == id);
+    }
 
+    /// Remove a previously added interval.
+    ///
+    /// # Notes
+    ///
+    /// Just like [`remove_deadline`] this is a costly operation.
+    ///
+    /// [`remove_deadline`]: Timers::remove_deadline
+    pub fn remove_interval(&mut self, id: event::Id) {
+        trace!("removing interval: id={}", id);
+        self.remove(|deadline| deadline.id == id && deadline.interval.is_some());
+    }
+
+    /// Remove all deadlines and intervals with `id`, returning the number of
+    /// deadlines removed.
+    ///
+    /// This is useful when multiple deadlines are added using the same id,
+    /// e.g. read, write and idle timeouts for a single connection, to remove
+    /// them all once the connection is closed.
+    ///
+    /// # Notes
+    ///
+    /// Just like [`remove_deadline`] this is a costly operation, but it's
+    /// cheaper than removing the deadlines one by one.
+    ///
+    /// [`remove_deadline`]: Timers::remove_deadline
+    #[allow(clippy::mem_replace_with_default)] // `mem::take` requires Rust 1.40.
+    pub fn remove_all(&mut self, id: event::Id) -> usize {
+        trace!("removing all deadlines: id={}", id);
+        let mut deadlines = replace(&mut self.deadlines, BinaryHeap::new()).into_vec();
+        let length = deadlines.len();
+        deadlines.retain(|deadline| deadline.0.id != id);
+        let removed = length - deadlines.len();
+        self.deadlines = BinaryHeap::from(deadlines);
+        removed
+    }
+
+    /// Returns the next deadline, if any.
+    ///
+    /// This can be used to determine the timeout for polling when not using
+    /// [`poll`], or when combining `Timers` with other blocking calls. Note
+    /// that the deadline might already have passed.
+    ///
+    /// [`poll`]: crate::poll
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Instant;
+    ///
+    /// use gaea::{event, Timers};
+    ///
+    /// let mut timers = Timers::new();
+    /// assert_eq!(timers.next_deadline(), None);
+    ///
+    /// let deadline = Instant::now();
+    /// timers.add_deadline(event::Id(0), deadline);
+    /// assert_eq!(timers.next_deadline(), Some(deadline));
+    /// ```
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.deadlines.peek().map(|deadline| deadline.0.deadline)
+    }
+
+    /// Returns the number of pending deadlines, including intervals.
+    pub fn len(&self) -> usize {
+        self.deadlines.len()
+    }
+
+    /// Returns true if there are no pending deadlines.
+    pub fn is_empty(&self) -> bool {
+        self.deadlines.is_empty()
+    }
+
+    /// Returns an iterator over the id and deadline of all pending deadlines.
+    ///
+    /// The deadlines are returned in order, the first deadline to expire is
+    /// returned first. Deadlines that expire at the same time are ordered by
+    /// id. For intervals the next deadline is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    ///
+    /// use gaea::{event, Timers};
+    ///
+    /// let mut timers = Timers::new();
+    /// timers.add_timeout(event::Id(0), Duration::from_secs(1));
+    /// timers.add_timeout(event::Id(1), Duration::from_millis(200));
+    /// assert_eq!(timers.len(), 2);
+    ///
+    /// // The first deadline to expire is returned first.
+    /// let ids: Vec<_> = timers.iter().map(|(id, _)| id).collect();
+    /// assert_eq!(ids, vec![event::Id(1), event::Id(0)]);
+    ///
+    /// for (id, deadline) in timers.iter() {
+    ///     println!("timer {} expires in {:?}", id, deadline - Instant::now());
+    /// }
+    /// ```
+    pub fn iter(&self) -> Deadlines<'_> {
+        let mut deadlines: Vec<&Deadline> = self.deadlines.iter()
+            .map(|deadline| &deadline.0)
+            .collect();
+        deadlines.sort_unstable();
+        Deadlines { deadlines: deadlines.into_iter() }
+    }
+
+    /// Remove all expired deadlines, returning the id and the deadline it
+    /// was scheduled for.
+    ///
+    /// This is an alternative to polling `Timers` as an event source, for
+    /// when the scheduled deadline is needed, e.g. to measure the latency of
+    /// handling timers. Just like polling, interval deadlines are
+    /// rescheduled. If ticks of an interval were missed only a single
+    /// deadline is returned, the one scheduled first, which can be compared to
+    /// the current time to detect the missed ticks.
+    ///
+    /// Deadlines are removed as the iterator is advanced, deadlines not
+    /// returned by the iterator remain in `Timers`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    ///
+    /// use gaea::{event, Timers};
+    ///
+    /// let mut timers = Timers::new();
+    /// let deadline = Instant::now() - Duration::from_millis(10);
+    /// timers.add_deadline(event::Id(0), deadline);
+    ///
+    /// for (id, deadline) in timers.take_expired() {
+    ///     println!("deadline {} was late by {:?}", id, deadline.elapsed());
+    /// }
+    /// assert_eq!(timers.next_deadline(), None);
+    /// ```
+    pub fn take_expired(&mut self) -> TakeExpired<'_, C> {
+        let now = self.clock.now();
+        let left = self.deadlines.len();
+        TakeExpired { timers: self, now, left }
+    }
+
+    /// Remove the first deadline that expired at or before `now`, rescheduling
+    /// it if it's an interval.
+    fn pop_expired(&mut self, now: Instant) -> Option<Deadline> {
+        match self.deadlines.peek() {
+            Some(deadline) if deadline.0.deadline <= now => {
+                let mut deadline = self.deadlines.pop().unwrap().0;
+                let expired = deadline;
+                if let Some(interval) = deadline.interval {
+                    // Reschedule the interval.
+                    deadline.deadline = deadline.next(interval, now);
+                    self.deadlines.push(Reverse(deadline));
+                }
+                Some(expired)
+            },
+            _ => None,
+        }
+    }
+
+    /// Remove the first deadline for which `predicate` returns true.
+    fn remove<F>(&mut self, predicate: F)
+        where F: Fn(&Deadline) -> bool,
+    {
         // TODO: optimize this.
         let index = self.deadlines.iter()
-            .position(|deadline| deadline.0.id == id);
+            .position(|deadline| predicate(&deadline.0));
 
         if let Some(index) = index {
             let deadlines = replace(&mut self.deadlines, BinaryHeap::new());
             let mut deadlines_vec = deadlines.into_vec();
             let removed_deadline = deadlines_vec.swap_remove(index);
-            debug_assert_eq!(removed_deadline.0.id, id, "remove_deadline: removed incorrect deadline");
+            debug_assert!(predicate(&removed_deadline.0), "remove: removed incorrect deadline");
             drop(replace(&mut self.deadlines, BinaryHeap::from(deadlines_vec)));
         }
     }
+
+    /// Create a deadline `timeout` from now, saturating on overflow.
+    fn deadline_from_timeout(&self, timeout: Duration) -> Instant {
+        let now = self.clock.now();
+        now.checked_add(timeout).unwrap_or_else(|| now + MAX_TIMEOUT)
+    }
 }
 
-impl<ES, E> event::Source<ES, E> for Timers
+impl<ES, E, C> event::Source<ES, E> for Timers<C>
     where ES: event::Sink,
+          C: Clock,
 {
     fn max_timeout(&self) -> Option<Duration> {
-        self.deadlines.peek().map(|deadline| {
-            let now = Instant::now();
-            if deadline.0.deadline <= now {
+        self.next_deadline().map(|deadline| {
+            let now = self.clock.now();
+            if deadline <= now {
                 // Deadline has already expired, so no blocking.
                 Duration::from_millis(0)
             } else {
                 // Time between the deadline and right now.
-                deadline.0.deadline.duration_since(now)
+                deadline.duration_since(now)
             }
         })
     }
 
-    fn poll(&mut self, event_sink: &mut ES) -> Result<(), E> {
+    fn poll(&mut self, event_sink: &mut ES) -> Result<usize, E> {
         trace!("polling timers");
-        let now = Instant::now();
+        let now = self.clock.now();
 
+        let mut n_events = 0;
         for _ in 0..event_sink.capacity_left().min(self.deadlines.len()) {
-            match self.deadlines.peek() {
-                Some(deadline) if deadline.0.deadline <= now => {
-                    let deadline = self.deadlines.pop().unwrap().0;
-                    event_sink.add(Event::new(deadline.id, Ready::TIMER));
+            match self.pop_expired(now) {
+                Some(deadline) => {
+                    event_sink.add(Event::new(deadline.id, deadline.readiness));
+                    n_events += 1;
                 },
-                _ => break,
+                None => break,
             }
         }
-        Ok(())
+        Ok(n_events)
+    }
+}
+
+/// Iterator over pending deadlines.
+///
+/// Created by [`Timers::iter`].
+#[derive(Clone, Debug)]
+pub struct Deadlines<'a> {
+    /// Sorted deadlines.
+    deadlines: vec::IntoIter<&'a Deadline>,
+}
+
+impl<'a> Iterator for Deadlines<'a> {
+    type Item = (event::Id, Instant);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.deadlines.next().map(|deadline| (deadline.id, deadline.deadline))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.deadlines.size_hint()
+    }
+}
+
+impl<'a> ExactSizeIterator for Deadlines<'a> {
+    fn len(&self) -> usize {
+        self.deadlines.len()
+    }
+}
+
+impl<'a> FusedIterator for Deadlines<'a> {}
+
+/// Iterator over expired deadlines.
+///
+/// Created by [`Timers::take_expired`].
+#[derive(Debug)]
+pub struct TakeExpired<'a, C = SystemClock> {
+    timers: &'a mut Timers<C>,
+    /// Time at which the iterator was created, only deadlines at or before it
+    /// are returned.
+    now: Instant,
+    /// Maximum number of deadlines left to return, this ensures rescheduled
+    /// intervals are returned at most once.
+    left: usize,
+}
+
+impl<'a, C> Iterator for TakeExpired<'a, C>
+    where C: Clock,
+{
+    type Item = (event::Id, Instant);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.left == 0 {
+            return None;
+        }
+        self.left -= 1;
+        self.timers.pop_expired(self.now).map(|deadline| (deadline.id, deadline.deadline))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.left))
     }
 }
 
+/// Number of nanoseconds in a second.
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// Maximum timeout used in saturating a deadline, roughly 30 years.
+const MAX_TIMEOUT: Duration = Duration::from_secs(30 * 365 * 24 * 60 * 60);
+
 impl Default for Timers {
     fn default() -> Timers {
         Timers::new()
@@ -16,6 +16,21 @@ use crate::event::{self, Event};
 ///
 /// Polling this event source never returns an error.
 ///
+/// # Ordering
+///
+/// Events are returned in the order in which they were added, first in first
+/// out. This also holds if not all events fit in the event sink, the remaining
+/// events are returned first in the next poll. Events are never reordered or
+/// merged, adding the same event twice returns it twice.
+///
+/// Polling only removes the events that are added to the event sink, from the
+/// front of the queue. If the sink has no capacity left, i.e.
+/// [`capacity_left`] returns `Limited(0)`, no events are removed and polling
+/// returns zero. The events stay in the queue until polled using a sink with
+/// capacity left.
+///
+/// [`capacity_left`]: event::Sink::capacity_left
+///
 /// # Examples
 ///
 /// ```
@@ -40,6 +55,8 @@ use crate::event::{self, Event};
 #[derive(Debug)]
 pub struct Queue {
     events: Vec<Event>,
+    /// Maximum number of events in the queue, `None` if unbounded.
+    capacity: Option<usize>,
 }
 
 impl Queue {
@@ -47,15 +64,110 @@ impl Queue {
     pub fn new() -> Queue {
         Queue {
             events: Vec::new(),
+            capacity: None,
+        }
+    }
+
+    /// Create a new bounded user space readiness event queue.
+    ///
+    /// The queue can hold at most `capacity` events, use [`try_add`] to add
+    /// events while respecting this bound.
+    ///
+    /// [`try_add`]: Queue::try_add
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gaea::{Event, Queue, Ready, event};
+    ///
+    /// let mut queue = Queue::with_capacity(1);
+    ///
+    /// let event = Event::new(event::Id(0), Ready::READABLE);
+    /// assert_eq!(queue.try_add(event), Ok(()));
+    /// // The queue is full.
+    /// assert_eq!(queue.try_add(event), Err(event));
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Queue {
+        Queue {
+            events: Vec::with_capacity(capacity),
+            capacity: Some(capacity),
         }
     }
 
     /// Add a new readiness event.
+    ///
+    /// # Notes
+    ///
+    /// This ignores the bound of queues created with [`with_capacity`], in
+    /// debug builds this will panic if the queue is full. Use [`try_add`] to
+    /// respect the bound.
+    ///
+    /// [`with_capacity`]: Queue::with_capacity
+    /// [`try_add`]: Queue::try_add
     pub fn add(&mut self, event: Event) {
+        debug_assert!(!self.is_full(), "adding event to full user space queue");
         trace!("adding user space event: id={}, readiness={:?}",
             event.id(), event.readiness());
         self.events.push(event);
     }
+
+    /// Try to add a new readiness event.
+    ///
+    /// If the queue is full, i.e. its bound set in [`with_capacity`] is
+    /// reached, this returns the event as error. For unbounded queues this
+    /// never fails.
+    ///
+    /// [`with_capacity`]: Queue::with_capacity
+    pub fn try_add(&mut self, event: Event) -> Result<(), Event> {
+        if self.is_full() {
+            trace!("user space queue full, rejecting event: id={}, readiness={:?}",
+                event.id(), event.readiness());
+            Err(event)
+        } else {
+            self.add(event);
+            Ok(())
+        }
+    }
+
+    /// Returns a reference to the next event, without removing it from the
+    /// queue.
+    ///
+    /// This is the event that will be returned first when polling.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gaea::{Event, Queue, Ready, event};
+    ///
+    /// let mut queue = Queue::new();
+    /// assert_eq!(queue.peek(), None);
+    ///
+    /// let event = Event::new(event::Id(0), Ready::READABLE);
+    /// queue.add(event);
+    /// queue.add(Event::new(event::Id(1), Ready::WRITABLE));
+    /// assert_eq!(queue.peek(), Some(&event));
+    /// ```
+    pub fn peek(&self) -> Option<&Event> {
+        self.events.first()
+    }
+
+    /// Returns the number of events in the queue.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns true if the queue contains no events.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Returns true if the queue is bounded and full.
+    pub fn is_full(&self) -> bool {
+        match self.capacity {
+            Some(capacity) => self.events.len() >= capacity,
+            None => false,
+        }
+    }
 }
 
 impl<ES, E> event::Source<ES, E> for Queue
@@ -69,11 +181,12 @@ impl<ES, E> event::Source<ES, E> for Queue
         }
     }
 
-    fn poll(&mut self, event_sink: &mut ES) -> Result<(), E> {
+    fn poll(&mut self, event_sink: &mut ES) -> Result<usize, E> {
         trace!("polling user space events");
-        let drain = self.events.drain(..event_sink.capacity_left().min(self.events.len()));
-        event_sink.extend(drain);
-        Ok(())
+        let n_events = event_sink.capacity_left().min(self.events.len());
+        event_sink.reserve(n_events);
+        event_sink.extend(self.events.drain(..n_events));
+        Ok(n_events)
     }
 }
 
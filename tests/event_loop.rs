@@ -0,0 +1,112 @@
+use std::io::{self, Read, Write};
+use std::time::Duration;
+
+use gaea::event::{self, Event, Ready};
+use gaea::os::{Interests, RegisterOption};
+use gaea::unix::{new_pipe, Receiver};
+use gaea::{EventLoop, Handler};
+
+mod util;
+
+use self::util::init;
+
+/// Handler that records all events, stopping after `stop_after` events.
+struct RecordHandler {
+    events: Vec<Event>,
+    stop_after: usize,
+}
+
+impl Handler for RecordHandler {
+    fn handle(&mut self, event_loop: &mut EventLoop, event: Event) -> io::Result<()> {
+        self.events.push(event);
+        if self.events.len() >= self.stop_after {
+            event_loop.stop();
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn event_loop() {
+    init();
+    let mut event_loop = EventLoop::new().expect("unable to create event loop");
+    assert!(!event_loop.is_running());
+
+    let event1 = Event::new(event::Id(0), Ready::READABLE);
+    let event2 = Event::new(event::Id(1), Ready::WRITABLE);
+    event_loop.reactor_mut().notify(event1);
+    event_loop.reactor_mut().notify(event2);
+
+    // Stopping still handles the remaining events in the batch.
+    let mut handler = RecordHandler { events: Vec::new(), stop_after: 1 };
+    event_loop.run(&mut handler).unwrap();
+    assert!(!event_loop.is_running());
+    assert_eq!(handler.events, vec![event1, event2]);
+}
+
+#[test]
+fn event_loop_register_in_handler() {
+    struct PipeHandler {
+        receiver: Option<Receiver>,
+        read: Vec<u8>,
+    }
+
+    impl Handler for PipeHandler {
+        fn handle(&mut self, event_loop: &mut EventLoop, event: Event) -> io::Result<()> {
+            match event.id() {
+                event::Id(0) => {
+                    let receiver = self.receiver.as_mut().unwrap();
+                    event_loop.reactor_mut().register(receiver, event::Id(1), Interests::READABLE, RegisterOption::EDGE)
+                },
+                event::Id(1) => {
+                    let _ = self.receiver.as_mut().unwrap().read_to_end(&mut self.read)
+                        .or_else(|err| if err.kind() == io::ErrorKind::WouldBlock { Ok(0) } else { Err(err) })?;
+                    event_loop.stop();
+                    Ok(())
+                },
+                id => panic!("unexpected event id: {}", id),
+            }
+        }
+    }
+
+    init();
+    let mut event_loop = EventLoop::new().unwrap();
+    let (mut sender, receiver) = new_pipe().unwrap();
+    sender.write_all(b"Hello").unwrap();
+    event_loop.reactor_mut().notify(Event::new(event::Id(0), Ready::READABLE));
+
+    let mut handler = PipeHandler { receiver: Some(receiver), read: Vec::new() };
+    event_loop.run(&mut handler).unwrap();
+    assert_eq!(handler.read, b"Hello");
+}
+
+#[test]
+fn event_loop_handler_error() {
+    struct ErrorHandler(usize);
+
+    impl Handler for ErrorHandler {
+        fn handle(&mut self, _: &mut EventLoop, _: Event) -> io::Result<()> {
+            self.0 += 1;
+            Err(io::Error::new(io::ErrorKind::Other, "handler error"))
+        }
+    }
+
+    init();
+    let mut event_loop = EventLoop::new().unwrap();
+    event_loop.reactor_mut().notify(Event::new(event::Id(0), Ready::READABLE));
+    event_loop.reactor_mut().notify(Event::new(event::Id(1), Ready::READABLE));
+
+    let mut handler = ErrorHandler(0);
+    let err = event_loop.run(&mut handler).unwrap_err();
+    assert_eq!(err.to_string(), "handler error");
+    assert!(!event_loop.is_running());
+    assert_eq!(handler.0, 1);
+
+    // The remaining event is kept and handled by the next run, before polling
+    // again.
+    let mut handler = RecordHandler { events: Vec::new(), stop_after: 1 };
+    event_loop.run(&mut handler).unwrap();
+    assert_eq!(handler.events, vec![Event::new(event::Id(1), Ready::READABLE)]);
+    let mut events = Vec::new();
+    assert_eq!(event_loop.reactor_mut().poll(&mut events, Some(Duration::from_millis(0))).unwrap(), 0);
+}
@@ -1,4 +1,11 @@
-use gaea::event::{self, Capacity, Event, Ready, Sink};
+use gaea::event::{self, Capacity, CappedEvents, Chain, Event, FilterSink, IdGenerator, LimitSink, MapId, PrioritySink, Ready, Sink};
+use std::time::{Duration, Instant};
+
+use gaea::{poll, Queue, Timers};
+
+mod util;
+
+use self::util::EventsCapacity;
 
 #[test]
 fn events_vec() {
@@ -11,6 +18,385 @@ fn events_vec() {
     assert_eq!(events.pop(), Some(event));
 }
 
+#[test]
+#[cfg(feature = "nightly")]
+fn array_events() {
+    use gaea::event::ArrayEvents;
+
+    let mut events: ArrayEvents<2> = ArrayEvents::new();
+    assert!(events.is_empty());
+    assert_eq!(events.capacity_left(), Capacity::Limited(2));
+
+    let event1 = Event::new(event::Id(0), Ready::READABLE);
+    let event2 = Event::new(event::Id(1), Ready::WRITABLE);
+    events.add(event1);
+    assert_eq!(events.capacity_left(), Capacity::Limited(1));
+    events.add(event2);
+    assert_eq!(events.capacity_left(), Capacity::Limited(0));
+    assert_eq!(events.len(), 2);
+    assert_eq!(events.as_slice(), &[event1, event2]);
+
+    // Over-filling drops the event.
+    events.add(Event::new(event::Id(2), Ready::ERROR));
+    assert_eq!(events.len(), 2);
+    assert_eq!(events.as_slice(), &[event1, event2]);
+
+    let drain = events.drain();
+    assert_eq!(drain.len(), 2);
+    assert_eq!(drain.collect::<Vec<_>>(), vec![event1, event2]);
+    assert!(events.is_empty());
+    assert_eq!(events.capacity_left(), Capacity::Limited(2));
+
+    events.add(event1);
+    events.clear();
+    assert!(events.is_empty());
+    assert_eq!(format!("{:?}", events), "[]");
+}
+
+#[test]
+#[cfg(feature = "nightly")]
+fn array_events_capacity() {
+    use gaea::event::ArrayEvents;
+    use gaea::Queue;
+
+    let mut queue = Queue::new();
+    let event = Event::new(event::Id(0), Ready::READABLE);
+    for _ in 0..3 {
+        queue.add(event);
+    }
+
+    // Poll should respect the capacity of the event sink.
+    let mut events: ArrayEvents<2> = ArrayEvents::new();
+    gaea::poll::<_, ()>(&mut [&mut queue], &mut events, None).unwrap();
+    assert_eq!(events.len(), 2);
+    gaea::poll::<_, ()>(&mut [&mut queue], &mut events, None).unwrap();
+    assert_eq!(events.len(), 2);
+
+    events.clear();
+    gaea::poll::<_, ()>(&mut [&mut queue], &mut events, None).unwrap();
+    assert_eq!(events.as_slice(), &[event]);
+}
+
+#[test]
+fn fan_out_events() {
+    let mut queue = Queue::new();
+    let event1 = Event::new(event::Id(0), Ready::READABLE);
+    let event2 = Event::new(event::Id(1), Ready::WRITABLE);
+    queue.add(event1);
+    queue.add(event2);
+
+    let mut events = (Vec::new(), EventsCapacity(Capacity::Growable, 0));
+    assert_eq!(events.capacity_left(), Capacity::Growable);
+    poll::<_, ()>(&mut [&mut queue], &mut events, None).unwrap();
+    assert_eq!(events.0, vec![event1, event2]);
+    assert_eq!((events.1).1, 2);
+}
+
+#[test]
+fn fan_out_events_capacity() {
+    let mut queue = Queue::new();
+    let event = Event::new(event::Id(0), Ready::READABLE);
+    for _ in 0..3 {
+        queue.add(event);
+    }
+
+    // The smallest capacity should be respected.
+    let mut events = (Vec::new(), EventsCapacity(Capacity::Limited(2), 0));
+    assert_eq!(events.capacity_left(), Capacity::Limited(2));
+    poll::<_, ()>(&mut [&mut queue], &mut events, None).unwrap();
+    assert_eq!(events.0, vec![event, event]);
+    assert_eq!((events.1).1, 2);
+
+    let mut events = (EventsCapacity(Capacity::Limited(1), 0), Vec::new());
+    assert_eq!(events.capacity_left(), Capacity::Limited(1));
+    poll::<_, ()>(&mut [&mut queue], &mut events, None).unwrap();
+    assert_eq!((events.0).1, 1);
+    assert_eq!(events.1, vec![event]);
+}
+
+#[test]
+fn filter_sink() {
+    let mut events = FilterSink::new(Vec::new(), |event: &Event| event.readiness().is_readable());
+    assert_eq!(events.capacity_left(), Capacity::Growable);
+
+    let event1 = Event::new(event::Id(0), Ready::READABLE);
+    let event2 = Event::new(event::Id(1), Ready::WRITABLE);
+    let event3 = Event::new(event::Id(2), Ready::READABLE | Ready::WRITABLE);
+    events.add(event1);
+    events.add(event2);
+    events.extend(vec![event2, event3].into_iter());
+    assert_eq!(events.get_ref(), &vec![event1, event3]);
+
+    events.get_mut().clear();
+    assert_eq!(format!("{:?}", events), "FilterSink { sink: [] }");
+}
+
+#[test]
+fn filter_sink_capacity() {
+    let mut queue = Queue::new();
+    let event1 = Event::new(event::Id(0), Ready::READABLE);
+    let event2 = Event::new(event::Id(1), Ready::WRITABLE);
+    queue.add(event1);
+    queue.add(event2);
+
+    let filter = |event: &Event| event.id() == event::Id(1);
+    let mut events = FilterSink::new(EventsCapacity(Capacity::Limited(1), 0), filter);
+    assert_eq!(events.capacity_left(), Capacity::Limited(1));
+
+    // The first event is dropped by the filter, even though it used up
+    // capacity while polling.
+    poll::<_, ()>(&mut [&mut queue], &mut events, None).unwrap();
+    assert_eq!(events.get_ref().1, 0);
+    poll::<_, ()>(&mut [&mut queue], &mut events, None).unwrap();
+    assert_eq!(events.into_inner().1, 1);
+}
+
+#[test]
+fn limit_sink() {
+    let mut events = Vec::new();
+    let mut limited = LimitSink::new(&mut events, 2);
+    assert_eq!(limited.capacity_left(), Capacity::Limited(2));
+
+    let event1 = Event::new(event::Id(0), Ready::READABLE);
+    let event2 = Event::new(event::Id(1), Ready::WRITABLE);
+    limited.add(event1);
+    assert_eq!(limited.capacity_left(), Capacity::Limited(1));
+    assert_eq!(limited.limit_left(), 1);
+    limited.extend(vec![event2, event1].into_iter());
+    assert_eq!(limited.capacity_left(), Capacity::Limited(0));
+    assert_eq!(limited.limit_left(), 0);
+    assert_eq!(events, vec![event1, event2]);
+
+    // Capacity of the wrapped sink is respected.
+    let mut events = EventsCapacity(Capacity::Limited(1), 0);
+    let limited = LimitSink::new(&mut events, 10);
+    assert_eq!(limited.capacity_left(), Capacity::Limited(1));
+}
+
+#[test]
+fn dyn_sink() {
+    let mut queue = Queue::new();
+    let mut timers = Timers::new();
+    let event1 = Event::new(event::Id(0), Ready::READABLE);
+    let event2 = Event::new(event::Id(1), Ready::TIMER);
+
+    // Boxed trait object.
+    queue.add(event1);
+    timers.add_deadline(event2.id(), Instant::now());
+    let mut events: Box<dyn Sink> = Box::new(EventsCapacity(Capacity::Growable, 0));
+    let n = poll::<_, ()>(&mut [&mut queue, &mut timers], &mut events, None).unwrap();
+    assert_eq!(n, 2);
+
+    // Limited capacity of the trait object is respected.
+    queue.add(event1);
+    queue.add(event1);
+    let mut events: Box<dyn Sink> = Box::new(EventsCapacity(Capacity::Limited(1), 0));
+    assert_eq!(events.capacity_left(), Capacity::Limited(1));
+    let n = poll::<_, ()>(&mut [&mut queue], &mut events, None).unwrap();
+    assert_eq!(n, 1);
+
+    // Trait object reference.
+    timers.add_deadline(event2.id(), Instant::now());
+    let mut events = Vec::new();
+    {
+        let mut event_sink: &mut dyn Sink = &mut events;
+        poll::<_, ()>(&mut [&mut queue, &mut timers], &mut event_sink, None).unwrap();
+    }
+    assert_eq!(events, vec![event1, event2]);
+}
+
+#[test]
+fn sink_reserve() {
+    /// Event sink that records the calls to `reserve`.
+    struct ReserveSink {
+        events: Vec<Event>,
+        reserved: Vec<usize>,
+    }
+
+    impl Sink for ReserveSink {
+        fn capacity_left(&self) -> Capacity {
+            Capacity::Growable
+        }
+
+        fn add(&mut self, event: Event) {
+            self.events.push(event);
+        }
+
+        fn reserve(&mut self, additional: usize) {
+            self.reserved.push(additional);
+        }
+    }
+
+    let mut queue = Queue::new();
+    let event = Event::new(event::Id(0), Ready::READABLE);
+    queue.add(event);
+    queue.add(event);
+    queue.add(event);
+
+    let mut events = ReserveSink { events: Vec::new(), reserved: Vec::new() };
+    let n = poll::<_, ()>(&mut [&mut queue], &mut events, None).unwrap();
+    assert_eq!(n, 3);
+    assert_eq!(events.events, vec![event, event, event]);
+    assert_eq!(events.reserved, vec![3]);
+
+    // Reserving is limited by the capacity of a `LimitSink`.
+    queue.add(event);
+    queue.add(event);
+    let mut events = ReserveSink { events: Vec::new(), reserved: Vec::new() };
+    let n = poll::<_, ()>(&mut [&mut queue], &mut LimitSink::new(&mut events, 1), None).unwrap();
+    assert_eq!(n, 1);
+    LimitSink::new(&mut events, 1).reserve(10);
+    assert_eq!(events.reserved, vec![1, 1]);
+}
+
+#[test]
+fn capped_events() {
+    let mut queue1 = Queue::new();
+    let mut queue2 = Queue::new();
+    let event1 = Event::new(event::Id(0), Ready::READABLE);
+    let event2 = Event::new(event::Id(1), Ready::WRITABLE);
+    queue1.add(event1);
+    queue1.add(event1);
+    queue2.add(event2);
+    queue2.add(event2);
+
+    let mut events = CappedEvents::new(3);
+    assert_eq!(events.capacity_left(), Capacity::Limited(3));
+    let n = poll::<_, ()>(&mut [&mut queue1, &mut queue2], &mut events, None).unwrap();
+    assert_eq!(n, 3);
+    assert_eq!(events.0, vec![event1, event1, event2]);
+    assert_eq!(events.capacity_left(), Capacity::Limited(0));
+
+    // Full, so the remaining event should stay in the queue.
+    let n = poll::<_, ()>(&mut [&mut queue1, &mut queue2], &mut events, None).unwrap();
+    assert_eq!(n, 0);
+    events.add(event1);
+    assert_eq!(events.0.len(), 3);
+
+    events.0.clear();
+    let n = poll::<_, ()>(&mut [&mut queue1, &mut queue2], &mut events, None).unwrap();
+    assert_eq!(n, 1);
+    assert_eq!(events.0, vec![event2]);
+
+    // Maximum lower than the number of events already in the vector.
+    let events = CappedEvents(vec![event1, event1], 1);
+    assert_eq!(events.capacity_left(), Capacity::Limited(0));
+}
+
+#[test]
+fn priority_sink() {
+    let mut queue = Queue::new();
+    let event1 = Event::new(event::Id(0), Ready::READABLE);
+    let event2 = Event::new(event::Id(1), Ready::READABLE | Ready::ERROR);
+    let event3 = Event::new(event::Id(2), Ready::WRITABLE);
+    let event4 = Event::new(event::Id(3), Ready::HUP);
+    queue.add(event1);
+    queue.add(event2);
+    queue.add(event3);
+    queue.add(event4);
+
+    let mut events = PrioritySink::new();
+    assert!(events.is_empty());
+    let n = poll::<_, ()>(&mut [&mut queue], &mut events, None).unwrap();
+    assert_eq!(n, 4);
+    assert_eq!(events.len(), 4);
+
+    let mut drain = events.drain();
+    assert_eq!(drain.len(), 4);
+    assert_eq!(drain.next(), Some(event2));
+    assert_eq!(drain.next(), Some(event4));
+    assert_eq!(drain.next(), Some(event1));
+    assert_eq!(drain.next(), Some(event3));
+    assert_eq!(drain.next(), None);
+    drop(drain);
+    assert!(events.is_empty());
+
+    events.add(event1);
+    events.clear();
+    assert!(events.is_empty());
+}
+
+#[test]
+fn map_id() {
+    let mut queue = MapId::new(Queue::new(), |id: event::Id| event::Id(id.0 + 1000));
+    queue.get_mut().add(Event::new(event::Id(0), Ready::READABLE));
+    queue.get_mut().add(Event::with_data(event::Id(1), Ready::WRITABLE, 123));
+
+    let mut events = Vec::new();
+    let n = poll::<_, ()>(&mut [&mut queue], &mut events, None).unwrap();
+    assert_eq!(n, 2);
+    assert_eq!(events, vec![
+        Event::new(event::Id(1000), Ready::READABLE),
+        Event::with_data(event::Id(1001), Ready::WRITABLE, 123),
+    ]);
+}
+
+#[test]
+fn map_id_capacity() {
+    let mut queue = MapId::new(Queue::new(), |id: event::Id| event::Id(id.0 * 2));
+    queue.get_mut().add(Event::new(event::Id(1), Ready::READABLE));
+    queue.get_mut().add(Event::new(event::Id(2), Ready::READABLE));
+
+    let mut events = (Vec::new(), EventsCapacity(Capacity::Limited(1), 0));
+    poll::<_, ()>(&mut [&mut queue], &mut events, None).unwrap();
+    assert_eq!(events.0, vec![Event::new(event::Id(2), Ready::READABLE)]);
+    poll::<_, ()>(&mut [&mut queue], &mut events, None).unwrap();
+    assert_eq!(events.0, vec![
+        Event::new(event::Id(2), Ready::READABLE),
+        Event::new(event::Id(4), Ready::READABLE),
+    ]);
+}
+
+#[test]
+fn chain() {
+    let mut chain = Chain::new(Queue::new(), Timers::new());
+    let event1 = Event::new(event::Id(0), Ready::READABLE);
+    chain.first_mut().add(event1);
+    chain.second_mut().add_deadline(event::Id(1), Instant::now());
+
+    // The second source is skipped if the first has events.
+    let mut events = Vec::new();
+    assert_eq!(poll::<_, ()>(&mut [&mut chain], &mut events, None), Ok(1));
+    assert_eq!(events, vec![event1]);
+
+    events.clear();
+    assert_eq!(poll::<_, ()>(&mut [&mut chain], &mut events, None), Ok(1));
+    assert_eq!(events, vec![Event::new(event::Id(1), Ready::TIMER)]);
+
+    events.clear();
+    assert_eq!(poll::<_, ()>(&mut [&mut chain], &mut events, Some(Duration::from_millis(0))), Ok(0));
+    assert!(events.is_empty());
+}
+
+#[test]
+fn chain_max_timeout() {
+    let mut chain = Chain::new(Queue::new(), Timers::new());
+    assert_eq!(event::Source::<Vec<Event>, ()>::max_timeout(&chain), None);
+
+    chain.second_mut().add_timeout(event::Id(0), Duration::from_secs(10));
+    let timeout = event::Source::<Vec<Event>, ()>::max_timeout(&chain).unwrap();
+    assert!(timeout <= Duration::from_secs(10));
+
+    chain.first_mut().add(Event::new(event::Id(1), Ready::READABLE));
+    assert_eq!(event::Source::<Vec<Event>, ()>::max_timeout(&chain), Some(Duration::from_millis(0)));
+}
+
+#[test]
+fn event_error() {
+    let event = Event::new(event::Id(0), Ready::ERROR);
+    assert_eq!(event.error(), None);
+
+    let event = Event::with_error(event::Id(1), Ready::ERROR | Ready::HUP, 104);
+    assert_eq!(event.id(), event::Id(1));
+    assert_eq!(event.readiness(), Ready::ERROR | Ready::HUP);
+    assert_eq!(event.data(), 0);
+    assert_eq!(event.error(), Some(104));
+    assert_ne!(event, Event::new(event::Id(1), Ready::ERROR | Ready::HUP));
+
+    // Zero means no error.
+    assert_eq!(Event::with_error(event::Id(0), Ready::ERROR, 0).error(), None);
+}
+
 #[test]
 fn event() {
     let event = Event::new(event::Id(0), Ready::READABLE);
@@ -18,6 +404,21 @@ fn event() {
     assert_eq!(event.readiness(), Ready::READABLE);
 }
 
+#[test]
+fn event_data() {
+    let event = Event::new(event::Id(0), Ready::READABLE);
+    assert_eq!(event.data(), 0);
+
+    let event = Event::with_data(event::Id(1), Ready::WRITABLE, 123);
+    assert_eq!(event.id(), event::Id(1));
+    assert_eq!(event.readiness(), Ready::WRITABLE);
+    assert_eq!(event.data(), 123);
+
+    // Different data.
+    assert_ne!(event, Event::with_data(event::Id(1), Ready::WRITABLE, 1));
+    assert_eq!(Event::new(event::Id(1), Ready::WRITABLE), Event::with_data(event::Id(1), Ready::WRITABLE, 0));
+}
+
 #[test]
 fn event_equality() {
     let event = Event::new(event::Id(0), Ready::WRITABLE);
@@ -50,6 +451,46 @@ fn ready_contains() {
     assert!(!Ready::WRITABLE.contains(Ready::READABLE | Ready::WRITABLE));
 }
 
+#[test]
+fn ready_contains_any() {
+    assert!((Ready::READABLE | Ready::WRITABLE).contains_any(Ready::READABLE));
+    assert!((Ready::READABLE | Ready::WRITABLE).contains_any(Ready::READABLE | Ready::ERROR));
+    assert!(Ready::READABLE.contains_any(Ready::READABLE | Ready::WRITABLE));
+    assert!(!Ready::READABLE.contains_any(Ready::WRITABLE | Ready::ERROR));
+    assert!(!Ready::READABLE.contains_any(Ready::EMPTY));
+    assert!(!Ready::EMPTY.contains_any(Ready::READABLE));
+}
+
+#[test]
+fn ready_remove() {
+    let mut ready = Ready::READABLE | Ready::WRITABLE;
+    ready.remove(Ready::READABLE);
+    assert_eq!(ready, Ready::WRITABLE);
+    // Removing a flag that isn't set does nothing.
+    ready.remove(Ready::ERROR);
+    assert_eq!(ready, Ready::WRITABLE);
+    ready.remove(Ready::WRITABLE | Ready::TIMER);
+    assert_eq!(ready, Ready::EMPTY);
+}
+
+#[test]
+fn ready_difference() {
+    let ready = Ready::READABLE | Ready::WRITABLE | Ready::ERROR;
+    assert_eq!(ready.difference(Ready::READABLE), Ready::WRITABLE | Ready::ERROR);
+    assert_eq!(ready.difference(Ready::TIMER), ready);
+    assert_eq!(ready.difference(ready), Ready::EMPTY);
+    assert_eq!(Ready::EMPTY.difference(ready), Ready::EMPTY);
+}
+
+#[test]
+fn ready_intersection() {
+    let ready = Ready::READABLE | Ready::WRITABLE | Ready::ERROR;
+    assert_eq!(ready.intersection(Ready::READABLE | Ready::TIMER), Ready::READABLE);
+    assert_eq!(ready.intersection(Ready::TIMER), Ready::EMPTY);
+    assert_eq!(ready.intersection(ready), ready);
+    assert_eq!(Ready::EMPTY.intersection(ready), Ready::EMPTY);
+}
+
 #[test]
 fn ready_is_tests() {
     assert!(!Ready::EMPTY.is_readable());
@@ -94,6 +535,21 @@ fn ready_is_tests() {
         assert!(!Ready::HUP.is_error());
         assert!(!Ready::HUP.is_timer());
         assert!(Ready::HUP.is_hup());
+        assert!(!Ready::HUP.is_priority());
+
+        assert!(!Ready::PRIORITY.is_readable());
+        assert!(!Ready::PRIORITY.is_writable());
+        assert!(!Ready::PRIORITY.is_error());
+        assert!(!Ready::PRIORITY.is_timer());
+        assert!(!Ready::PRIORITY.is_hup());
+        assert!(Ready::PRIORITY.is_priority());
+        assert!(!Ready::PRIORITY.is_read_hup());
+
+        assert!(!Ready::READ_HUP.is_readable());
+        assert!(!Ready::READ_HUP.is_writable());
+        assert!(!Ready::READ_HUP.is_hup());
+        assert!(!Ready::READ_HUP.is_priority());
+        assert!(Ready::READ_HUP.is_read_hup());
     }
 }
 
@@ -129,6 +585,10 @@ fn ready_fmt_debug() {
     assert_eq!(format!("{:?}", Ready::TIMER), "TIMER");
     #[cfg(unix)]
     assert_eq!(format!("{:?}", Ready::HUP), "HUP");
+    #[cfg(unix)]
+    assert_eq!(format!("{:?}", Ready::PRIORITY), "PRIORITY");
+    #[cfg(unix)]
+    assert_eq!(format!("{:?}", Ready::READ_HUP), "READ_HUP");
 
     assert_eq!(format!("{:?}", Ready::READABLE | Ready::WRITABLE), "READABLE | WRITABLE");
     assert_eq!(format!("{:?}", Ready::ERROR | Ready::TIMER), "ERROR | TIMER");
@@ -136,6 +596,53 @@ fn ready_fmt_debug() {
         "READABLE | WRITABLE | ERROR | TIMER");
 }
 
+#[test]
+fn ready_iter() {
+    assert_eq!(Ready::EMPTY.iter().next(), None);
+    assert_eq!(Ready::EMPTY.iter().len(), 0);
+
+    let ready = Ready::READABLE | Ready::WRITABLE | Ready::ERROR | Ready::TIMER;
+    let iter = ready.iter();
+    assert_eq!(iter.len(), 4);
+    assert_eq!(iter.collect::<Vec<_>>(),
+        vec![Ready::READABLE, Ready::WRITABLE, Ready::ERROR, Ready::TIMER]);
+
+    #[cfg(unix)]
+    {
+        let ready = Ready::READABLE | Ready::HUP | Ready::PRIORITY | Ready::READ_HUP;
+        let flags: Vec<Ready> = ready.into_iter().collect();
+        assert_eq!(flags, vec![Ready::READABLE, Ready::HUP, Ready::PRIORITY, Ready::READ_HUP]);
+    }
+}
+
+#[test]
+fn ready_fmt_display() {
+    assert_eq!(Ready::EMPTY.to_string(), "(empty)");
+    assert_eq!(Ready::READABLE.to_string(), "READABLE");
+    assert_eq!((Ready::READABLE | Ready::WRITABLE).to_string(), "READABLE | WRITABLE");
+    #[cfg(unix)]
+    assert_eq!((Ready::READABLE | Ready::WRITABLE | Ready::HUP).to_string(), "READABLE | WRITABLE | HUP");
+}
+
+#[test]
+fn event_fmt() {
+    let event = Event::new(event::Id(0), Ready::READABLE);
+    assert_eq!(event.to_string(), "Event { id: 0, readiness: READABLE }");
+    assert_eq!(format!("{:?}", event), "Event { id: Id(0), readiness: READABLE, data: 0, error: None }");
+
+    let event = Event::new(event::Id(3), Ready::READABLE | Ready::WRITABLE);
+    assert_eq!(event.to_string(), "Event { id: 3, readiness: READABLE | WRITABLE }");
+
+    let event = Event::with_data(event::Id(1), Ready::TIMER, 123);
+    assert_eq!(event.to_string(), "Event { id: 1, readiness: TIMER, data: 123 }");
+
+    let event = Event::with_error(event::Id(2), Ready::ERROR, 104);
+    assert_eq!(event.to_string(), "Event { id: 2, readiness: ERROR, error: 104 }");
+
+    let event = Event::new(event::Id(4), Ready::EMPTY);
+    assert_eq!(event.to_string(), "Event { id: 4, readiness: (empty) }");
+}
+
 #[test]
 fn id() {
     let id = event::Id(0);
@@ -156,6 +663,62 @@ fn id() {
     assert_eq!(id, id1);
 }
 
+#[test]
+fn id_map_key() {
+    use std::collections::{BTreeMap, HashSet};
+
+    let mut map = BTreeMap::new();
+    assert!(map.insert(event::Id(2), "c").is_none());
+    assert!(map.insert(event::Id(0), "a").is_none());
+    assert!(map.insert(event::Id(1), "b").is_none());
+    assert_eq!(map.insert(event::Id(1), "B"), Some("b"));
+    let values: Vec<_> = map.into_iter().collect();
+    assert_eq!(values, vec![(event::Id(0), "a"), (event::Id(1), "B"), (event::Id(2), "c")]);
+
+    let mut set = HashSet::new();
+    assert!(set.insert(event::Id(0)));
+    assert!(set.insert(event::Id(1)));
+    assert!(!set.insert(event::Id(0)));
+    assert!(set.contains(&event::Id(1)));
+    assert!(!set.contains(&event::Id(2)));
+
+    assert!(event::Id(0) < event::Id(1));
+    assert_eq!(event::Id(1).max(event::Id(5)), event::Id(5));
+}
+
+#[test]
+fn id_generator() {
+    let mut ids = IdGenerator::new();
+    assert_eq!(ids.next(), event::Id(0));
+    assert_eq!(ids.next(), event::Id(1));
+    assert_eq!(ids.next(), event::Id(2));
+
+    ids.free(event::Id(1));
+    ids.free(event::Id(0));
+    assert_eq!(ids.next(), event::Id(0));
+    assert_eq!(ids.next(), event::Id(1));
+    assert_eq!(ids.next(), event::Id(3));
+
+    // Never handed out.
+    ids.free(event::Id(100));
+    assert_eq!(ids.next(), event::Id(4));
+}
+
+#[test]
+fn id_generator_reserve() {
+    let mut ids = IdGenerator::reserve(10);
+    assert_eq!(ids.next(), event::Id(10));
+    assert_eq!(ids.next(), event::Id(11));
+
+    // Reserved ids are never handed out.
+    ids.free(event::Id(0));
+    ids.free(event::Id(9));
+    assert_eq!(ids.next(), event::Id(12));
+
+    ids.free(event::Id(10));
+    assert_eq!(ids.next(), event::Id(10));
+}
+
 #[test]
 fn id_fmt() {
     assert_eq!(format!("{:?}", event::Id(0)), "Id(0)");
@@ -164,3 +727,69 @@ fn id_fmt() {
     assert_eq!(format!("{}", event::Id(0)), "0");
     assert_eq!(format!("{}", event::Id(999)), "999");
 }
+
+#[test]
+#[cfg(feature = "serde")]
+fn serde_id() {
+    use serde_test::{assert_tokens, Token};
+
+    assert_tokens(&event::Id(0), &[Token::NewtypeStruct { name: "Id" }, Token::U64(0)]);
+    assert_tokens(&event::Id(100), &[Token::NewtypeStruct { name: "Id" }, Token::U64(100)]);
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn serde_ready() {
+    use serde_test::{assert_tokens, Token};
+
+    let tests = &[
+        (Ready::EMPTY, 0),
+        (Ready::READABLE, 1),
+        (Ready::WRITABLE, 2),
+        (Ready::ERROR, 4),
+        (Ready::TIMER, 8),
+        (Ready::READABLE | Ready::WRITABLE, 3),
+    ];
+    for &(ready, value) in tests {
+        assert_tokens(&ready, &[Token::NewtypeStruct { name: "Ready" }, Token::U8(value)]);
+    }
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn serde_event() {
+    use serde_test::{assert_tokens, Token};
+
+    let event = Event::with_data(event::Id(123), Ready::READABLE | Ready::WRITABLE, 456);
+    assert_tokens(&event, &[
+        Token::Struct { name: "Event", len: 4 },
+        Token::Str("id"),
+        Token::NewtypeStruct { name: "Id" },
+        Token::U64(123),
+        Token::Str("readiness"),
+        Token::NewtypeStruct { name: "Ready" },
+        Token::U8(3),
+        Token::Str("data"),
+        Token::U64(456),
+        Token::Str("error"),
+        Token::None,
+        Token::StructEnd,
+    ]);
+
+    let event = Event::with_error(event::Id(123), Ready::ERROR, libc::ECONNRESET);
+    assert_tokens(&event, &[
+        Token::Struct { name: "Event", len: 4 },
+        Token::Str("id"),
+        Token::NewtypeStruct { name: "Id" },
+        Token::U64(123),
+        Token::Str("readiness"),
+        Token::NewtypeStruct { name: "Ready" },
+        Token::U8(4),
+        Token::Str("data"),
+        Token::U64(0),
+        Token::Str("error"),
+        Token::Some,
+        Token::I32(libc::ECONNRESET),
+        Token::StructEnd,
+    ]);
+}
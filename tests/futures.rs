@@ -0,0 +1,177 @@
+#![cfg(feature = "futures")]
+
+use std::future::Future;
+use std::io::{Read, Write};
+use std::mem::ManuallyDrop;
+use std::net;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+use std::thread::{self, Thread};
+use std::time::Duration;
+
+use futures_core::Stream;
+use futures_io::{AsyncRead, AsyncWrite};
+use gaea::net::{AsyncDriver, AsyncTcpStream, IncomingStream, TcpListener};
+
+mod util;
+
+use self::util::{any_local_address, init};
+
+const DATA: &[u8] = b"Hello world!";
+
+#[test]
+fn incoming_stream() {
+    init();
+
+    let mut driver = AsyncDriver::new().unwrap();
+    let driver_handle = driver.handle();
+    let stop = Arc::new(AtomicBool::new(false));
+    let driver_stop = Arc::clone(&stop);
+    let driver_thread = thread::spawn(move || {
+        while !driver_stop.load(Ordering::Acquire) {
+            driver.poll(Some(Duration::from_millis(10))).unwrap();
+        }
+    });
+
+    let mut listener = TcpListener::bind(any_local_address()).unwrap();
+    let address = listener.local_addr().unwrap();
+    let mut incoming = IncomingStream::new(listener, &driver_handle).unwrap();
+
+    // No connections yet.
+    let waker = thread_waker();
+    let mut ctx = Context::from_waker(&waker);
+    assert!(Pin::new(&mut incoming).poll_next(&mut ctx).is_pending());
+
+    let handle = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        let mut stream = net::TcpStream::connect(address).unwrap();
+        stream.write_all(DATA).unwrap();
+        let mut buf = [0; 20];
+        let n = stream.read(&mut buf).unwrap();
+        assert_eq!(&buf[..n], DATA);
+    });
+
+    let (stream, _) = block_on(PollFn(|ctx: &mut Context<'_>| Pin::new(&mut incoming).poll_next(ctx)))
+        .expect("stream ended")
+        .expect("unable to accept connection");
+    let mut stream = AsyncTcpStream::new(stream, &driver_handle).unwrap();
+
+    let mut buf = [0; 20];
+    let n = block_on(PollFn(|ctx: &mut Context<'_>| Pin::new(&mut stream).poll_read(ctx, &mut buf)))
+        .expect("unable to read");
+    assert_eq!(&buf[..n], DATA);
+
+    let n = block_on(PollFn(|ctx: &mut Context<'_>| Pin::new(&mut stream).poll_write(ctx, DATA)))
+        .expect("unable to write");
+    assert_eq!(n, DATA.len());
+
+    handle.join().unwrap();
+
+    // Peer closed the connection.
+    let n = block_on(PollFn(|ctx: &mut Context<'_>| Pin::new(&mut stream).poll_read(ctx, &mut buf)))
+        .expect("unable to read");
+    assert_eq!(n, 0);
+
+    stop.store(true, Ordering::Release);
+    driver_thread.join().unwrap();
+}
+
+#[test]
+fn async_driver_shared() {
+    init();
+
+    // Multiple handles are driven by a single driver.
+    let mut driver = AsyncDriver::new().unwrap();
+    let driver_handle = driver.handle();
+
+    let mut listener = TcpListener::bind(any_local_address()).unwrap();
+    let address = listener.local_addr().unwrap();
+    let mut incoming = IncomingStream::new(listener, &driver_handle).unwrap();
+
+    let mut peers = Vec::new();
+    let mut streams = Vec::new();
+    for _ in 0..3 {
+        peers.push(net::TcpStream::connect(address).unwrap());
+        let stream = loop {
+            let waker = thread_waker();
+            let mut ctx = Context::from_waker(&waker);
+            match Pin::new(&mut incoming).poll_next(&mut ctx) {
+                Poll::Ready(Some(Ok((stream, _)))) => break stream,
+                Poll::Ready(result) => panic!("unexpected result: {:?}", result),
+                Poll::Pending => driver.poll(None).unwrap(),
+            }
+        };
+        streams.push(AsyncTcpStream::new(stream, &driver_handle).unwrap());
+    }
+
+    let waker = thread_waker();
+    let mut ctx = Context::from_waker(&waker);
+    let mut buf = [0; 20];
+    for stream in streams.iter_mut() {
+        assert!(Pin::new(stream).poll_read(&mut ctx, &mut buf).is_pending());
+    }
+
+    peers[1].write_all(DATA).unwrap();
+    driver.poll(None).unwrap();
+    let n = match Pin::new(&mut streams[1]).poll_read(&mut ctx, &mut buf) {
+        Poll::Ready(result) => result.unwrap(),
+        Poll::Pending => panic!("stream not ready after driver poll"),
+    };
+    assert_eq!(&buf[..n], DATA);
+    assert!(Pin::new(&mut streams[0]).poll_read(&mut ctx, &mut buf).is_pending());
+    assert!(Pin::new(&mut streams[2]).poll_read(&mut ctx, &mut buf).is_pending());
+}
+
+/// Future calling the function until it returns `Poll::Ready`.
+struct PollFn<F>(F);
+
+impl<F, T> Future for PollFn<F>
+    where F: FnMut(&mut Context<'_>) -> Poll<T> + Unpin,
+{
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<T> {
+        (self.get_mut().0)(ctx)
+    }
+}
+
+/// Run `future` to completion on the current thread.
+fn block_on<F>(mut future: F) -> F::Output
+    where F: Future + Unpin,
+{
+    let waker = thread_waker();
+    let mut ctx = Context::from_waker(&waker);
+    loop {
+        match Pin::new(&mut future).poll(&mut ctx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+/// Returns a waker that unparks the current thread.
+fn thread_waker() -> Waker {
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, wake, wake_by_ref, drop);
+
+    unsafe fn clone(data: *const ()) -> RawWaker {
+        let thread = ManuallyDrop::new(Arc::from_raw(data as *const Thread));
+        RawWaker::new(Arc::into_raw(Arc::clone(&thread)) as *const (), &VTABLE)
+    }
+
+    unsafe fn wake(data: *const ()) {
+        Arc::from_raw(data as *const Thread).unpark();
+    }
+
+    unsafe fn wake_by_ref(data: *const ()) {
+        (*(data as *const Thread)).unpark();
+    }
+
+    unsafe fn drop(data: *const ()) {
+        let _ = Arc::from_raw(data as *const Thread);
+    }
+
+    let thread = Arc::new(thread::current());
+    unsafe { Waker::from_raw(RawWaker::new(Arc::into_raw(thread) as *const (), &VTABLE)) }
+}
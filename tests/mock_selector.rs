@@ -0,0 +1,118 @@
+//! Tests for the mock selector, run using:
+//! `cargo test --features mock_selector`.
+
+#![cfg(feature = "mock_selector")]
+
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::time::{Duration, Instant};
+
+use gaea::event::{self, Event, Ready};
+use gaea::os::{Awakener, Interests, OsQueue, RegisterOption};
+use gaea::poll;
+use gaea::unix::new_pipe;
+
+mod util;
+
+use self::util::{expect_events, init, init_with_os_queue};
+
+const ID1: event::Id = event::Id(0);
+const ID2: event::Id = event::Id(1);
+
+#[test]
+fn mock_registrations() {
+    init();
+    let mut os_queue = OsQueue::new_mock().unwrap();
+    assert!(os_queue.is_mock());
+    let (mut sender, mut receiver) = new_pipe().unwrap();
+
+    assert_eq!(os_queue.mock_registration(receiver.as_raw_fd()), None);
+    os_queue.register(&mut receiver, ID1, Interests::READABLE, RegisterOption::EDGE).unwrap();
+    os_queue.register(&mut sender, ID2, Interests::WRITABLE, RegisterOption::ONESHOT).unwrap();
+    assert_eq!(os_queue.mock_registration(receiver.as_raw_fd()),
+        Some((ID1, Interests::READABLE, RegisterOption::EDGE)));
+    assert_eq!(os_queue.mock_registration(sender.as_raw_fd()),
+        Some((ID2, Interests::WRITABLE, RegisterOption::ONESHOT)));
+
+    os_queue.reregister(&mut receiver, ID2, Interests::READABLE, RegisterOption::LEVEL).unwrap();
+    assert_eq!(os_queue.mock_registration(receiver.as_raw_fd()),
+        Some((ID2, Interests::READABLE, RegisterOption::LEVEL)));
+
+    os_queue.deregister(&mut receiver).unwrap();
+    assert_eq!(os_queue.mock_registration(receiver.as_raw_fd()), None);
+    assert_eq!(os_queue.deregister(&mut receiver).unwrap_err().kind(), io::ErrorKind::NotFound);
+
+    // Cloned queues share the registrations.
+    let os_queue2 = os_queue.try_clone().unwrap();
+    assert_eq!(os_queue2.mock_registration(sender.as_raw_fd()),
+        Some((ID2, Interests::WRITABLE, RegisterOption::ONESHOT)));
+}
+
+#[test]
+fn mock_events() {
+    init();
+    let mut os_queue = OsQueue::new_mock().unwrap();
+    let mut events = Vec::new();
+
+    // No events, polling doesn't block.
+    let start = Instant::now();
+    poll::<_, io::Error>(&mut [&mut os_queue], &mut events, None).unwrap();
+    assert!(events.is_empty());
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    os_queue.mock_event(Event::new(ID1, Ready::READABLE));
+    os_queue.mock_event(Event::new(ID2, Ready::WRITABLE | Ready::HUP));
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(ID1, Ready::READABLE),
+        Event::new(ID2, Ready::WRITABLE | Ready::HUP),
+    ]);
+
+    // Events are only returned once.
+    poll::<_, io::Error>(&mut [&mut os_queue], &mut events, None).unwrap();
+    assert!(events.is_empty());
+}
+
+#[test]
+fn mock_awakener() {
+    init();
+    let mut os_queue = OsQueue::new_mock().unwrap();
+    let mut events = Vec::new();
+
+    let awakener = Awakener::new(&mut os_queue, ID1).unwrap();
+    awakener.wake().unwrap();
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(ID1, Ready::READABLE),
+    ]);
+
+    // Clones wake the same id, with the readiness combined.
+    let awakener2 = awakener.try_clone().unwrap();
+    awakener2.wake_with(Ready::HUP).unwrap();
+    awakener.wake_with(Ready::ERROR).unwrap();
+    poll::<_, io::Error>(&mut [&mut os_queue], &mut events, None).unwrap();
+    assert!(events.iter().all(|event| event.id() == ID1));
+    assert!(events[0].readiness().contains(Ready::HUP | Ready::ERROR));
+
+    // Waking doesn't register anything with the selector.
+    assert!(os_queue.is_empty());
+}
+
+#[test]
+fn mock_os_queue_not_affected() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+    assert!(!os_queue.is_mock());
+
+    // Uses the OS selector.
+    let (mut sender, _receiver) = new_pipe().unwrap();
+    os_queue.register(&mut sender, ID1, Interests::WRITABLE, RegisterOption::EDGE).unwrap();
+    assert_eq!(os_queue.mock_registration(sender.as_raw_fd()), None);
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(ID1, Ready::WRITABLE),
+    ]);
+}
+
+#[test]
+#[should_panic(expected = "not backed by a mock selector")]
+fn mock_event_os_queue() {
+    let (os_queue, _) = init_with_os_queue();
+    os_queue.mock_event(Event::new(ID1, Ready::READABLE));
+}
@@ -1,4 +1,4 @@
-use std::io::{self, Write};
+use std::io::{self, Read, Write};
 use std::sync::{Arc, Barrier};
 use std::thread;
 use std::time::{Duration, Instant};
@@ -9,7 +9,7 @@ use gaea::unix::new_pipe;
 
 mod util;
 
-use self::util::{assert_error, max_timeout, expect_no_events, expect_events, init, init_with_os_queue, EventsCapacity, TIMEOUT_MARGIN};
+use self::util::{assert_cloexec, assert_error, assert_would_block, max_timeout, expect_no_events, expect_events, init, init_with_os_queue, EventsCapacity, TIMEOUT_MARGIN};
 
 struct TestEvented {
     registrations: Vec<(event::Id, Interests, RegisterOption)>,
@@ -250,6 +250,171 @@ fn awakener_try_clone() {
     expect_no_events(&mut os_queue);
 }
 
+#[test]
+fn awakener_interrupts_blocking_poll() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    // A registered handle that is never ready.
+    let (_sender, mut receiver) = new_pipe().unwrap();
+    os_queue.register(&mut receiver, event::Id(0), Interests::READABLE, RegisterOption::LEVEL).unwrap();
+
+    let event_id = event::Id(1);
+    let awakener = Awakener::new(&mut os_queue, event_id).unwrap();
+
+    const DELAY: Duration = Duration::from_millis(50);
+    for _ in 0..3 {
+        let awakener = awakener.try_clone().unwrap();
+        let handle = thread::spawn(move || {
+            thread::sleep(DELAY);
+            awakener.wake().expect("unable to wake");
+        });
+
+        // Should block until the awakener is woken.
+        let start = Instant::now();
+        events.clear();
+        let n = gaea::poll::<_, io::Error>(&mut [&mut os_queue], &mut events, None).unwrap();
+        assert!(start.elapsed() >= DELAY);
+        assert_eq!(n, 1);
+        assert_eq!(events, vec![Event::new(event_id, Ready::READABLE)]);
+
+        handle.join().unwrap();
+    }
+}
+
+#[test]
+fn os_queue_zero_and_max_timeout() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+    let (mut sender, _receiver) = new_pipe().unwrap();
+
+    // A zero timeout shouldn't block, even if no events are ready.
+    let start = Instant::now();
+    let n = gaea::poll::<_, io::Error>(&mut [&mut os_queue], &mut events, Some(Duration::from_millis(0))).unwrap();
+    assert_eq!(n, 0);
+    assert!(start.elapsed() < TIMEOUT_MARGIN);
+
+    // With a zero timeout events that are ready should be returned.
+    let id = event::Id(0);
+    os_queue.register(&mut sender, id, Interests::WRITABLE, RegisterOption::LEVEL).unwrap();
+    let n = gaea::poll::<_, io::Error>(&mut [&mut os_queue], &mut events, Some(Duration::from_millis(0))).unwrap();
+    assert_eq!(n, 1);
+    assert_eq!(events, vec![Event::new(id, Ready::WRITABLE)]);
+
+    // The largest possible timeout should saturate and return the ready
+    // events, rather than returning an error.
+    events.clear();
+    #[allow(clippy::legacy_numeric_constants)] // `MAX` requires Rust 1.43.
+    let timeout = Duration::new(u64::max_value(), 999_999_999);
+    let n = gaea::poll::<_, io::Error>(&mut [&mut os_queue], &mut events, Some(timeout)).unwrap();
+    assert_eq!(n, 1);
+    assert_eq!(events, vec![Event::new(id, Ready::WRITABLE)]);
+}
+
+#[test]
+fn awakener_drain() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    let event_id = event::Id(10);
+    let awakener = Awakener::new(&mut os_queue, event_id)
+        .expect("unable to create awakener");
+
+    // Many wakes should collapse into a single event.
+    for _ in 0..1000 {
+        awakener.wake().expect("unable to wake");
+    }
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(event_id, Ready::READABLE),
+    ]);
+
+    // After which polling should block again.
+    let timeout = Duration::from_millis(50);
+    let start = Instant::now();
+    events.clear();
+    event::Source::<_, io::Error>::blocking_poll(&mut os_queue, &mut events, Some(timeout)).unwrap();
+    assert!(events.is_empty(), "unexpected events: {:?}", events);
+    assert!(start.elapsed() >= timeout);
+
+    // And waking should still work.
+    awakener.wake().expect("unable to wake");
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(event_id, Ready::READABLE),
+    ]);
+}
+
+#[test]
+fn awakener_wake_with() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    let event_id = event::Id(10);
+    let awakener = Awakener::new(&mut os_queue, event_id)
+        .expect("unable to create awakener");
+
+    awakener.wake_with(Ready::WRITABLE).expect("unable to wake");
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(event_id, Ready::WRITABLE),
+    ]);
+
+    // Readiness of multiple wakes should be combined.
+    awakener.wake_with(Ready::WRITABLE).expect("unable to wake");
+    awakener.wake_with(Ready::HUP).expect("unable to wake");
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(event_id, Ready::WRITABLE | Ready::HUP),
+    ]);
+
+    // Waking from another thread, using a clone.
+    let awakener1 = awakener.try_clone()
+        .expect("unable to clone awakener");
+    let handle = thread::spawn(move || {
+        awakener1.wake_with(Ready::ERROR).expect("unable to wake");
+    });
+    handle.join().unwrap();
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(event_id, Ready::ERROR),
+    ]);
+
+    // Regular wake.
+    awakener.wake().expect("unable to wake");
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(event_id, Ready::READABLE),
+    ]);
+    expect_no_events(&mut os_queue);
+}
+
+#[test]
+fn multiple_awakeners() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    let id1 = event::Id(10);
+    let id2 = event::Id(11);
+    let awakener1 = Awakener::new(&mut os_queue, id1)
+        .expect("unable to create awakener");
+    let awakener2 = Awakener::new(&mut os_queue, id2)
+        .expect("unable to create awakener");
+
+    awakener1.wake().expect("unable to wake");
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(id1, Ready::READABLE),
+    ]);
+    expect_no_events(&mut os_queue);
+
+    awakener2.wake_with(Ready::HUP).expect("unable to wake");
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(id2, Ready::HUP),
+    ]);
+    expect_no_events(&mut os_queue);
+
+    // Waking both.
+    let handle = thread::spawn(move || {
+        awakener2.wake().expect("unable to wake");
+    });
+    awakener1.wake_with(Ready::WRITABLE).expect("unable to wake");
+    handle.join().unwrap();
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(id1, Ready::WRITABLE),
+        Event::new(id2, Ready::READABLE),
+    ]);
+    expect_no_events(&mut os_queue);
+}
+
 #[test]
 fn awakener_multiple_wakeups() {
     let (mut os_queue, mut events) = init_with_os_queue();
@@ -291,3 +456,1102 @@ fn awakener_multiple_wakeups() {
     handle1.join().unwrap();
     handle2.join().unwrap();
 }
+
+#[test]
+#[cfg(any(target_os = "freebsd", target_os = "macos",
+          target_os = "netbsd", target_os = "openbsd"))]
+fn timer() {
+    use gaea::os::Timer;
+
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    let timeout = Duration::from_millis(20);
+
+    // Oneshot timer.
+    let _timer = Timer::new(&mut os_queue, event::Id(0), timeout)
+        .expect("unable to create timer");
+    thread::sleep(timeout);
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(event::Id(0), Ready::TIMER),
+    ]);
+    expect_no_events(&mut os_queue);
+
+    // Periodic timer.
+    let timer = Timer::periodic(&mut os_queue, event::Id(1), timeout)
+        .expect("unable to create timer");
+    for _ in 0..2 {
+        thread::sleep(timeout);
+        expect_events(&mut os_queue, &mut events, vec![
+            Event::new(event::Id(1), Ready::TIMER),
+        ]);
+    }
+
+    // After cancelling no more events should be triggered.
+    timer.cancel(&mut os_queue).expect("unable to cancel timer");
+    thread::sleep(timeout);
+    expect_no_events(&mut os_queue);
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn exclusive_register_option() {
+    use std::os::unix::io::AsRawFd;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use gaea::unix::EventedFd;
+
+    init();
+    let (mut sender, receiver) = new_pipe().unwrap();
+    let fd = receiver.as_raw_fd();
+
+    // Exclusive can't be combined with oneshot.
+    let mut os_queue = OsQueue::new().unwrap();
+    let opt = RegisterOption::EXCLUSIVE | RegisterOption::ONESHOT;
+    assert_error(os_queue.register(&mut EventedFd(&fd), event::Id(0), Interests::READABLE, opt),
+        "can't combine exclusive and oneshot register options");
+
+    // Nor with priority interest.
+    let opt = RegisterOption::EXCLUSIVE;
+    assert_error(os_queue.register(&mut EventedFd(&fd), event::Id(0), Interests::READABLE | Interests::PRIORITY, opt),
+        "can't combine exclusive register option and priority interest");
+
+    // Two `OsQueue`s, on different threads, sharing the same handle. Only one
+    // of them should be woken.
+    let woken = Arc::new(AtomicUsize::new(0));
+    let barrier = Arc::new(Barrier::new(3));
+    let handles: Vec<_> = (0..2).map(|_| {
+        let mut os_queue = OsQueue::new().unwrap();
+        let opt = RegisterOption::EDGE | RegisterOption::EXCLUSIVE;
+        os_queue.register(&mut EventedFd(&fd), event::Id(0), Interests::READABLE, opt)
+            .expect("unable to register with exclusive option");
+        let woken = woken.clone();
+        let barrier = barrier.clone();
+        thread::spawn(move || {
+            let mut events = Vec::new();
+            barrier.wait();
+            gaea::poll::<_, io::Error>(&mut [&mut os_queue], &mut events,
+                Some(Duration::from_millis(500))).unwrap();
+            if !events.is_empty() {
+                let _ = woken.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+    }).collect();
+
+    // Give both threads a chance to start polling.
+    barrier.wait();
+    thread::sleep(Duration::from_millis(100));
+    let n = sender.write(b"Hello").unwrap();
+    assert_eq!(n, 5);
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    // The kernel only guarantees that "one or more" of the `OsQueue`s are
+    // woken, so we can't check that only one was woken.
+    assert!(woken.load(Ordering::SeqCst) >= 1);
+    drop(receiver);
+}
+
+#[test]
+fn read_hup() {
+    use std::net::Shutdown;
+    use std::os::unix::io::AsRawFd;
+    use std::os::unix::net::UnixStream;
+
+    use gaea::unix::EventedFd;
+
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    let (stream1, stream2) = UnixStream::pair().unwrap();
+    stream1.set_nonblocking(true).unwrap();
+    os_queue.register(&mut EventedFd(&stream1.as_raw_fd()), event::Id(0),
+        Interests::READABLE | Interests::WRITABLE, RegisterOption::EDGE).unwrap();
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(event::Id(0), Ready::WRITABLE),
+    ]);
+
+    // Peer closes its writing side, we should still be able to write.
+    stream2.shutdown(Shutdown::Write).unwrap();
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(event::Id(0), Ready::READ_HUP),
+    ]);
+    (&stream1).write_all(b"Hello").unwrap();
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn os_queue_signal_mask() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::{mem, ptr};
+
+    use gaea::os::SignalSet;
+
+    static HANDLED: AtomicBool = AtomicBool::new(false);
+
+    extern "C" fn handler(_: libc::c_int) {
+        HANDLED.store(true, Ordering::SeqCst);
+    }
+
+    /// Restores the signal mask of the thread and the signal action when
+    /// dropped.
+    struct Restore(libc::sigset_t, libc::sigaction);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let _ = unsafe { libc::pthread_sigmask(libc::SIG_SETMASK, &self.0, ptr::null_mut()) };
+            let _ = unsafe { libc::sigaction(libc::SIGQUIT, &self.1, ptr::null_mut()) };
+        }
+    }
+
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    os_queue.set_signal_mask(SignalSet::all()).expect("unable to set signal mask");
+    let awakener = Awakener::new(&mut os_queue, event::Id(0)).unwrap();
+    awakener.wake().unwrap();
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(event::Id(0), Ready::READABLE),
+    ]);
+    expect_no_events(&mut os_queue);
+
+    // Block the signal on this thread, the signal mask of the `OsQueue` must
+    // keep it blocked while polling. The handler records if the signal was
+    // delivered (interrupting the poll) anyway.
+    let mut set: libc::sigset_t = unsafe { mem::zeroed() };
+    let _restore = unsafe {
+        assert_eq!(libc::sigemptyset(&mut set), 0);
+        assert_eq!(libc::sigaddset(&mut set, libc::SIGQUIT), 0);
+        let mut action: libc::sigaction = mem::zeroed();
+        action.sa_sigaction = handler as *const () as libc::sighandler_t;
+        let mut old_action: libc::sigaction = mem::zeroed();
+        assert_eq!(libc::sigaction(libc::SIGQUIT, &action, &mut old_action), 0);
+        let mut old_set: libc::sigset_t = mem::zeroed();
+        assert_eq!(libc::pthread_sigmask(libc::SIG_BLOCK, &set, &mut old_set), 0);
+        Restore(old_set, old_action)
+    };
+
+    let thread = unsafe { libc::pthread_self() } as usize;
+    let handle = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(unsafe { libc::pthread_kill(thread as libc::pthread_t, libc::SIGQUIT) }, 0);
+    });
+
+    // The poll must not be interrupted, nor return early.
+    let timeout = Duration::from_millis(50);
+    let start = Instant::now();
+    event::Source::<_, io::Error>::blocking_poll(&mut os_queue, &mut events, Some(timeout))
+        .expect("unable to poll");
+    let duration = start.elapsed();
+    handle.join().unwrap();
+    assert!(events.is_empty());
+    assert!(!HANDLED.load(Ordering::SeqCst), "signal delivered while polling");
+    #[cfg(not(feature="disable_test_deadline"))]
+    assert!(duration >= timeout && duration <= timeout + TIMEOUT_MARGIN,
+        "blocking time incorrect: {:?}, wanted: >= {:?} and <= {:?}.", duration, timeout, timeout + TIMEOUT_MARGIN);
+
+    // The signal must still be pending, accept it so it's not delivered once
+    // the signal mask is restored.
+    let mut pending: libc::sigset_t = unsafe { mem::zeroed() };
+    assert_eq!(unsafe { libc::sigpending(&mut pending) }, 0);
+    assert_eq!(unsafe { libc::sigismember(&pending, libc::SIGQUIT) }, 1, "signal not pending");
+    let no_wait = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    assert_eq!(unsafe { libc::sigtimedwait(&set, ptr::null_mut(), &no_wait) }, libc::SIGQUIT);
+}
+
+#[test]
+fn os_queue_as_raw_fd() {
+    use std::os::unix::io::AsRawFd;
+
+    use gaea::unix::EventedFd;
+
+    let (mut os_queue, mut events) = init_with_os_queue();
+    let mut inner_os_queue = OsQueue::new().expect("unable to create OsQueue");
+    assert_ne!(os_queue.as_raw_fd(), inner_os_queue.as_raw_fd());
+
+    let inner_fd = inner_os_queue.as_raw_fd();
+    os_queue.register(&mut EventedFd(&inner_fd), event::Id(0), Interests::READABLE, RegisterOption::LEVEL)
+        .expect("unable to register inner OsQueue");
+    expect_no_events(&mut os_queue);
+
+    // Once the inner queue has events ready, the outer queue should report it
+    // as readable.
+    let awakener = Awakener::new(&mut inner_os_queue, event::Id(1)).unwrap();
+    awakener.wake().unwrap();
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(event::Id(0), Ready::READABLE),
+    ]);
+    expect_events(&mut inner_os_queue, &mut events, vec![
+        Event::new(event::Id(1), Ready::READABLE),
+    ]);
+}
+
+#[test]
+fn os_queue_try_clone() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+    let mut os_queue2 = os_queue.try_clone().expect("unable to clone OsQueue");
+
+    // Registering with the clone should be visible to the original.
+    let (mut sender, mut receiver) = new_pipe().expect("unable to create pipe");
+    os_queue2.register(&mut receiver, event::Id(0), Interests::READABLE, RegisterOption::EDGE)
+        .expect("unable to register receiver");
+    sender.write_all(b"Hello").unwrap();
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(event::Id(0), Ready::READABLE),
+    ]);
+
+    // And the other way around.
+    let awakener = Awakener::new(&mut os_queue, event::Id(1)).unwrap();
+    awakener.wake().unwrap();
+    expect_events(&mut os_queue2, &mut events, vec![
+        Event::new(event::Id(1), Ready::READABLE),
+    ]);
+
+    // Dropping the clone shouldn't affect the original.
+    drop(os_queue2);
+    sender.write_all(b"World").unwrap();
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(event::Id(0), Ready::READABLE),
+    ]);
+}
+
+#[test]
+fn os_queue_try_clone_registrations() {
+    let (mut os_queue, _) = init_with_os_queue();
+    let mut os_queue2 = os_queue.try_clone().unwrap();
+    let (mut sender, mut receiver) = new_pipe().unwrap();
+
+    // Registrations are shared between the clones.
+    os_queue.register(&mut sender, event::Id(0), Interests::WRITABLE, RegisterOption::EDGE).unwrap();
+    os_queue2.register(&mut receiver, event::Id(1), Interests::READABLE, RegisterOption::EDGE).unwrap();
+    for os_queue in &[&os_queue, &os_queue2] {
+        assert_eq!(os_queue.len(), 2);
+        assert_eq!(os_queue.interests(event::Id(0)), Some(Interests::WRITABLE));
+        assert_eq!(os_queue.interests(event::Id(1)), Some(Interests::READABLE));
+    }
+
+    // Including deregistering.
+    os_queue2.deregister(&mut sender).unwrap();
+    assert_eq!(os_queue.len(), 1);
+    assert_eq!(os_queue.interests(event::Id(0)), None);
+
+    // And clearing.
+    os_queue.clear().unwrap();
+    assert!(os_queue2.is_empty());
+    assert_eq!(os_queue2.interests(event::Id(1)), None);
+}
+
+#[test]
+fn os_queue_interests() {
+    let (mut os_queue, _) = init_with_os_queue();
+    let (mut sender, mut receiver) = new_pipe().unwrap();
+
+    let id = event::Id(0);
+    assert_eq!(os_queue.interests(id), None);
+
+    os_queue.register(&mut sender, id, Interests::WRITABLE, RegisterOption::EDGE).unwrap();
+    assert_eq!(os_queue.interests(id), Some(Interests::WRITABLE));
+
+    os_queue.register(&mut receiver, event::Id(1), Interests::READABLE, RegisterOption::LEVEL).unwrap();
+    assert_eq!(os_queue.interests(event::Id(1)), Some(Interests::READABLE));
+
+    // Reregistering with a different id should move the interests.
+    os_queue.reregister(&mut receiver, event::Id(2), Interests::READABLE, RegisterOption::EDGE).unwrap();
+    assert_eq!(os_queue.interests(event::Id(1)), None);
+    assert_eq!(os_queue.interests(event::Id(2)), Some(Interests::READABLE));
+
+    os_queue.deregister(&mut sender).unwrap();
+    assert_eq!(os_queue.interests(id), None);
+    os_queue.deregister(&mut receiver).unwrap();
+    assert_eq!(os_queue.interests(event::Id(2)), None);
+}
+
+#[test]
+fn os_queue_interests_same_id() {
+    use std::os::unix::io::AsRawFd;
+
+    use gaea::unix::EventedFd;
+
+    let (mut os_queue, _) = init_with_os_queue();
+    let (mut sender, mut receiver) = new_pipe().unwrap();
+
+    let id = event::Id(0);
+    os_queue.register(&mut sender, id, Interests::WRITABLE, RegisterOption::EDGE).unwrap();
+    os_queue.register(&mut receiver, id, Interests::READABLE, RegisterOption::EDGE).unwrap();
+    assert_eq!(os_queue.len(), 2);
+    assert_eq!(os_queue.interests(id), Some(Interests::READABLE | Interests::WRITABLE));
+
+    // Can't determine which registration to use.
+    let fd = receiver.as_raw_fd();
+    assert_error(os_queue.add_interest(&mut EventedFd(&fd), id, Interests::WRITABLE),
+        "multiple handles registered with id");
+
+    // Deregistering one handle shouldn't affect the other.
+    os_queue.deregister(&mut sender).unwrap();
+    assert_eq!(os_queue.len(), 1);
+    assert_eq!(os_queue.interests(id), Some(Interests::READABLE));
+    os_queue.deregister(&mut receiver).unwrap();
+    assert_eq!(os_queue.interests(id), None);
+}
+
+#[test]
+fn os_queue_add_remove_interest() {
+    use std::os::unix::io::AsRawFd;
+    use std::os::unix::net::UnixStream;
+
+    use gaea::unix::EventedFd;
+
+    let (mut os_queue, mut events) = init_with_os_queue();
+    let (stream1, stream2) = UnixStream::pair().unwrap();
+    let fd = stream1.as_raw_fd();
+
+    let id = event::Id(0);
+    assert_error(os_queue.add_interest(&mut EventedFd(&fd), id, Interests::WRITABLE),
+        "no handle registered with id");
+
+    os_queue.register(&mut EventedFd(&fd), id, Interests::READABLE, RegisterOption::LEVEL).unwrap();
+    expect_no_events(&mut os_queue);
+
+    // Adding writable interest should make writable events appear.
+    os_queue.add_interest(&mut EventedFd(&fd), id, Interests::WRITABLE).unwrap();
+    assert_eq!(os_queue.interests(id), Some(Interests::READABLE | Interests::WRITABLE));
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(id, Ready::WRITABLE),
+    ]);
+
+    // And removing it should make them disappear again, keeping the level
+    // triggered option.
+    os_queue.remove_interest(&mut EventedFd(&fd), id, Interests::WRITABLE).unwrap();
+    assert_eq!(os_queue.interests(id), Some(Interests::READABLE));
+    expect_no_events(&mut os_queue);
+    (&stream2).write_all(b"Hello").unwrap();
+    for _ in 0..2 {
+        expect_events(&mut os_queue, &mut events, vec![
+            Event::new(id, Ready::READABLE),
+        ]);
+    }
+
+    // Removing all interests should deregister the handle.
+    os_queue.remove_interest(&mut EventedFd(&fd), id, Interests::READABLE).unwrap();
+    assert_eq!(os_queue.interests(id), None);
+    expect_no_events(&mut os_queue);
+}
+
+#[test]
+fn os_queue_with_capacity() {
+    init();
+    assert_error(OsQueue::with_capacity(0), "capacity must be larger then zero");
+
+    let mut os_queue = OsQueue::with_capacity(2).expect("unable to create OsQueue");
+    let mut events = Vec::new();
+
+    let mut pipes = Vec::new();
+    for id in 0..3 {
+        let (mut sender, mut receiver) = new_pipe().unwrap();
+        os_queue.register(&mut receiver, event::Id(id), Interests::READABLE, RegisterOption::EDGE).unwrap();
+        sender.write_all(b"Hello").unwrap();
+        pipes.push((sender, receiver));
+    }
+
+    // Only two events should be returned per poll, even with a growable event
+    // sink.
+    event::Source::<_, io::Error>::poll(&mut os_queue, &mut events).unwrap();
+    assert_eq!(events.len(), 2);
+    event::Source::<_, io::Error>::poll(&mut os_queue, &mut events).unwrap();
+    assert_eq!(events.len(), 3);
+
+    let mut ids: Vec<_> = events.iter().map(|event| event.id()).collect();
+    ids.sort();
+    assert_eq!(ids, vec![event::Id(0), event::Id(1), event::Id(2)]);
+
+    // Clones should use the same capacity.
+    let mut os_queue2 = os_queue.try_clone().unwrap();
+    for (sender, _) in pipes.iter_mut() {
+        sender.write_all(b"World").unwrap();
+    }
+    events.clear();
+    event::Source::<_, io::Error>::poll(&mut os_queue2, &mut events).unwrap();
+    assert_eq!(events.len(), 2);
+}
+
+#[test]
+fn os_queue_poll_interrupted() {
+    use std::{mem, ptr};
+
+    extern "C" fn noop_handler(_: libc::c_int) {}
+
+    /// Restores the signal action when dropped.
+    struct RestoreAction(libc::sigaction);
+
+    impl Drop for RestoreAction {
+        fn drop(&mut self) {
+            let _ = unsafe { libc::sigaction(libc::SIGUSR1, &self.0, ptr::null_mut()) };
+        }
+    }
+
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    // Install a signal handler so the signal doesn't kill the process, but
+    // interrupts the system call. The previous action is restored at the end.
+    let _restore = unsafe {
+        let mut action: libc::sigaction = mem::zeroed();
+        action.sa_sigaction = noop_handler as libc::sighandler_t;
+        let mut old_action: libc::sigaction = mem::zeroed();
+        assert_eq!(libc::sigaction(libc::SIGUSR1, &action, &mut old_action), 0);
+        RestoreAction(old_action)
+    };
+
+    let thread = unsafe { libc::pthread_self() } as usize;
+    let handle = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(unsafe { libc::pthread_kill(thread as libc::pthread_t, libc::SIGUSR1) }, 0);
+    });
+
+    // The poll should be retried, not returning an error.
+    let timeout = Duration::from_millis(50);
+    let start = Instant::now();
+    event::Source::<_, io::Error>::blocking_poll(&mut os_queue, &mut events, Some(timeout))
+        .expect("unable to poll");
+    let duration = start.elapsed();
+    assert!(events.is_empty());
+    #[cfg(not(feature="disable_test_deadline"))]
+    assert!(duration >= timeout && duration <= timeout + TIMEOUT_MARGIN,
+        "blocking time incorrect: {:?}, wanted: >= {:?} and <= {:?}.", duration, timeout, timeout + TIMEOUT_MARGIN);
+
+    handle.join().unwrap();
+}
+
+#[test]
+fn evented_via_fd_macro() {
+    use std::os::unix::io::{AsRawFd, RawFd};
+    use std::os::unix::net::UnixStream;
+
+    use gaea::evented_via_fd;
+
+    struct MyStream(UnixStream);
+
+    impl AsRawFd for MyStream {
+        fn as_raw_fd(&self) -> RawFd {
+            self.0.as_raw_fd()
+        }
+    }
+
+    evented_via_fd!(MyStream);
+
+    let (mut os_queue, mut events) = init_with_os_queue();
+    let (stream1, stream2) = UnixStream::pair().unwrap();
+    let mut stream = MyStream(stream1);
+
+    os_queue.register(&mut stream, event::Id(0), Interests::READABLE, RegisterOption::LEVEL).unwrap();
+    (&stream2).write_all(b"Hello").unwrap();
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(event::Id(0), Ready::READABLE),
+    ]);
+
+    os_queue.reregister(&mut stream, event::Id(1), Interests::READABLE, RegisterOption::LEVEL).unwrap();
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(event::Id(1), Ready::READABLE),
+    ]);
+
+    os_queue.deregister(&mut stream).unwrap();
+    expect_no_events(&mut os_queue);
+}
+
+#[test]
+fn evented_std_net() {
+    use std::net::{TcpListener, TcpStream, UdpSocket};
+
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    let mut listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.set_nonblocking(true).unwrap();
+    os_queue.register(&mut listener, event::Id(0), Interests::READABLE, RegisterOption::EDGE).unwrap();
+
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    stream.set_nonblocking(true).unwrap();
+    os_queue.register(&mut stream, event::Id(1), Interests::WRITABLE, RegisterOption::EDGE).unwrap();
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(event::Id(0), Ready::READABLE),
+        Event::new(event::Id(1), Ready::WRITABLE),
+    ]);
+
+    let mut socket1 = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket1.set_nonblocking(true).unwrap();
+    let socket2 = UdpSocket::bind("127.0.0.1:0").unwrap();
+    os_queue.register(&mut socket1, event::Id(2), Interests::READABLE, RegisterOption::EDGE).unwrap();
+    socket2.send_to(b"Hello", socket1.local_addr().unwrap()).unwrap();
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(event::Id(2), Ready::READABLE),
+    ]);
+
+    os_queue.deregister(&mut listener).unwrap();
+    os_queue.deregister(&mut stream).unwrap();
+    os_queue.deregister(&mut socket1).unwrap();
+    expect_no_events(&mut os_queue);
+}
+
+#[test]
+fn os_queue_cloexec() {
+    use std::os::unix::io::AsRawFd;
+
+    let (os_queue, _) = init_with_os_queue();
+    assert_cloexec(os_queue.as_raw_fd());
+    let os_queue2 = os_queue.try_clone().unwrap();
+    assert_cloexec(os_queue2.as_raw_fd());
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn splice() {
+    use std::io::Read;
+    use std::net::{TcpListener, TcpStream};
+
+    use gaea::os::Splicer;
+
+    init();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let mut client1 = TcpStream::connect(address).unwrap();
+    let (server1, _) = listener.accept().unwrap();
+    let mut client2 = TcpStream::connect(address).unwrap();
+    let (server2, _) = listener.accept().unwrap();
+    server1.set_nonblocking(true).unwrap();
+    server2.set_nonblocking(true).unwrap();
+
+    let mut splicer = Splicer::new().unwrap();
+
+    // No data available.
+    assert_would_block(splicer.splice(&server1, &server2, 1024));
+
+    const DATA: &[u8] = b"Hello world";
+    client1.write_all(DATA).unwrap();
+    thread::sleep(Duration::from_millis(10));
+
+    let n = splicer.splice(&server1, &server2, 5).unwrap();
+    assert_eq!(n, 5);
+    let n = splicer.splice(&server1, &server2, 1024).unwrap();
+    assert_eq!(n, DATA.len() - 5);
+    assert_would_block(splicer.splice(&server1, &server2, 1024));
+    assert_eq!(splicer.buffered(), 0);
+
+    let mut buf = [0; 11];
+    client2.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, DATA);
+
+    // Closing the connection should return zero bytes.
+    drop(client1);
+    thread::sleep(Duration::from_millis(10));
+    assert_eq!(splicer.splice(&server1, &server2, 1024).unwrap(), 0);
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn splice_buffered() {
+    use std::net::{TcpListener, TcpStream};
+    use std::os::unix::io::AsRawFd;
+
+    use gaea::os::Splicer;
+
+    init();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (server, _) = listener.accept().unwrap();
+    server.set_nonblocking(true).unwrap();
+
+    // Pipe with a buffer smaller than the data send.
+    let (sender, mut receiver) = new_pipe().unwrap();
+    assert_ne!(unsafe { libc::fcntl(sender.as_raw_fd(), libc::F_SETPIPE_SZ, 4096) }, -1);
+
+    let data: Vec<u8> = (0..65536).map(|n| n as u8).collect();
+    client.write_all(&data).unwrap();
+    thread::sleep(Duration::from_millis(10));
+
+    let mut splicer = Splicer::new().unwrap();
+    let n = splicer.splice(&server, &sender, data.len()).unwrap();
+    assert!(n < data.len());
+    // The data that didn't fit in `sender` should be kept, rather than
+    // blocking.
+    assert!(splicer.buffered() > 0);
+    assert_would_block(splicer.splice(&server, &sender, data.len()));
+
+    let mut output = Vec::new();
+    let mut buf = [0; 4096];
+    while output.len() < data.len() {
+        match receiver.read(&mut buf) {
+            Ok(n) => output.extend_from_slice(&buf[..n]),
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {
+                let _ = splicer.splice(&server, &sender, data.len()).unwrap();
+            },
+            Err(err) => panic!("unexpected error reading from pipe: {}", err),
+        }
+    }
+    assert_eq!(output, data);
+    assert_eq!(splicer.buffered(), 0);
+    assert_would_block(splicer.splice(&server, &sender, data.len()));
+}
+
+#[test]
+fn os_queue_coalesce() {
+    use std::os::unix::io::AsRawFd;
+    use std::os::unix::net::UnixStream;
+
+    use gaea::unix::EventedFd;
+
+    let (mut os_queue, mut events) = init_with_os_queue();
+    assert!(!os_queue.is_coalescing());
+
+    // Register two streams using the same id, one readable and one writable.
+    let (stream1, mut peer1) = UnixStream::pair().unwrap();
+    let (stream2, _peer2) = UnixStream::pair().unwrap();
+    peer1.write_all(b"Hello world").unwrap();
+    let id = event::Id(0);
+    os_queue.register(&mut EventedFd(&stream1.as_raw_fd()), id, Interests::READABLE, RegisterOption::LEVEL).unwrap();
+    os_queue.register(&mut EventedFd(&stream2.as_raw_fd()), id, Interests::WRITABLE, RegisterOption::LEVEL).unwrap();
+
+    // Not coalesced by default.
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(id, Ready::READABLE),
+        Event::new(id, Ready::WRITABLE),
+    ]);
+
+    os_queue.set_coalesce(true);
+    assert!(os_queue.is_coalescing());
+    events.clear();
+    let n = gaea::poll::<_, io::Error>(&mut [&mut os_queue], &mut events, Some(Duration::from_millis(0))).unwrap();
+    assert_eq!(n, 1);
+    assert_eq!(events, vec![Event::new(id, Ready::READABLE | Ready::WRITABLE)]);
+
+    // The setting is copied to clones.
+    let os_queue2 = os_queue.try_clone().unwrap();
+    assert!(os_queue2.is_coalescing());
+
+    os_queue.set_coalesce(false);
+    assert!(!os_queue.is_coalescing());
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(id, Ready::READABLE),
+        Event::new(id, Ready::WRITABLE),
+    ]);
+}
+
+#[test]
+fn os_queue_try_deregister() {
+    let (mut os_queue, _) = init_with_os_queue();
+    let (mut sender, mut receiver) = new_pipe().unwrap();
+
+    // Never registered.
+    assert!(!os_queue.try_deregister(&mut sender).unwrap());
+    let err = os_queue.deregister(&mut sender).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+    os_queue.register(&mut receiver, event::Id(0), Interests::READABLE, RegisterOption::EDGE).unwrap();
+    assert!(os_queue.try_deregister(&mut receiver).unwrap());
+    // Deregistering twice.
+    assert!(!os_queue.try_deregister(&mut receiver).unwrap());
+    let err = os_queue.deregister(&mut receiver).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+}
+
+#[test]
+#[cfg(any(target_os = "freebsd", target_os = "macos",
+          target_os = "netbsd", target_os = "openbsd"))]
+fn os_queue_priority_interest_only() {
+    let (mut os_queue, _) = init_with_os_queue();
+    let (_sender, mut receiver) = new_pipe().unwrap();
+
+    // kqueue doesn't support priority interests, so nothing would be
+    // registered.
+    let err = os_queue.register(&mut receiver, event::Id(0), Interests::PRIORITY, RegisterOption::EDGE)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert!(os_queue.is_empty());
+
+    let (index, err) = os_queue.register_many(&mut [
+        (&mut receiver, event::Id(0), Interests::PRIORITY, RegisterOption::EDGE),
+    ]).unwrap_err();
+    assert_eq!(index, 0);
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert!(os_queue.is_empty());
+
+    os_queue.register(&mut receiver, event::Id(0), Interests::READABLE, RegisterOption::EDGE).unwrap();
+    let err = os_queue.reregister(&mut receiver, event::Id(0), Interests::PRIORITY, RegisterOption::EDGE)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(os_queue.interests(event::Id(0)), Some(Interests::READABLE));
+}
+
+#[test]
+fn os_queue_double_registration() {
+    let (mut os_queue, _) = init_with_os_queue();
+    let (_sender, mut receiver) = new_pipe().unwrap();
+
+    os_queue.register(&mut receiver, event::Id(0), Interests::READABLE, RegisterOption::EDGE).unwrap();
+    let err = os_queue.register(&mut receiver, event::Id(1), Interests::READABLE, RegisterOption::LEVEL)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+    assert!(err.to_string().contains("reregister"));
+    // The original registration is unchanged.
+    assert_eq!(os_queue.interests(event::Id(0)), Some(Interests::READABLE));
+    assert_eq!(os_queue.interests(event::Id(1)), None);
+
+    // After deregistering the handle can be registered again.
+    os_queue.deregister(&mut receiver).unwrap();
+    os_queue.register(&mut receiver, event::Id(1), Interests::READABLE, RegisterOption::LEVEL).unwrap();
+}
+
+#[test]
+fn os_queue_register_reused_fd() {
+    use std::os::unix::io::AsRawFd;
+
+    use gaea::unix::EventedFd;
+
+    let (mut os_queue, mut events) = init_with_os_queue();
+    let (_sender1, receiver1) = new_pipe().unwrap();
+    let (mut sender2, receiver2) = new_pipe().unwrap();
+
+    let fd = receiver1.as_raw_fd();
+    os_queue.register(&mut EventedFd(&fd), event::Id(0), Interests::READABLE, RegisterOption::EDGE).unwrap();
+
+    // Close the registered file descriptor, without deregistering it, and
+    // reuse its number for another file descriptor.
+    assert_ne!(unsafe { libc::dup2(receiver2.as_raw_fd(), fd) }, -1);
+
+    // The stale registration is still tracked.
+    let err = os_queue.register(&mut EventedFd(&fd), event::Id(1), Interests::READABLE, RegisterOption::EDGE)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+
+    // Deregistering only removes the stale registration.
+    os_queue.deregister(&mut EventedFd(&fd)).unwrap();
+    assert!(os_queue.is_empty());
+
+    os_queue.register(&mut EventedFd(&fd), event::Id(1), Interests::READABLE, RegisterOption::EDGE)
+        .expect("unable to register reused file descriptor");
+    assert_eq!(os_queue.len(), 1);
+    assert_eq!(os_queue.interests(event::Id(0)), None);
+    assert_eq!(os_queue.interests(event::Id(1)), Some(Interests::READABLE));
+
+    sender2.write_all(b"Hello").unwrap();
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(event::Id(1), Ready::READABLE),
+    ]);
+}
+
+#[test]
+fn os_queue_register_oneshot_fired() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+    let (mut sender, _receiver) = new_pipe().unwrap();
+
+    os_queue.register(&mut sender, event::Id(0), Interests::WRITABLE, RegisterOption::ONESHOT).unwrap();
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(event::Id(0), Ready::WRITABLE),
+    ]);
+
+    // The registration already fired, but it's still registered on all
+    // platforms.
+    let err = os_queue.register(&mut sender, event::Id(1), Interests::WRITABLE, RegisterOption::ONESHOT)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+    assert_eq!(os_queue.interests(event::Id(0)), Some(Interests::WRITABLE));
+
+    os_queue.reregister(&mut sender, event::Id(1), Interests::WRITABLE, RegisterOption::ONESHOT).unwrap();
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(event::Id(1), Ready::WRITABLE),
+    ]);
+}
+
+#[test]
+fn os_queue_register_many() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+    let (mut sender1, mut receiver1) = new_pipe().unwrap();
+    let (mut sender2, mut receiver2) = new_pipe().unwrap();
+
+    os_queue.register_many(&mut [
+        (&mut sender1, event::Id(0), Interests::WRITABLE, RegisterOption::EDGE),
+        (&mut receiver1, event::Id(1), Interests::READABLE, RegisterOption::EDGE),
+        (&mut sender2, event::Id(2), Interests::WRITABLE, RegisterOption::EDGE),
+        (&mut receiver2, event::Id(3), Interests::READABLE, RegisterOption::EDGE),
+    ]).unwrap();
+    assert_eq!(os_queue.len(), 4);
+    assert_eq!(os_queue.interests(event::Id(1)), Some(Interests::READABLE));
+
+    sender1.write_all(b"Hello").unwrap();
+    sender2.write_all(b"world").unwrap();
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(event::Id(0), Ready::WRITABLE),
+        Event::new(event::Id(1), Ready::READABLE),
+        Event::new(event::Id(2), Ready::WRITABLE),
+        Event::new(event::Id(3), Ready::READABLE),
+    ]);
+
+    // The handles can be deregistered separately.
+    os_queue.deregister(&mut receiver1).unwrap();
+    assert_eq!(os_queue.len(), 3);
+}
+
+#[test]
+fn os_queue_register_many_error() {
+    use std::os::unix::io::AsRawFd;
+
+    use gaea::unix::EventedFd;
+
+    let (mut os_queue, mut events) = init_with_os_queue();
+    let (mut sender1, mut receiver1) = new_pipe().unwrap();
+    let (mut sender2, mut receiver2) = new_pipe().unwrap();
+
+    os_queue.register(&mut receiver2, event::Id(10), Interests::READABLE, RegisterOption::EDGE).unwrap();
+
+    // Registering a handle that is already registered.
+    let (index, err) = os_queue.register_many(&mut [
+        (&mut sender1, event::Id(0), Interests::WRITABLE, RegisterOption::EDGE),
+        (&mut receiver1, event::Id(1), Interests::READABLE, RegisterOption::EDGE),
+        (&mut receiver2, event::Id(2), Interests::READABLE, RegisterOption::EDGE),
+        (&mut sender2, event::Id(3), Interests::WRITABLE, RegisterOption::EDGE),
+    ]).unwrap_err();
+    assert_eq!(index, 2);
+    assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+    // None of the other handles should be registered, the existing
+    // registration should be unchanged.
+    assert_eq!(os_queue.len(), 1);
+    assert_eq!(os_queue.interests(event::Id(10)), Some(Interests::READABLE));
+    sender1.write_all(b"Hello").unwrap();
+    sender2.write_all(b"world").unwrap();
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(event::Id(10), Ready::READABLE),
+    ]);
+
+    // Registering the same handle twice.
+    let fd = receiver1.as_raw_fd();
+    let (index, err) = os_queue.register_many(&mut [
+        (&mut receiver1, event::Id(1), Interests::READABLE, RegisterOption::EDGE),
+        (&mut EventedFd(&fd), event::Id(2), Interests::READABLE, RegisterOption::EDGE),
+    ]).unwrap_err();
+    assert_eq!(index, 1);
+    assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+    assert_eq!(os_queue.len(), 1);
+
+    // Handle returning an error.
+    let (index, err) = os_queue.register_many(&mut [
+        (&mut receiver1, event::Id(1), Interests::READABLE, RegisterOption::EDGE),
+        (&mut ErroneousTestEvented, event::Id(2), Interests::READABLE, RegisterOption::EDGE),
+        (&mut sender1, event::Id(3), Interests::WRITABLE, RegisterOption::EDGE),
+    ]).unwrap_err();
+    assert_eq!(index, 1);
+    assert_eq!(err.to_string(), "register");
+    assert_eq!(os_queue.len(), 1);
+
+    // After an error the handles can be registered again.
+    os_queue.register_many(&mut [
+        (&mut receiver1, event::Id(1), Interests::READABLE, RegisterOption::EDGE),
+        (&mut sender1, event::Id(3), Interests::WRITABLE, RegisterOption::EDGE),
+    ]).unwrap();
+    assert_eq!(os_queue.len(), 3);
+}
+
+#[test]
+fn os_queue_reregister_toggle_interests() {
+    use std::os::unix::io::AsRawFd;
+    use std::os::unix::net::UnixStream;
+
+    use gaea::unix::EventedFd;
+
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    let (stream, mut peer) = UnixStream::pair().unwrap();
+    peer.write_all(b"Hello world").unwrap();
+    let fd = stream.as_raw_fd();
+    let id = event::Id(0);
+
+    os_queue.register(&mut EventedFd(&fd), id, Interests::READABLE, RegisterOption::LEVEL).unwrap();
+    let all_interests = [
+        (Interests::READABLE, Ready::READABLE),
+        (Interests::READABLE, Ready::READABLE),
+        (Interests::WRITABLE, Ready::WRITABLE),
+        (Interests::WRITABLE, Ready::WRITABLE),
+        (Interests::READABLE | Interests::WRITABLE, Ready::READABLE | Ready::WRITABLE),
+        (Interests::READABLE, Ready::READABLE),
+        (Interests::READABLE | Interests::WRITABLE, Ready::READABLE | Ready::WRITABLE),
+        (Interests::WRITABLE, Ready::WRITABLE),
+    ];
+    for &(interests, readiness) in &all_interests {
+        os_queue.reregister(&mut EventedFd(&fd), id, interests, RegisterOption::LEVEL)
+            .expect("unable to reregister");
+        assert_eq!(os_queue.interests(id), Some(interests));
+
+        // Only the filters for the current interests should be active.
+        events.clear();
+        gaea::poll::<_, io::Error>(&mut [&mut os_queue], &mut events, Some(Duration::from_millis(0))).unwrap();
+        let got = events.iter().fold(None, |acc: Option<Ready>, event| {
+            assert_eq!(event.id(), id);
+            Some(acc.map_or(event.readiness(), |acc| acc | event.readiness()))
+        });
+        assert_eq!(got, Some(readiness));
+    }
+}
+
+#[test]
+fn os_queue_rearm() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+    let (mut sender, mut receiver) = new_pipe().unwrap();
+
+    let id = event::Id(0);
+    // Not registered.
+    let err = os_queue.rearm(&mut receiver, id).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+    os_queue.register(&mut receiver, id, Interests::READABLE, RegisterOption::ONESHOT).unwrap();
+    sender.write_all(b"Hello").unwrap();
+    expect_events(&mut os_queue, &mut events, vec![Event::new(id, Ready::READABLE)]);
+
+    // Disabled after the first event.
+    sender.write_all(b"world").unwrap();
+    expect_no_events(&mut os_queue);
+
+    for _ in 0..3 {
+        os_queue.rearm(&mut receiver, id).unwrap();
+        expect_events(&mut os_queue, &mut events, vec![Event::new(id, Ready::READABLE)]);
+        expect_no_events(&mut os_queue);
+    }
+
+    // Not registered using the oneshot option.
+    os_queue.reregister(&mut receiver, id, Interests::READABLE, RegisterOption::EDGE).unwrap();
+    let err = os_queue.rearm(&mut receiver, id).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn os_queue_edge_oneshot() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+    let (mut sender, mut receiver) = new_pipe().unwrap();
+
+    let id = event::Id(0);
+    os_queue.register(&mut receiver, id, Interests::READABLE, RegisterOption::edge().oneshot()).unwrap();
+    sender.write_all(b"Hello").unwrap();
+    sender.write_all(b"world").unwrap();
+    expect_events(&mut os_queue, &mut events, vec![Event::new(id, Ready::READABLE)]);
+
+    // Only a single event is returned, even though not all data is read and
+    // more data is written.
+    let mut buf = [0; 5];
+    receiver.read_exact(&mut buf).unwrap();
+    sender.write_all(b"!").unwrap();
+    expect_no_events(&mut os_queue);
+
+    os_queue.rearm(&mut receiver, id).unwrap();
+    expect_events(&mut os_queue, &mut events, vec![Event::new(id, Ready::READABLE)]);
+    expect_no_events(&mut os_queue);
+}
+
+#[test]
+fn os_queue_emulated_level() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+    let (mut sender, mut receiver) = new_pipe().unwrap();
+
+    let id = event::Id(0);
+    os_queue.register(&mut receiver, id, Interests::READABLE, RegisterOption::EMULATED_LEVEL).unwrap();
+    expect_no_events(&mut os_queue);
+
+    sender.write_all(b"Hello world").unwrap();
+    // Keep returning events until all data is read.
+    for _ in 0..3 {
+        expect_events(&mut os_queue, &mut events, vec![Event::new(id, Ready::READABLE)]);
+    }
+
+    let mut buf = [0; 5];
+    receiver.read_exact(&mut buf).unwrap();
+    expect_events(&mut os_queue, &mut events, vec![Event::new(id, Ready::READABLE)]);
+
+    let mut buf = [0; 20];
+    assert_eq!(receiver.read(&mut buf).unwrap(), 6);
+    expect_no_events(&mut os_queue);
+
+    // Data written after draining should be returned again.
+    sender.write_all(b"Hello").unwrap();
+    expect_events(&mut os_queue, &mut events, vec![Event::new(id, Ready::READABLE)]);
+    expect_events(&mut os_queue, &mut events, vec![Event::new(id, Ready::READABLE)]);
+
+    // No longer emulated after reregistering.
+    os_queue.reregister(&mut receiver, id, Interests::READABLE, RegisterOption::EDGE).unwrap();
+    // Reregistering could return a single edge-triggered event.
+    let _ = gaea::poll::<_, io::Error>(&mut [&mut os_queue], &mut events, Some(Duration::from_millis(0))).unwrap();
+    expect_no_events(&mut os_queue);
+
+    // Deregistering removes the emulated registration.
+    os_queue.reregister(&mut receiver, id, Interests::READABLE, RegisterOption::EMULATED_LEVEL).unwrap();
+    expect_events(&mut os_queue, &mut events, vec![Event::new(id, Ready::READABLE)]);
+    os_queue.deregister(&mut receiver).unwrap();
+    expect_no_events(&mut os_queue);
+}
+
+#[test]
+fn os_queue_emulated_level_same_id() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+    let (mut sender1, mut receiver1) = new_pipe().unwrap();
+    let (mut sender2, mut receiver2) = new_pipe().unwrap();
+
+    let id = event::Id(0);
+    os_queue.register(&mut receiver1, id, Interests::READABLE, RegisterOption::EMULATED_LEVEL).unwrap();
+    os_queue.register(&mut receiver2, id, Interests::READABLE, RegisterOption::EMULATED_LEVEL).unwrap();
+    sender1.write_all(b"Hello").unwrap();
+    sender2.write_all(b"world").unwrap();
+    expect_events(&mut os_queue, &mut events, vec![Event::new(id, Ready::READABLE)]);
+
+    // Deregistering one handle shouldn't affect the emulation of the other.
+    os_queue.deregister(&mut receiver1).unwrap();
+    for _ in 0..2 {
+        expect_events(&mut os_queue, &mut events, vec![Event::new(id, Ready::READABLE)]);
+    }
+
+    let mut buf = [0; 10];
+    assert_eq!(receiver2.read(&mut buf).unwrap(), 5);
+    expect_no_events(&mut os_queue);
+}
+
+#[test]
+fn os_queue_emulated_level_invalid_options() {
+    let mut os_queue = OsQueue::new().unwrap();
+    let (_, mut receiver) = new_pipe().unwrap();
+
+    let opts = [
+        RegisterOption::EMULATED_LEVEL | RegisterOption::EDGE,
+        RegisterOption::EMULATED_LEVEL | RegisterOption::ONESHOT,
+    ];
+    for opt in opts.iter() {
+        let err = os_queue.register(&mut receiver, event::Id(0), Interests::READABLE, *opt).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
+
+#[test]
+fn os_queue_len_and_clear() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+    assert_eq!(os_queue.len(), 0);
+    assert!(os_queue.is_empty());
+
+    let (mut sender1, mut receiver1) = new_pipe().unwrap();
+    let (mut sender2, mut receiver2) = new_pipe().unwrap();
+    os_queue.register(&mut sender1, event::Id(0), Interests::WRITABLE, RegisterOption::LEVEL).unwrap();
+    os_queue.register(&mut receiver1, event::Id(1), Interests::READABLE, RegisterOption::EDGE).unwrap();
+    os_queue.register(&mut sender2, event::Id(2), Interests::WRITABLE, RegisterOption::LEVEL).unwrap();
+    os_queue.register(&mut receiver2, event::Id(3), Interests::READABLE, RegisterOption::EMULATED_LEVEL).unwrap();
+    assert_eq!(os_queue.len(), 4);
+    assert!(!os_queue.is_empty());
+
+    os_queue.deregister(&mut sender2).unwrap();
+    assert_eq!(os_queue.len(), 3);
+
+    // Closed handles are ignored.
+    drop(receiver1);
+
+    sender2.write_all(b"Hello").unwrap();
+    os_queue.clear().unwrap();
+    assert_eq!(os_queue.len(), 0);
+    assert!(os_queue.is_empty());
+    assert_eq!(os_queue.interests(event::Id(0)), None);
+    assert_eq!(os_queue.interests(event::Id(3)), None);
+    expect_no_events(&mut os_queue);
+
+    // Handles can be registered again.
+    os_queue.register(&mut sender1, event::Id(0), Interests::WRITABLE, RegisterOption::LEVEL).unwrap();
+    assert_eq!(os_queue.len(), 1);
+    expect_events(&mut os_queue, &mut events, vec![Event::new(event::Id(0), Ready::WRITABLE)]);
+}
@@ -1,11 +1,13 @@
+use std::io;
 use std::thread::sleep;
 use std::time::{Duration, Instant};
 
-use gaea::{event, poll};
+use gaea::event::{Capacity, LimitSink};
+use gaea::{event, poll, poll_collect_errors, poll_each, poll_fair, poll_until, poll_with_budget, Event, OsQueue, Queue, Ready, Timers};
 
 mod util;
 
-use self::util::{init, TIMEOUT_MARGIN};
+use self::util::{init, EventsCapacity, TIMEOUT_MARGIN};
 
 struct SleepySource;
 
@@ -16,14 +18,14 @@ impl<ES, E> event::Source<ES, E> for SleepySource
         None
     }
 
-    fn poll(&mut self, event_sink: &mut ES) -> Result<(), E> {
+    fn poll(&mut self, event_sink: &mut ES) -> Result<usize, E> {
         self.blocking_poll(event_sink, Some(Duration::from_millis(0)))
     }
 
-    fn blocking_poll(&mut self, _event_sink: &mut ES, timeout: Option<Duration>) -> Result<(), E> {
+    fn blocking_poll(&mut self, _event_sink: &mut ES, timeout: Option<Duration>) -> Result<usize, E> {
         let timeout = timeout.expect("SleepySource needs a timeout");
         sleep(timeout);
-        Ok(())
+        Ok(0)
     }
 }
 
@@ -36,8 +38,8 @@ impl<ES, E> event::Source<ES, E> for AvailableSource
         Some(self.0)
     }
 
-    fn poll(&mut self, _event_sink: &mut ES) -> Result<(), E> {
-        Ok(())
+    fn poll(&mut self, _event_sink: &mut ES) -> Result<usize, E> {
+        Ok(0)
     }
 }
 
@@ -65,7 +67,62 @@ fn poll_determine_timeout() {
         "blocking time incorrect: {:?}, wanted: >= {:?} and >= {:?}.", duration, timeout, timeout + TIMEOUT_MARGIN);
 }
 
-struct ResultSource<E>(Result<(), E>);
+#[test]
+fn poll_until_deadline() {
+    init();
+
+    let mut events = Vec::new();
+    let timeout = Duration::from_millis(10);
+
+    let start = Instant::now();
+    let deadline = start + timeout;
+    poll_until::<_, ()>(&mut [&mut SleepySource], &mut events, deadline).unwrap();
+    assert!(events.is_empty());
+    let duration = start.elapsed();
+    #[cfg(not(feature="disable_test_deadline"))]
+    assert!(duration >= timeout && duration <= timeout + TIMEOUT_MARGIN,
+        "blocking time incorrect: {:?}, wanted: >= {:?} and >= {:?}.", duration, timeout, timeout + TIMEOUT_MARGIN);
+
+    // Deadline already passed, shouldn't block.
+    let start = Instant::now();
+    poll_until::<_, ()>(&mut [&mut SleepySource], &mut events, deadline).unwrap();
+    assert!(events.is_empty());
+    #[cfg(not(feature="disable_test_deadline"))]
+    assert!(start.elapsed() <= TIMEOUT_MARGIN,
+        "blocking time incorrect: {:?}, wanted: <= {:?}.", start.elapsed(), TIMEOUT_MARGIN);
+
+    // Other sources should still limit the timeout.
+    let start = Instant::now();
+    let deadline = start + Duration::from_secs(1);
+    poll_until::<_, ()>(&mut [&mut SleepySource, &mut AvailableSource(timeout)], &mut events, deadline).unwrap();
+    let duration = start.elapsed();
+    #[cfg(not(feature="disable_test_deadline"))]
+    assert!(duration >= timeout && duration <= timeout + TIMEOUT_MARGIN,
+        "blocking time incorrect: {:?}, wanted: >= {:?} and >= {:?}.", duration, timeout, timeout + TIMEOUT_MARGIN);
+}
+
+#[test]
+fn poll_timeout_clamped_to_deadline() {
+    init();
+
+    let mut os_queue = OsQueue::new().expect("unable to create OsQueue");
+    let mut timers = Timers::new();
+    let mut events = Vec::new();
+
+    let timeout = Duration::from_millis(50);
+    let start = Instant::now();
+    // Use a deadline that isn't a whole number of milliseconds away.
+    timers.add_deadline(event::Id(0), start + timeout + Duration::from_micros(500));
+
+    poll::<_, io::Error>(&mut [&mut os_queue, &mut timers], &mut events, Some(Duration::from_secs(5))).unwrap();
+    let duration = start.elapsed();
+    assert_eq!(events, vec![Event::new(event::Id(0), Ready::TIMER)]);
+    #[cfg(not(feature="disable_test_deadline"))]
+    assert!(duration >= timeout && duration <= timeout + TIMEOUT_MARGIN,
+        "blocking time incorrect: {:?}, wanted: >= {:?} and <= {:?}.", duration, timeout, timeout + TIMEOUT_MARGIN);
+}
+
+struct ResultSource<E>(Result<usize, E>);
 
 impl<E2, ES, E> event::Source<ES, E> for ResultSource<E2>
     where ES: event::Sink,
@@ -76,7 +133,7 @@ impl<E2, ES, E> event::Source<ES, E> for ResultSource<E2>
         None
     }
 
-    fn poll(&mut self, _event_sink: &mut ES) -> Result<(), E> {
+    fn poll(&mut self, _event_sink: &mut ES) -> Result<usize, E> {
         self.0.clone().map_err(Into::into)
     }
 }
@@ -112,7 +169,7 @@ fn poll_different_source_error_types() {
 
     let mut events = Vec::new();
 
-    let mut s1 = ResultSource::<u8>(Ok(()));
+    let mut s1 = ResultSource::<u8>(Ok(0));
     let mut s2 = ResultSource(Err(1u8));
     let mut s3 = ResultSource(Err(2u16));
     let mut s4 = ResultSource(Err(3u32));
@@ -120,3 +177,250 @@ fn poll_different_source_error_types() {
     let res = poll(&mut [&mut s1, &mut s2, &mut s3, &mut s4], &mut events, None);
     assert_eq!(res, Err(Error::U8(1)));
 }
+
+#[test]
+fn poll_collect_errors_polls_all_sources() {
+    init();
+
+    let mut events = Vec::new();
+
+    let mut s1 = ResultSource(Err(1u8));
+    let mut s2 = ResultSource::<u8>(Ok(0));
+    let mut s3 = ResultSource(Err(2u16));
+    let mut queue = Queue::new();
+    queue.add(Event::new(event::Id(0), Ready::READABLE));
+
+    let errors = poll_collect_errors::<_, Error>(&mut [&mut s1, &mut s2, &mut s3, &mut queue], &mut events, None);
+    assert_eq!(errors, vec![(0, Error::U8(1)), (2, Error::U16(2))]);
+    // The events of the queue should still be returned.
+    assert_eq!(events, vec![Event::new(event::Id(0), Ready::READABLE)]);
+
+    let errors = poll_collect_errors::<_, Error>(&mut [&mut s2, &mut queue], &mut events, None);
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn poll_custom_error_type() {
+    init();
+
+    /// Application specific error type.
+    #[derive(Debug)]
+    enum AppError {
+        Io(io::Error),
+    }
+
+    impl From<io::Error> for AppError {
+        fn from(err: io::Error) -> AppError {
+            AppError::Io(err)
+        }
+    }
+
+    let mut os_queue = OsQueue::new().unwrap();
+    let mut queue = Queue::new();
+    let mut timers = Timers::new();
+    queue.add(Event::new(event::Id(0), Ready::READABLE));
+    timers.add_deadline(event::Id(1), Instant::now());
+
+    let mut events = Vec::new();
+    let res = poll::<_, AppError>(&mut [&mut os_queue, &mut queue, &mut timers], &mut events, None);
+    match res {
+        Ok(n) => assert_eq!(n, 2),
+        Err(AppError::Io(err)) => panic!("unexpected error: {}", err),
+    }
+    assert_eq!(events, vec![
+        Event::new(event::Id(0), Ready::READABLE),
+        Event::new(event::Id(1), Ready::TIMER),
+    ]);
+}
+
+#[test]
+fn poll_returns_number_of_events() {
+    init();
+
+    let mut events = Vec::new();
+    let mut queue = Queue::new();
+    let mut timers = Timers::new();
+
+    assert_eq!(poll::<_, ()>(&mut [&mut queue, &mut timers], &mut events, Some(Duration::from_millis(0))), Ok(0));
+
+    queue.add(Event::new(event::Id(0), Ready::READABLE));
+    queue.add(Event::new(event::Id(1), Ready::WRITABLE));
+    timers.add_deadline(event::Id(2), Instant::now());
+    assert_eq!(poll::<_, ()>(&mut [&mut queue, &mut timers], &mut events, None), Ok(3));
+    assert_eq!(events.len(), 3);
+
+    // Only events added to the sink should be counted.
+    queue.add(Event::new(event::Id(0), Ready::READABLE));
+    queue.add(Event::new(event::Id(1), Ready::WRITABLE));
+    let mut events = EventsCapacity(Capacity::Limited(1), 0);
+    assert_eq!(poll::<_, ()>(&mut [&mut queue, &mut timers], &mut events, None), Ok(1));
+    assert_eq!(events.1, 1);
+}
+
+/// Event source that always has events ready.
+struct SaturatedSource(usize);
+
+impl<ES, E> event::Source<ES, E> for SaturatedSource
+    where ES: event::Sink,
+{
+    fn max_timeout(&self) -> Option<Duration> {
+        Some(Duration::from_millis(0))
+    }
+
+    fn poll(&mut self, event_sink: &mut ES) -> Result<usize, E> {
+        let n = event_sink.capacity_left().min(100);
+        for _ in 0..n {
+            event_sink.add(Event::new(event::Id(self.0), Ready::READABLE));
+        }
+        Ok(n)
+    }
+}
+
+#[test]
+fn poll_fair_saturated_sources() {
+    init();
+
+    let mut source1 = SaturatedSource(1);
+    let mut source2 = SaturatedSource(2);
+    let mut events = Vec::new();
+
+    // With `poll` the first source uses up all capacity.
+    let n = poll::<_, ()>(&mut [&mut source1, &mut source2], &mut LimitSink::new(&mut events, 10), None).unwrap();
+    assert_eq!(n, 10);
+    assert!(events.iter().all(|event| event.id() == event::Id(1)));
+
+    events.clear();
+    let n = poll_fair::<_, ()>(&mut [&mut source1, &mut source2], &mut LimitSink::new(&mut events, 10), None).unwrap();
+    assert_eq!(n, 10);
+    assert_eq!(events.iter().filter(|event| event.id() == event::Id(1)).count(), 5);
+    assert_eq!(events.iter().filter(|event| event.id() == event::Id(2)).count(), 5);
+
+    // Capacity left after sharing is filled up.
+    events.clear();
+    let mut queue = Queue::new();
+    queue.add(Event::new(event::Id(3), Ready::READABLE));
+    let n = poll_fair::<_, ()>(&mut [&mut queue, &mut source1, &mut source2], &mut LimitSink::new(&mut events, 9), None).unwrap();
+    assert_eq!(n, 9);
+    assert_eq!(events[0], Event::new(event::Id(3), Ready::READABLE));
+    assert_eq!(events.iter().filter(|event| event.id() == event::Id(1)).count(), 5);
+    assert_eq!(events.iter().filter(|event| event.id() == event::Id(2)).count(), 3);
+}
+
+#[test]
+fn poll_fair_growable_capacity() {
+    init();
+
+    let mut queue1 = Queue::new();
+    let mut queue2 = Queue::new();
+    for n in 0..3 {
+        queue1.add(Event::new(event::Id(n), Ready::READABLE));
+        queue2.add(Event::new(event::Id(10 + n), Ready::WRITABLE));
+    }
+
+    let mut events = Vec::new();
+    let n = poll_fair::<_, ()>(&mut [&mut queue1, &mut queue2], &mut events, None).unwrap();
+    assert_eq!(n, 6);
+    let ids: Vec<_> = events.iter().map(|event| event.id().0).collect();
+    assert_eq!(ids, vec![0, 1, 2, 10, 11, 12]);
+}
+
+#[test]
+fn poll_fair_small_capacity() {
+    init();
+
+    let mut source1 = SaturatedSource(1);
+    let mut source2 = SaturatedSource(2);
+    let mut source3 = SaturatedSource(3);
+    let mut events = Vec::new();
+
+    let n = poll_fair::<_, ()>(&mut [&mut source1, &mut source2, &mut source3], &mut LimitSink::new(&mut events, 2), None).unwrap();
+    assert_eq!(n, 2);
+    assert_eq!(events, vec![Event::new(event::Id(1), Ready::READABLE), Event::new(event::Id(2), Ready::READABLE)]);
+
+    let n = poll_fair::<_, ()>(&mut [&mut source1, &mut source2], &mut EventsCapacity(Capacity::Limited(0), 0), None).unwrap();
+    assert_eq!(n, 0);
+}
+
+#[test]
+fn poll_with_budget_saturated_sources() {
+    init();
+
+    let mut source1 = SaturatedSource(1);
+    let mut source2 = SaturatedSource(2);
+    let mut events = Vec::new();
+
+    let n = poll_with_budget::<_, ()>(&mut [&mut source1, &mut source2], &mut events, None, 3).unwrap();
+    assert_eq!(n, 6);
+    let ids: Vec<_> = events.iter().map(|event| event.id().0).collect();
+    assert_eq!(ids, vec![1, 1, 1, 2, 2, 2]);
+
+    // The capacity of the event sink is still respected.
+    events.clear();
+    let n = poll_with_budget::<_, ()>(&mut [&mut source1, &mut source2], &mut LimitSink::new(&mut events, 4), None, 3).unwrap();
+    assert_eq!(n, 4);
+    let ids: Vec<_> = events.iter().map(|event| event.id().0).collect();
+    assert_eq!(ids, vec![1, 1, 1, 2]);
+}
+
+#[test]
+#[should_panic(expected = "poll_with_budget: budget must be at least one")]
+fn poll_with_budget_zero() {
+    init();
+
+    let mut queue = Queue::new();
+    queue.add(Event::new(event::Id(0), Ready::READABLE));
+    let mut events = Vec::new();
+    let _ = poll_with_budget::<_, ()>(&mut [&mut queue], &mut events, None, 0);
+}
+
+#[test]
+fn poll_with_budget_remaining_events() {
+    init();
+
+    let mut queue = Queue::new();
+    let mut timers = Timers::new();
+    for n in 0..5 {
+        queue.add(Event::new(event::Id(n), Ready::READABLE));
+    }
+    timers.add_deadline(event::Id(10), Instant::now());
+
+    let mut events = Vec::new();
+    let n = poll_with_budget::<_, ()>(&mut [&mut queue, &mut timers], &mut events, None, 2).unwrap();
+    assert_eq!(n, 3);
+    let ids: Vec<_> = events.iter().map(|event| event.id().0).collect();
+    assert_eq!(ids, vec![0, 1, 10]);
+
+    events.clear();
+    let n = poll_with_budget::<_, ()>(&mut [&mut queue, &mut timers], &mut events, None, 2).unwrap();
+    assert_eq!(n, 2);
+    events.clear();
+    let n = poll_with_budget::<_, ()>(&mut [&mut queue, &mut timers], &mut events, None, 2).unwrap();
+    assert_eq!(n, 1);
+    assert_eq!(events, vec![Event::new(event::Id(4), Ready::READABLE)]);
+}
+
+#[test]
+fn poll_each_calls_function() {
+    init();
+
+    let mut os_queue = OsQueue::new().unwrap();
+    let mut queue = Queue::new();
+    let mut timers = Timers::new();
+    queue.add(Event::new(event::Id(0), Ready::READABLE));
+    queue.add(Event::new(event::Id(1), Ready::WRITABLE));
+    timers.add_deadline(event::Id(2), Instant::now());
+
+    let mut events = Vec::new();
+    let n = poll_each::<_, io::Error>(&mut [&mut os_queue, &mut queue, &mut timers], None, |event| events.push(event)).unwrap();
+    assert_eq!(n, 3);
+    assert_eq!(events, vec![
+        Event::new(event::Id(0), Ready::READABLE),
+        Event::new(event::Id(1), Ready::WRITABLE),
+        Event::new(event::Id(2), Ready::TIMER),
+    ]);
+
+    let mut called = false;
+    let n = poll_each::<_, io::Error>(&mut [&mut os_queue, &mut queue, &mut timers], Some(Duration::from_millis(0)), |_| called = true).unwrap();
+    assert_eq!(n, 0);
+    assert!(!called);
+}
@@ -1,6 +1,6 @@
 use std::time::Duration;
 
-use gaea::event::{self, Capacity, Ready, Source};
+use gaea::event::{self, Capacity, CappedEvents, Ready, Source};
 use gaea::{Event, Queue};
 
 mod util;
@@ -35,6 +35,88 @@ fn queue() {
     ]);
 }
 
+#[test]
+fn queue_fifo_ordering() {
+    init();
+    let mut queue = Queue::new();
+
+    let events: Vec<Event> = (0..1000)
+        .map(|n| Event::with_data(event::Id(n % 10), Ready::READABLE, n as u64))
+        .collect();
+    for event in &events {
+        queue.add(*event);
+    }
+
+    // Poll in chunks, the ordering should be kept between polls.
+    let mut got = Vec::new();
+    loop {
+        let mut sink = CappedEvents::new(7);
+        let n = Source::<_, ()>::poll(&mut queue, &mut sink).unwrap();
+        if n == 0 {
+            break;
+        }
+        got.extend(sink.0);
+    }
+    assert_eq!(got, events);
+}
+
+#[test]
+fn queue_partial_drain() {
+    init();
+    let mut queue = Queue::new();
+
+    let events: Vec<Event> = (0..10)
+        .map(|n| Event::with_data(event::Id(n), Ready::READABLE, n as u64))
+        .collect();
+    for event in &events {
+        queue.add(*event);
+    }
+
+    // A sink without capacity shouldn't remove any events.
+    let mut sink = CappedEvents::new(0);
+    assert_eq!(Source::<_, ()>::poll(&mut queue, &mut sink), Ok(0));
+    assert!(sink.0.is_empty());
+    assert_eq!(queue.len(), events.len());
+    assert_eq!(queue.peek(), Some(&events[0]));
+
+    let mut got = Vec::new();
+    for n in 0..events.len() {
+        let mut sink = CappedEvents::new(1);
+        assert_eq!(Source::<_, ()>::poll(&mut queue, &mut sink), Ok(1));
+        got.extend(sink.0);
+        assert_eq!(queue.len(), events.len() - n - 1);
+    }
+    assert_eq!(got, events);
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn queue_event_data() {
+    init();
+    let mut queue = Queue::new();
+    let mut events = Vec::new();
+
+    let event = Event::with_data(event::Id(0), Ready::READABLE, u64::max_value());
+    queue.add(event);
+    Source::<_, ()>::poll(&mut queue, &mut events).unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0], event);
+    assert_eq!(events[0].data(), u64::max_value());
+}
+
+#[test]
+fn queue_event_error() {
+    init();
+    let mut queue = Queue::new();
+    let mut events = Vec::new();
+
+    let event = Event::with_error(event::Id(0), Ready::ERROR, 32);
+    queue.add(event);
+    Source::<_, ()>::poll(&mut queue, &mut events).unwrap();
+    assert_eq!(events, vec![event]);
+    assert_eq!(events[0].error(), Some(32));
+}
+
 #[test]
 fn queue_events_capacity() {
     init();
@@ -68,3 +150,80 @@ fn queue_events_capacity() {
     Source::<_, ()>::poll(&mut queue, &mut events).unwrap();
     assert_eq!(events.1, 2);
 }
+
+#[test]
+fn bounded_queue() {
+    init();
+    let mut queue = Queue::with_capacity(2);
+    let mut events = Vec::new();
+
+    assert!(queue.is_empty());
+    assert!(!queue.is_full());
+
+    let event1 = Event::new(event::Id(0), Ready::READABLE);
+    let event2 = Event::new(event::Id(1), Ready::WRITABLE);
+    let event3 = Event::new(event::Id(2), Ready::ERROR);
+    assert_eq!(queue.try_add(event1), Ok(()));
+    assert_eq!(queue.try_add(event2), Ok(()));
+    assert_eq!(queue.len(), 2);
+    assert!(queue.is_full());
+
+    // Past capacity the event should be rejected.
+    assert_eq!(queue.try_add(event3), Err(event3));
+    assert_eq!(queue.len(), 2);
+
+    // After polling there should be room again.
+    expect_events(&mut queue, &mut events, vec![event1, event2]);
+    assert!(queue.is_empty());
+    assert!(!queue.is_full());
+    assert_eq!(queue.try_add(event3), Ok(()));
+    expect_events(&mut queue, &mut events, vec![event3]);
+}
+
+#[test]
+fn bounded_queue_zero_capacity() {
+    init();
+    let mut queue = Queue::with_capacity(0);
+    let event = Event::new(event::Id(0), Ready::READABLE);
+    assert!(queue.is_full());
+    assert_eq!(queue.try_add(event), Err(event));
+}
+
+#[test]
+fn unbounded_queue_try_add() {
+    init();
+    let mut queue = Queue::new();
+    let event = Event::new(event::Id(0), Ready::READABLE);
+    for _ in 0..100 {
+        assert_eq!(queue.try_add(event), Ok(()));
+    }
+    assert_eq!(queue.len(), 100);
+    assert!(!queue.is_full());
+}
+
+#[test]
+fn queue_peek() {
+    init();
+    let mut queue = Queue::new();
+    let mut events = Vec::new();
+
+    assert_eq!(queue.peek(), None);
+
+    let event1 = Event::new(event::Id(0), Ready::READABLE);
+    let event2 = Event::new(event::Id(1), Ready::WRITABLE);
+    queue.add(event1);
+    queue.add(event2);
+
+    // Peeking shouldn't remove the event.
+    assert_eq!(queue.peek(), Some(&event1));
+    assert_eq!(queue.peek(), Some(&event1));
+    assert_eq!(queue.len(), 2);
+    assert_eq!(max_timeout(&queue), Some(Duration::from_millis(0)));
+
+    let mut events_one = EventsCapacity(Capacity::Limited(1), 0);
+    Source::<_, ()>::poll(&mut queue, &mut events_one).unwrap();
+    assert_eq!(queue.peek(), Some(&event2));
+
+    expect_events(&mut queue, &mut events, vec![event2]);
+    assert_eq!(queue.peek(), None);
+}
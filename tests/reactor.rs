@@ -0,0 +1,87 @@
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use gaea::event::{self, Event, Ready};
+use gaea::os::{Interests, RegisterOption};
+use gaea::unix::new_pipe;
+use gaea::Reactor;
+
+mod util;
+
+use self::util::{init, TIMEOUT_MARGIN};
+
+#[test]
+fn reactor() {
+    init();
+    let mut reactor = Reactor::new().expect("unable to create reactor");
+    let mut events = Vec::new();
+
+    // No events.
+    assert_eq!(reactor.poll(&mut events, Some(Duration::from_millis(0))).unwrap(), 0);
+    assert!(events.is_empty());
+
+    // User space event.
+    let event = Event::new(event::Id(0), Ready::READABLE);
+    reactor.notify(event);
+    assert_eq!(reactor.poll(&mut events, None).unwrap(), 1);
+    assert_eq!(events, vec![event]);
+    events.clear();
+
+    // OS event.
+    let (mut sender, mut receiver) = new_pipe().unwrap();
+    reactor.register(&mut receiver, event::Id(1), Interests::READABLE, RegisterOption::EDGE).unwrap();
+    assert_eq!(reactor.os_queue().interests(event::Id(1)), Some(Interests::READABLE));
+    sender.write_all(b"Hello").unwrap();
+    assert_eq!(reactor.poll(&mut events, None).unwrap(), 1);
+    assert_eq!(events, vec![Event::new(event::Id(1), Ready::READABLE)]);
+    events.clear();
+
+    reactor.deregister(&mut receiver).unwrap();
+    assert_eq!(reactor.os_queue().interests(event::Id(1)), None);
+
+    // Deadline.
+    reactor.add_deadline(event::Id(2), Instant::now());
+    assert_eq!(reactor.poll(&mut events, None).unwrap(), 1);
+    assert_eq!(events, vec![Event::new(event::Id(2), Ready::TIMER)]);
+    events.clear();
+
+    // Removed deadline.
+    reactor.add_deadline(event::Id(3), Instant::now());
+    reactor.remove_deadline(event::Id(3));
+    assert_eq!(reactor.poll(&mut events, Some(Duration::from_millis(0))).unwrap(), 0);
+    assert!(events.is_empty());
+}
+
+#[test]
+fn reactor_timeout_clamped_to_deadline() {
+    init();
+    let mut reactor = Reactor::new().expect("unable to create reactor");
+    let mut events = Vec::new();
+
+    let timeout = Duration::from_millis(20);
+    reactor.add_timeout(event::Id(0), timeout);
+
+    let start = Instant::now();
+    reactor.poll(&mut events, Some(Duration::from_secs(5))).unwrap();
+    let duration = start.elapsed();
+    assert_eq!(events, vec![Event::new(event::Id(0), Ready::TIMER)]);
+    #[cfg(not(feature="disable_test_deadline"))]
+    assert!(duration >= timeout && duration <= timeout + TIMEOUT_MARGIN,
+        "blocking time incorrect: {:?}, wanted: >= {:?} and <= {:?}.", duration, timeout, timeout + TIMEOUT_MARGIN);
+}
+
+#[test]
+fn reactor_inner_sources() {
+    init();
+    let mut reactor = Reactor::new().expect("unable to create reactor");
+
+    let event = Event::new(event::Id(0), Ready::READABLE);
+    reactor.queue_mut().add(event);
+    assert_eq!(reactor.queue().peek(), Some(&event));
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    reactor.timers_mut().add_deadline(event::Id(1), deadline);
+    assert_eq!(reactor.timers().next_deadline(), Some(deadline));
+
+    let _ = reactor.os_queue_mut().try_clone().unwrap();
+}
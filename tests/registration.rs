@@ -0,0 +1,156 @@
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+use gaea::event::{self, Event, Ready};
+use gaea::os::{Awakener, Notifier, Registration};
+use gaea::{poll, OsQueue};
+
+mod util;
+
+use self::util::{init, init_with_os_queue, max_timeout};
+
+const WAKE_ID: event::Id = event::Id(0);
+const REGISTRATION_ID: event::Id = event::Id(1);
+
+#[test]
+fn notifier_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Notifier>();
+    assert_send_sync::<Registration>();
+}
+
+#[test]
+fn registration() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+    let awakener = Awakener::new(&mut os_queue, WAKE_ID).unwrap();
+    let (mut registration, notifier) = Registration::new(&awakener, REGISTRATION_ID).unwrap();
+    assert_eq!(registration.id(), REGISTRATION_ID);
+    assert_eq!(max_timeout(&registration), None);
+
+    notifier.notify(Ready::READABLE).unwrap();
+    notifier.clone().notify(Ready::WRITABLE).unwrap();
+    assert_eq!(max_timeout(&registration), Some(Duration::from_millis(0)));
+
+    poll::<_, io::Error>(&mut [&mut os_queue, &mut registration], &mut events, None).unwrap();
+    assert!(events.contains(&Event::new(WAKE_ID, Ready::READABLE)));
+    let registration_events: Vec<_> = events.iter()
+        .filter(|event| event.id() == REGISTRATION_ID)
+        .cloned()
+        .collect();
+    assert_eq!(registration_events, vec![
+        Event::new(REGISTRATION_ID, Ready::READABLE),
+        Event::new(REGISTRATION_ID, Ready::WRITABLE),
+    ]);
+    assert_eq!(max_timeout(&registration), None);
+}
+
+#[test]
+fn notify_from_other_thread() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+    let awakener = Awakener::new(&mut os_queue, WAKE_ID).unwrap();
+    let (mut registration, notifier) = Registration::new(&awakener, REGISTRATION_ID).unwrap();
+
+    let handle = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        notifier.notify(Ready::READABLE).unwrap();
+    });
+
+    // Block without a timeout, the notifier must wake us.
+    poll::<_, io::Error>(&mut [&mut os_queue, &mut registration], &mut events, None).unwrap();
+    handle.join().unwrap();
+
+    assert!(events.contains(&Event::new(REGISTRATION_ID, Ready::READABLE)));
+}
+
+#[test]
+fn dropping_registration() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+    let awakener = Awakener::new(&mut os_queue, WAKE_ID).unwrap();
+    let (registration, notifier) = Registration::new(&awakener, REGISTRATION_ID).unwrap();
+    assert!(!notifier.is_dead());
+
+    notifier.notify(Ready::READABLE).unwrap();
+    drop(registration);
+    assert!(notifier.is_dead());
+
+    let err = notifier.notify(Ready::READABLE).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotConnected);
+
+    // Only the first notification woke the `OsQueue`.
+    poll::<_, io::Error>(&mut [&mut os_queue], &mut events, Some(Duration::from_millis(100))).unwrap();
+    assert_eq!(events, vec![Event::new(WAKE_ID, Ready::READABLE)]);
+}
+
+#[test]
+fn dropping_registration_on_other_thread() {
+    init();
+    let mut os_queue = OsQueue::new().unwrap();
+    let awakener = Awakener::new(&mut os_queue, WAKE_ID).unwrap();
+    let (registration, notifier) = Registration::new(&awakener, REGISTRATION_ID).unwrap();
+
+    let handle = thread::spawn(move || {
+        loop {
+            match notifier.notify(Ready::READABLE) {
+                Ok(()) => thread::yield_now(),
+                Err(ref err) if err.kind() == io::ErrorKind::NotConnected => return,
+                Err(err) => panic!("unexpected error: {}", err),
+            }
+        }
+    });
+
+    thread::sleep(Duration::from_millis(10));
+    drop(registration);
+    handle.join().unwrap();
+}
+
+#[test]
+fn set_readiness() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+    let awakener = Awakener::new(&mut os_queue, WAKE_ID).unwrap();
+    let (mut registration, notifier) = Registration::new(&awakener, REGISTRATION_ID).unwrap();
+
+    notifier.set_readiness(Ready::READABLE).unwrap();
+    notifier.set_readiness(Ready::WRITABLE).unwrap();
+    notifier.set_readiness(Ready::READABLE).unwrap();
+    assert_eq!(max_timeout(&registration), Some(Duration::from_millis(0)));
+
+    poll::<_, io::Error>(&mut [&mut registration], &mut events, None).unwrap();
+    assert_eq!(events, vec![Event::new(REGISTRATION_ID, Ready::READABLE | Ready::WRITABLE)]);
+    assert_eq!(max_timeout(&registration), None);
+
+    // Combined with `notify`.
+    events.clear();
+    notifier.notify(Ready::ERROR).unwrap();
+    notifier.set_readiness(Ready::READABLE).unwrap();
+    poll::<_, io::Error>(&mut [&mut registration], &mut events, None).unwrap();
+    assert_eq!(events, vec![
+        Event::new(REGISTRATION_ID, Ready::READABLE),
+        Event::new(REGISTRATION_ID, Ready::ERROR),
+    ]);
+
+    // The `OsQueue` was woken.
+    events.clear();
+    poll::<_, io::Error>(&mut [&mut os_queue], &mut events, Some(Duration::from_millis(100))).unwrap();
+    assert!(events.contains(&Event::new(WAKE_ID, Ready::READABLE)));
+
+    drop(registration);
+    let err = notifier.set_readiness(Ready::READABLE).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotConnected);
+}
+
+#[test]
+fn set_readiness_from_other_thread() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+    let awakener = Awakener::new(&mut os_queue, WAKE_ID).unwrap();
+    let (mut registration, notifier) = Registration::new(&awakener, REGISTRATION_ID).unwrap();
+
+    let handle = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        notifier.set_readiness(Ready::WRITABLE).unwrap();
+    });
+
+    poll::<_, io::Error>(&mut [&mut os_queue, &mut registration], &mut events, None).unwrap();
+    handle.join().unwrap();
+    assert!(events.contains(&Event::new(REGISTRATION_ID, Ready::WRITABLE)));
+}
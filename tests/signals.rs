@@ -5,12 +5,12 @@ use std::process::{Child, Command, Stdio};
 use std::thread::sleep;
 use std::time::Duration;
 
-use gaea::event;
+use gaea::event::{self, Event, Ready};
 use gaea::os::{Signal, Signals, SignalSet};
 
 mod util;
 
-use self::util::init_with_os_queue;
+use self::util::{expect_events, init_with_os_queue};
 
 #[test]
 fn signal_bit_or() {
@@ -98,6 +98,23 @@ fn receive_no_signal() {
     assert_eq!(signals.receive().expect("unable to receive signal"), None);
 }
 
+#[test]
+fn receive_signal_raised() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    let id = event::Id(0);
+    let mut signals = Signals::new(&mut os_queue, Signal::Terminate.into(), id)
+        .expect("unable to create Signals");
+
+    // The signal is blocked on this thread by `Signals`, raise it to this
+    // thread only so the default action isn't taken by another thread.
+    let res = unsafe { libc::pthread_kill(libc::pthread_self(), libc::SIGTERM) };
+    assert_eq!(res, 0, "unable to raise signal: {}", io::Error::from_raw_os_error(res));
+
+    expect_events(&mut os_queue, &mut events, vec![Event::new(id, Ready::READABLE)]);
+    assert_eq!(signals.receive().expect("unable to receive signal"), Some(Signal::Terminate));
+}
+
 #[test]
 fn signals_example() {
     let child = run_example("signals");
@@ -11,7 +11,7 @@ use gaea::{event, poll};
 
 mod util;
 
-use self::util::{any_local_address, any_local_ipv6_address, assert_would_block, expect_events, init, init_with_os_queue};
+use self::util::{any_local_address, any_local_ipv6_address, assert_cloexec, assert_would_block, expect_events, init, init_with_os_queue};
 
 const ID1: event::Id = event::Id(0);
 const ID2: event::Id = event::Id(1);
@@ -187,6 +187,46 @@ fn tcp_listener_ttl() {
     assert!(listener.take_error().unwrap().is_none());
 }
 
+#[test]
+fn tcp_listener_backlog() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    let mut listener = TcpListener::bind_with_backlog(any_local_address(), 1024).unwrap();
+    os_queue.register(&mut listener, ID1, TcpListener::INTERESTS, RegisterOption::EDGE).unwrap();
+
+    // Burst of connections, none of which is accepted until all are made.
+    const N_CONNECTIONS: usize = 256;
+    let address = listener.local_addr().unwrap();
+    let streams: Vec<_> = (0..N_CONNECTIONS)
+        .map(|_| net::TcpStream::connect(address).unwrap())
+        .collect();
+
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(ID1, Ready::READABLE),
+    ]);
+
+    for _ in 0..N_CONNECTIONS {
+        let _ = listener.accept().unwrap();
+    }
+    assert_would_block(listener.accept());
+    drop(streams);
+}
+
+#[test]
+fn tcp_listener_sockopt() {
+    init();
+
+    let mut listener = TcpListener::bind(any_local_address()).unwrap();
+    // Set by `bind`.
+    let mut buf = [0; 4];
+    assert_eq!(listener.get_sockopt(libc::SOL_SOCKET, libc::SO_REUSEADDR, &mut buf).unwrap(), 4);
+    assert_ne!(i32::from_ne_bytes(buf), 0);
+
+    listener.set_sockopt(libc::SOL_SOCKET, libc::SO_REUSEADDR, &0i32.to_ne_bytes()).unwrap();
+    assert_eq!(listener.get_sockopt(libc::SOL_SOCKET, libc::SO_REUSEADDR, &mut buf).unwrap(), 4);
+    assert_eq!(i32::from_ne_bytes(buf), 0);
+}
+
 #[test]
 fn tcp_listener_raw_fd() {
     init();
@@ -511,3 +551,50 @@ fn start_connections(listener: &mut TcpListener, n_connections: usize, barrier:
         }
     })
 }
+
+#[test]
+fn tcp_listener_cloexec() {
+    init();
+    let listener = TcpListener::bind(any_local_address()).unwrap();
+    assert_cloexec(listener.as_raw_fd());
+}
+
+#[test]
+fn tcp_listener_accept_flags() {
+    init();
+
+    // Create the listener using the standard library.
+    let std_listener = net::TcpListener::bind(any_local_address()).unwrap();
+    std_listener.set_nonblocking(true).unwrap();
+    let address = std_listener.local_addr().unwrap();
+    let mut listener = unsafe { TcpListener::from_raw_fd(std_listener.into_raw_fd()) };
+
+    let stream = net::TcpStream::connect(address).unwrap();
+    let (accepted, peer_address) = loop {
+        match listener.accept() {
+            Ok(result) => break result,
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => continue,
+            Err(err) => panic!("unexpected error accepting: {}", err),
+        }
+    };
+    assert_eq!(peer_address, stream.local_addr().unwrap());
+
+    let fd = accepted.as_raw_fd();
+    assert_cloexec(fd);
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+    assert_ne!(flags & libc::O_NONBLOCK, 0, "accepted stream not in non-blocking mode");
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn tcp_listener_bind_device() {
+    init();
+
+    let mut listener = TcpListener::bind(any_local_address()).unwrap();
+    match listener.set_bind_device(Some("lo")) {
+        Ok(()) => listener.set_bind_device(None).unwrap(),
+        // Requires the `CAP_NET_RAW` capability.
+        Err(ref err) if err.kind() == io::ErrorKind::PermissionDenied => {},
+        Err(err) => panic!("unexpected error binding to device: {}", err),
+    }
+}
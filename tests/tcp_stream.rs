@@ -1,19 +1,21 @@
 use std::io::{self, Read, Write};
+use std::mem::MaybeUninit;
 use std::net::{self, Shutdown, SocketAddr};
 use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
+use std::slice;
 use std::sync::mpsc::channel;
 use std::sync::{Arc, Barrier};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use gaea::event::{Event, Ready};
-use gaea::net::TcpStream;
+use gaea::net::{ConnState, TcpListener, TcpStream};
 use gaea::os::{Interests, RegisterOption};
 use gaea::{event, poll};
 
 mod util;
 
-use self::util::{any_local_address, any_local_ipv6_address, assert_would_block, expect_events, init, init_with_os_queue};
+use self::util::{any_local_address, any_local_ipv6_address, assert_cloexec, assert_would_block, expect_events, init, init_with_os_queue};
 
 /// Data used in reading and writing tests.
 const DATA: &[u8] = b"Hello world!";
@@ -118,6 +120,35 @@ fn tcp_stream_ttl() {
     thread_handle.join().expect("unable to join thread");
 }
 
+#[test]
+fn tcp_stream_tos() {
+    init();
+
+    let (thread_handle, address) = start_listener(1, None);
+
+    let mut stream = TcpStream::connect(address).unwrap();
+
+    // Expedited forwarding.
+    const TOS: u32 = 46 << 2;
+    stream.set_tos(TOS).unwrap();
+    assert_eq!(stream.tos().unwrap(), TOS);
+    assert!(stream.take_error().unwrap().is_none());
+
+    thread_handle.join().expect("unable to join thread");
+}
+
+#[test]
+fn tcp_stream_tos_ipv6() {
+    init();
+
+    let listener = net::TcpListener::bind(any_local_ipv6_address()).unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+
+    const TOS: u32 = 46 << 2;
+    stream.set_tos(TOS).unwrap();
+    assert_eq!(stream.tos().unwrap(), TOS);
+}
+
 #[test]
 fn tcp_stream_nodelay() {
     init();
@@ -134,6 +165,19 @@ fn tcp_stream_nodelay() {
     thread_handle.join().expect("unable to join thread");
 }
 
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn tcp_stream_user_timeout() {
+    init();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+
+    assert_eq!(stream.user_timeout().unwrap(), Duration::from_millis(0));
+    stream.set_user_timeout(Duration::from_millis(1500)).unwrap();
+    assert_eq!(stream.user_timeout().unwrap(), Duration::from_millis(1500));
+}
+
 #[test]
 fn tcp_stream_peek() {
     let (mut os_queue, mut events) = init_with_os_queue();
@@ -290,6 +334,75 @@ fn tcp_stream_read() {
     thread_handle.join().expect("unable to join thread");
 }
 
+#[test]
+fn tcp_stream_read_uninit() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (mut peer, _) = listener.accept().unwrap();
+    os_queue.register(&mut stream, ID1, Interests::READABLE, RegisterOption::EDGE)
+        .expect("unable to register TCP stream");
+
+    let mut buf: [MaybeUninit<u8>; 20] = unsafe { MaybeUninit::uninit().assume_init() };
+    assert_would_block(stream.read_uninit(&mut buf));
+
+    assert_eq!(peer.write(DATA).unwrap(), DATA.len());
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(ID1, Ready::READABLE),
+    ]);
+
+    let n = stream.read_uninit(&mut buf).unwrap();
+    assert_eq!(n, DATA.len());
+    let data = unsafe { slice::from_raw_parts(buf.as_ptr() as *const u8, n) };
+    assert_eq!(data, DATA);
+
+    assert_would_block(stream.read_uninit(&mut buf));
+
+    // Reading after the peer closed the connection returns zero bytes.
+    drop(peer);
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(ID1, Ready::READABLE),
+    ]);
+    assert_eq!(stream.read_uninit(&mut buf).unwrap(), 0);
+}
+
+#[test]
+fn tcp_stream_readable_hint() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (mut peer, _) = listener.accept().unwrap();
+    assert!(stream.is_readable_hint());
+
+    let mut buf = [0; 20];
+    assert_would_block(stream.read(&mut buf));
+    assert!(!stream.is_readable_hint());
+
+    // Registering clears the hint.
+    os_queue.register(&mut stream, ID1, Interests::READABLE, RegisterOption::EDGE).unwrap();
+    assert!(stream.is_readable_hint());
+    assert_would_block(stream.peek(&mut buf));
+    assert!(!stream.is_readable_hint());
+
+    // Events without readable readiness don't clear it.
+    stream.update_readiness_hint(&Event::new(ID1, Ready::WRITABLE));
+    assert!(!stream.is_readable_hint());
+
+    assert_eq!(peer.write(DATA).unwrap(), DATA.len());
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(ID1, Ready::READABLE),
+    ]);
+    stream.update_readiness_hint(&Event::new(ID1, Ready::READABLE));
+    assert!(stream.is_readable_hint());
+
+    assert_eq!(stream.read(&mut buf).unwrap(), DATA.len());
+    assert!(stream.is_readable_hint());
+    assert_would_block(stream.read(&mut buf));
+    assert!(!stream.is_readable_hint());
+}
+
 // TODO: add test to check that writing is non-blocking.
 #[test]
 fn tcp_stream_write() {
@@ -323,6 +436,248 @@ fn tcp_stream_write() {
     thread_handle.join().expect("unable to join thread");
 }
 
+#[test]
+fn tcp_stream_fastopen() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    let mut listener = TcpListener::bind(any_local_address()).unwrap();
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    listener.set_fastopen(16).unwrap();
+    let address = listener.local_addr().unwrap();
+
+    // Whether or not the data is send in the SYN packet depends on the OS
+    // configuration, either way the stream must work like a regular stream.
+    let (mut stream, n) = TcpStream::connect_fastopen(address, DATA).unwrap();
+    assert!(n <= DATA.len());
+    os_queue.register(&mut stream, ID1, Interests::WRITABLE, RegisterOption::EDGE).unwrap();
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(ID1, Ready::WRITABLE),
+    ]);
+    stream.write_all(&DATA[n..]).unwrap();
+
+    let (mut server_stream, _) = listener.accept().unwrap();
+    os_queue.register(&mut server_stream, ID2, Interests::READABLE, RegisterOption::EDGE).unwrap();
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(ID2, Ready::READABLE),
+    ]);
+
+    let mut buf = [0; 20];
+    let n = server_stream.read(&mut buf).unwrap();
+    assert_eq!(&buf[..n], DATA);
+}
+
+#[test]
+fn tcp_stream_set_nonblocking() {
+    init();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (mut peer, _) = listener.accept().unwrap();
+
+    let mut buf = [0; 20];
+    assert_would_block(stream.read(&mut buf));
+
+    stream.set_nonblocking(false).unwrap();
+    let thread_handle = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        peer.write_all(DATA).unwrap();
+        peer
+    });
+
+    // Blocks until the data is written.
+    let n = stream.read(&mut buf).unwrap();
+    assert_eq!(&buf[..n], DATA);
+    let _peer = thread_handle.join().unwrap();
+
+    // Handing it off as a blocking standard library stream.
+    let mut stream = unsafe { net::TcpStream::from_raw_fd(stream.into_raw_fd()) };
+    stream.set_read_timeout(Some(Duration::from_millis(10))).unwrap();
+    let err = stream.read(&mut buf).unwrap_err();
+    // Timed out, rather than `WouldBlock` from a non-blocking socket, but
+    // some platforms report a timeout using `WouldBlock`.
+    assert!(err.kind() == io::ErrorKind::WouldBlock || err.kind() == io::ErrorKind::TimedOut);
+
+    let mut stream = unsafe { TcpStream::from_raw_fd(stream.into_raw_fd()) };
+    stream.set_nonblocking(true).unwrap();
+    assert_would_block(stream.read(&mut buf));
+}
+
+/// Returns an address on which no one is listening.
+fn refused_address() -> SocketAddr {
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    listener.local_addr().unwrap()
+}
+
+#[test]
+fn tcp_stream_happy_eyeballs() {
+    init();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let address = listener.local_addr().unwrap();
+
+    // First address refuses the connection, the second attempt is started
+    // immediately after it.
+    let start = Instant::now();
+    let addresses = [refused_address(), address];
+    let mut stream = TcpStream::connect_happy_eyeballs(&addresses, Duration::from_secs(10)).unwrap();
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert_eq!(stream.peer_addr().unwrap(), address);
+
+    // The stream is connected and usable.
+    let (mut peer, _) = listener.accept().unwrap();
+    peer.write_all(DATA).unwrap();
+    stream.set_nonblocking(false).unwrap();
+    let mut buf = [0; 20];
+    let n = stream.read(&mut buf).unwrap();
+    assert_eq!(&buf[..n], DATA);
+
+    // No other connections were made.
+    listener.set_nonblocking(true).unwrap();
+    assert_would_block(listener.accept());
+}
+
+#[test]
+fn tcp_stream_happy_eyeballs_ipv6() {
+    init();
+
+    let listener = net::TcpListener::bind(any_local_ipv6_address()).unwrap();
+    let address = listener.local_addr().unwrap();
+
+    // The IPv6 address is tried second, interleaved with the IPv4 addresses.
+    let addresses = [refused_address(), refused_address(), address];
+    let mut stream = TcpStream::connect_happy_eyeballs(&addresses, Duration::from_millis(10)).unwrap();
+    assert_eq!(stream.peer_addr().unwrap(), address);
+}
+
+#[test]
+fn tcp_stream_happy_eyeballs_all_fail() {
+    init();
+
+    let addresses = [refused_address(), refused_address()];
+    let err = TcpStream::connect_happy_eyeballs(&addresses, Duration::from_millis(10)).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::ConnectionRefused);
+
+    let err = TcpStream::connect_happy_eyeballs(&[], Duration::from_millis(10)).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn tcp_stream_poll_connect() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+
+    os_queue.register(&mut stream, ID1, Interests::WRITABLE, RegisterOption::EDGE)
+        .expect("unable to register TCP stream");
+    expect_events(&mut os_queue, &mut events, vec![Event::new(ID1, Ready::WRITABLE)]);
+    assert!(stream.poll_connect().unwrap());
+    // Should remain connected.
+    assert!(stream.poll_connect().unwrap());
+}
+
+#[test]
+fn tcp_stream_poll_connect_refused() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    let mut stream = TcpStream::connect(refused_address()).unwrap();
+
+    os_queue.register(&mut stream, ID1, Interests::WRITABLE, RegisterOption::EDGE)
+        .expect("unable to register TCP stream");
+    poll::<_, io::Error>(&mut [&mut os_queue], &mut events, None).unwrap();
+    assert!(events.iter().any(|event| event.id() == ID1));
+
+    let err = stream.poll_connect().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::ConnectionRefused);
+}
+
+#[test]
+fn tcp_stream_take_errors() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+    assert!(!os_queue.is_taking_errors());
+    os_queue.set_take_errors(true);
+    assert!(os_queue.is_taking_errors());
+
+    let mut stream = TcpStream::connect(refused_address()).unwrap();
+    os_queue.register(&mut stream, ID1, Interests::WRITABLE, RegisterOption::EDGE).unwrap();
+    poll::<_, io::Error>(&mut [&mut os_queue], &mut events, None).unwrap();
+
+    let event = events.iter().find(|event| event.id() == ID1).unwrap();
+    assert!(event.readiness().is_error());
+    assert_eq!(event.error(), Some(libc::ECONNREFUSED));
+    // On Linux the error is taken from the socket.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    assert!(stream.take_error().unwrap().is_none());
+}
+
+#[test]
+fn tcp_stream_had_error() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    let mut stream = TcpStream::connect(refused_address()).unwrap();
+    os_queue.register(&mut stream, ID1, Interests::WRITABLE, RegisterOption::LEVEL).unwrap();
+    poll::<_, io::Error>(&mut [&mut os_queue], &mut events, None).unwrap();
+
+    // Reading the error clears it.
+    assert!(stream.had_error().unwrap());
+    assert!(!stream.had_error().unwrap());
+    assert!(stream.take_error().unwrap().is_none());
+}
+
+#[test]
+fn tcp_stream_connection_state() {
+    init();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+
+    let tests = &[
+        (Ready::READABLE | Ready::WRITABLE, ConnState::Open),
+        (Ready::READABLE | Ready::READ_HUP, ConnState::ReadClosed),
+        (Ready::HUP | Ready::READ_HUP, ConnState::Closed),
+        (Ready::WRITABLE | Ready::ERROR, ConnState::Closed),
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        (Ready::HUP, ConnState::Closed),
+        #[cfg(not(any(target_os = "android", target_os = "linux")))]
+        (Ready::HUP, ConnState::WriteClosed),
+    ];
+    for &(readiness, expected) in tests {
+        let event = Event::new(ID1, readiness);
+        assert_eq!(stream.connection_state(&event), expected, "readiness: {:?}", readiness);
+    }
+
+    assert!(ConnState::Open.can_read() && ConnState::Open.can_write());
+    assert!(!ConnState::ReadClosed.can_read() && ConnState::ReadClosed.can_write());
+    assert!(ConnState::WriteClosed.can_read() && !ConnState::WriteClosed.can_write());
+    assert!(!ConnState::Closed.can_read() && !ConnState::Closed.can_write());
+}
+
+#[test]
+fn tcp_stream_connection_state_half_close() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (mut peer, _) = listener.accept().unwrap();
+
+    os_queue.register(&mut stream, ID1, Interests::READABLE, RegisterOption::LEVEL)
+        .expect("unable to register TCP stream");
+
+    // Peer closes its writing side.
+    peer.shutdown(Shutdown::Write).unwrap();
+    poll::<_, io::Error>(&mut [&mut os_queue], &mut events, None).unwrap();
+    let event = events.iter().find(|event| event.id() == ID1).expect("missing event");
+    let state = stream.connection_state(event);
+    assert_eq!(state, ConnState::ReadClosed);
+
+    // Writing should still be possible.
+    assert!(state.can_write());
+    stream.write_all(DATA).unwrap();
+    let mut buf = [0; 20];
+    let n = peer.read(&mut buf).unwrap();
+    assert_eq!(&buf[..n], DATA);
+}
+
 #[test]
 fn tcp_stream_raw_fd() {
     init();
@@ -669,3 +1024,32 @@ fn start_listener(n_connections: usize, barrier: Option<Arc<Barrier>>) -> (threa
     });
     (thread_handle, receiver.recv().unwrap())
 }
+
+#[test]
+fn tcp_stream_cloexec() {
+    init();
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    assert_cloexec(stream.as_raw_fd());
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn tcp_stream_connect_with_device() {
+    init();
+
+    let listener = net::TcpListener::bind(any_local_address()).unwrap();
+    let address = listener.local_addr().unwrap();
+    match TcpStream::connect_with_device(address, "lo") {
+        Ok(mut stream) => {
+            let (_, peer_address) = listener.accept().unwrap();
+            assert_eq!(stream.local_addr().unwrap(), peer_address);
+            assert_eq!(stream.peer_addr().unwrap(), address);
+        },
+        // Requires the `CAP_NET_RAW` capability.
+        Err(ref err) if err.kind() == io::ErrorKind::PermissionDenied => return,
+        Err(err) => panic!("unexpected error binding to device: {}", err),
+    }
+
+    assert!(TcpStream::connect_with_device(address, "not a valid interface").is_err());
+}
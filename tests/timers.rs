@@ -1,8 +1,11 @@
+use std::cell::Cell;
 use std::thread::sleep;
 use std::time::{Duration, Instant};
 
 use gaea::event::{self, Capacity, Event, Ready, Source};
-use gaea::Timers;
+use gaea::{Clock, Timers};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use gaea::CoarseClock;
 
 mod util;
 
@@ -39,6 +42,87 @@ fn timers() {
     expect_no_events(&mut timers);
 }
 
+#[test]
+fn timers_next_deadline() {
+    init();
+    let mut timers = Timers::new();
+    assert_eq!(timers.next_deadline(), None);
+
+    let deadline = Instant::now() + Duration::from_millis(100);
+    timers.add_deadline(event::Id(0), deadline);
+    assert_eq!(timers.next_deadline(), Some(deadline));
+
+    // Should return the soonest deadline.
+    let deadline2 = deadline - Duration::from_millis(50);
+    timers.add_deadline(event::Id(1), deadline2);
+    assert_eq!(timers.next_deadline(), Some(deadline2));
+
+    timers.remove_deadline(event::Id(1));
+    assert_eq!(timers.next_deadline(), Some(deadline));
+    timers.remove_deadline(event::Id(0));
+    assert_eq!(timers.next_deadline(), None);
+}
+
+#[test]
+fn timers_add_deadline_with() {
+    init();
+    let mut timers = Timers::new();
+    let mut events = Vec::new();
+    let id = event::Id(0);
+
+    let deadline = Instant::now();
+    timers.add_deadline_with(id, deadline, Ready::TIMER | Ready::READABLE);
+    timers.add_deadline_with(id, deadline + Duration::from_millis(10), Ready::TIMER | Ready::WRITABLE);
+    expect_events(&mut timers, &mut events, vec![
+        Event::new(id, Ready::TIMER | Ready::READABLE),
+    ]);
+
+    sleep(Duration::from_millis(10));
+    expect_events(&mut timers, &mut events, vec![
+        Event::new(id, Ready::TIMER | Ready::WRITABLE),
+    ]);
+    expect_no_events(&mut timers);
+}
+
+#[test]
+fn timers_many_deadlines() {
+    init();
+    let mut timers = Timers::new();
+    const N: usize = 10_000;
+
+    // Add the deadlines in reverse order, all already passed.
+    let start = Instant::now();
+    let now = Instant::now();
+    for n in (0..N).rev() {
+        timers.add_deadline(event::Id(n), now - Duration::from_millis(N as u64 - n as u64));
+    }
+    assert_eq!(timers.next_deadline(), Some(now - Duration::from_millis(N as u64)));
+
+    let mut events = Vec::with_capacity(N);
+    Source::<_, ()>::poll(&mut timers, &mut events).unwrap();
+    let elapsed = start.elapsed();
+
+    assert_eq!(events.len(), N);
+    for (n, event) in events.iter().enumerate() {
+        assert_eq!(*event, Event::new(event::Id(n), Ready::TIMER));
+    }
+    assert_eq!(timers.next_deadline(), None);
+    // Should be well within this limit, even in debug mode, with a `O(log n)`
+    // implementation.
+    assert!(elapsed < Duration::from_secs(1), "adding and polling deadlines took {:?}", elapsed);
+}
+
+#[test]
+fn timers_add_timeout_saturates() {
+    init();
+    let mut timers = Timers::new();
+
+    // Shouldn't panic on overflow.
+    timers.add_timeout(event::Id(0), Duration::from_secs(u64::max_value()));
+    assert!(max_timeout(&timers).unwrap() > Duration::from_secs(60 * 60 * 24 * 365));
+    expect_no_events(&mut timers);
+}
+
 #[test]
 fn timers_multiple_deadlines_same_time() {
     init();
@@ -118,6 +202,54 @@ fn timers_remove_deadline() {
     expect_no_events(&mut timers);
 }
 
+#[test]
+fn timers_interval() {
+    init();
+    let mut timers = Timers::new();
+    let mut events = Vec::new();
+    let id = event::Id(0);
+
+    let interval = Duration::from_millis(20);
+    timers.add_interval(id, interval);
+    roughly_equal(max_timeout(&timers).unwrap(), interval);
+    expect_no_events(&mut timers);
+
+    // Should trigger after each interval.
+    for _ in 0..3 {
+        sleep(interval);
+        expect_events(&mut timers, &mut events, vec![Event::new(id, Ready::TIMER)]);
+    }
+
+    // Missed ticks should be skipped, only returning a single event.
+    sleep(interval * 3);
+    events.clear();
+    Source::<_, ()>::poll(&mut timers, &mut events).unwrap();
+    assert_eq!(events, vec![Event::new(id, Ready::TIMER)]);
+    assert!(max_timeout(&timers).unwrap() <= interval);
+
+    // After removing the interval no more events should be triggered.
+    timers.remove_interval(id);
+    assert_eq!(max_timeout(&timers), None);
+    sleep(interval);
+    expect_no_events(&mut timers);
+}
+
+#[test]
+fn timers_remove_interval_keeps_deadline() {
+    init();
+    let mut timers = Timers::new();
+    let mut events = Vec::new();
+    let id = event::Id(0);
+
+    timers.add_deadline(id, Instant::now());
+    timers.add_interval(id, Duration::from_millis(10));
+    timers.remove_interval(id);
+
+    // Only the deadline, not the interval, should have been removed.
+    expect_events(&mut timers, &mut events, vec![Event::new(id, Ready::TIMER)]);
+    assert_eq!(max_timeout(&timers), None);
+}
+
 #[test]
 fn timers_events_capacity() {
     init();
@@ -153,6 +285,225 @@ fn timers_events_capacity() {
     assert_eq!(events.1, 2);
 }
 
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn timers_coarse_clock() {
+    init();
+    let clock = CoarseClock::new();
+    let resolution = clock.resolution();
+    assert!(resolution > Duration::from_millis(0));
+
+    // Never ahead of the system clock, and lagging behind at most twice the
+    // resolution (plus some slack for the time between the two calls).
+    for _ in 0..10 {
+        let coarse = clock.now();
+        let now = Instant::now();
+        assert!(coarse <= now, "coarse clock ahead of system clock");
+        assert!(now - coarse <= resolution * 2 + Duration::from_millis(5));
+        sleep(Duration::from_millis(1));
+    }
+
+    let mut timers = Timers::with_clock(clock);
+    let mut events = Vec::new();
+    let id = event::Id(0);
+    let timeout = Duration::from_millis(10);
+    timers.add_timeout(id, timeout);
+    expect_no_events(&mut timers);
+    sleep(timeout + resolution * 2);
+    expect_events(&mut timers, &mut events, vec![Event::new(id, Ready::TIMER)]);
+}
+
+#[test]
+fn timers_mock_clock() {
+    init();
+    let start = Instant::now();
+    let mut timers = Timers::with_clock(MockClock::new(start));
+    let mut events = Vec::new();
+    let id = event::Id(0);
+
+    let timeout = Duration::from_secs(10);
+    timers.add_timeout(id, timeout);
+    assert_eq!(timers.next_deadline(), Some(start + timeout));
+    assert_eq!(max_timeout(&timers), Some(timeout));
+    expect_no_events(&mut timers);
+
+    // One nanosecond before the deadline nothing should happen.
+    timers.clock().set(start + timeout - Duration::from_nanos(1));
+    assert_eq!(max_timeout(&timers), Some(Duration::from_nanos(1)));
+    expect_no_events(&mut timers);
+
+    // But at exactly the deadline the event should be returned.
+    timers.clock().set(start + timeout);
+    assert_eq!(max_timeout(&timers), Some(Duration::from_millis(0)));
+    expect_events(&mut timers, &mut events, vec![Event::new(id, Ready::TIMER)]);
+    assert_eq!(max_timeout(&timers), None);
+}
+
+#[test]
+fn timers_interval_mock_clock() {
+    init();
+    let start = Instant::now();
+    let mut timers = Timers::with_clock(MockClock::new(start));
+    let mut events = Vec::new();
+    let id = event::Id(0);
+
+    let interval = Duration::from_secs(1);
+    timers.add_interval(id, interval);
+
+    for n in 1..4 {
+        timers.clock().advance(interval);
+        expect_events(&mut timers, &mut events, vec![Event::new(id, Ready::TIMER)]);
+        assert_eq!(timers.next_deadline(), Some(start + interval * (n + 1)));
+    }
+
+    // Polling late shouldn't cause drifting.
+    timers.clock().advance(interval + interval / 2);
+    expect_events(&mut timers, &mut events, vec![Event::new(id, Ready::TIMER)]);
+    assert_eq!(timers.next_deadline(), Some(start + interval * 5));
+    assert_eq!(max_timeout(&timers), Some(interval / 2));
+
+    // Missed ticks should be skipped.
+    timers.clock().advance(interval * 3);
+    expect_events(&mut timers, &mut events, vec![Event::new(id, Ready::TIMER)]);
+    assert_eq!(timers.next_deadline(), Some(start + interval * 8));
+}
+
+#[test]
+fn timers_interval_many_missed_ticks() {
+    init();
+    let start = Instant::now();
+    let mut timers = Timers::with_clock(MockClock::new(start));
+    let mut events = Vec::new();
+    let id = event::Id(0);
+
+    let interval = Duration::from_nanos(2);
+    timers.add_interval(id, interval);
+
+    // Misses more ticks than fit in a `u32`.
+    let elapsed = Duration::from_secs(10) + Duration::from_nanos(1);
+    timers.clock().advance(elapsed);
+    expect_events(&mut timers, &mut events, vec![Event::new(id, Ready::TIMER)]);
+    assert_eq!(timers.next_deadline(), Some(start + elapsed + Duration::from_nanos(1)));
+}
+
+#[test]
+fn timers_take_expired() {
+    init();
+    let start = Instant::now();
+    let mut timers = Timers::with_clock(MockClock::new(start));
+    let interval = Duration::from_secs(1);
+
+    timers.add_deadline(event::Id(0), start + interval);
+    timers.add_deadline(event::Id(1), start + interval * 10);
+    timers.add_interval(event::Id(2), interval);
+    assert_eq!(timers.take_expired().next(), None);
+
+    timers.clock().advance(interval);
+    let expired: Vec<_> = timers.take_expired().collect();
+    assert_eq!(expired, vec![(event::Id(0), start + interval), (event::Id(2), start + interval)]);
+    assert_eq!(timers.next_deadline(), Some(start + interval * 2));
+
+    // Missed ticks: the first missed deadline is returned, only once.
+    timers.clock().advance(interval * 3 + interval / 2);
+    let expired: Vec<_> = timers.take_expired().collect();
+    assert_eq!(expired, vec![(event::Id(2), start + interval * 2)]);
+    assert_eq!(timers.next_deadline(), Some(start + interval * 5));
+
+    // Unconsumed deadlines remain.
+    timers.clock().set(start + interval * 10);
+    assert_eq!(timers.take_expired().next(), Some((event::Id(2), start + interval * 5)));
+    let mut events = Vec::new();
+    expect_events(&mut timers, &mut events, vec![Event::new(event::Id(1), Ready::TIMER)]);
+    assert_eq!(timers.next_deadline(), Some(start + interval * 11));
+}
+
+#[test]
+fn timers_len_and_iter() {
+    init();
+    let start = Instant::now();
+    let mut timers = Timers::with_clock(MockClock::new(start));
+    assert_eq!(timers.len(), 0);
+    assert!(timers.is_empty());
+    assert_eq!(timers.iter().next(), None);
+
+    let interval = Duration::from_secs(1);
+    timers.add_deadline(event::Id(0), start + interval * 2);
+    timers.add_deadline(event::Id(1), start + interval);
+    timers.add_interval(event::Id(2), interval * 3);
+    assert_eq!(timers.len(), 3);
+    assert!(!timers.is_empty());
+    assert_eq!(timers.iter().len(), 3);
+
+    // Returned in deadline order.
+    assert_eq!(timers.iter().collect::<Vec<_>>(), vec![
+        (event::Id(1), start + interval),
+        (event::Id(0), start + interval * 2),
+        (event::Id(2), start + interval * 3),
+    ]);
+
+    timers.remove_deadline(event::Id(0));
+    assert_eq!(timers.len(), 2);
+    assert!(timers.iter().all(|(id, _)| id != event::Id(0)));
+
+    // Intervals remain pending after they expire.
+    timers.clock().advance(interval * 3);
+    let mut events = Vec::new();
+    expect_events(&mut timers, &mut events, vec![
+        Event::new(event::Id(1), Ready::TIMER),
+        Event::new(event::Id(2), Ready::TIMER),
+    ]);
+    assert_eq!(timers.iter().collect::<Vec<_>>(), vec![(event::Id(2), start + interval * 6)]);
+}
+
+#[test]
+fn timers_remove_all() {
+    init();
+    let start = Instant::now();
+    let mut timers = Timers::with_clock(MockClock::new(start));
+    let id = event::Id(0);
+    let other_id = event::Id(1);
+
+    let timeout = Duration::from_secs(1);
+    timers.add_deadline(id, start + timeout);
+    timers.add_timeout(id, timeout * 2);
+    timers.add_interval(id, timeout * 3);
+    timers.add_deadline(other_id, start + timeout);
+    assert_eq!(timers.len(), 4);
+
+    assert_eq!(timers.remove_all(id), 3);
+    assert_eq!(timers.len(), 1);
+    assert_eq!(timers.remove_all(id), 0);
+
+    timers.clock().advance(timeout * 10);
+    let mut events = Vec::new();
+    expect_events(&mut timers, &mut events, vec![Event::new(other_id, Ready::TIMER)]);
+    assert!(timers.is_empty());
+}
+
+/// Clock that only advances manually.
+#[derive(Debug)]
+struct MockClock(Cell<Instant>);
+
+impl MockClock {
+    fn new(now: Instant) -> MockClock {
+        MockClock(Cell::new(now))
+    }
+
+    fn set(&self, now: Instant) {
+        self.0.set(now);
+    }
+
+    fn advance(&self, duration: Duration) {
+        self.0.set(self.0.get() + duration);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.0.get()
+    }
+}
+
 /// Assert that `left` and `right` are roughly equal, with a margin of
 /// `DURATION_MARGIN` difference.
 fn roughly_equal(left: Duration, right: Duration) {
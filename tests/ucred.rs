@@ -0,0 +1,23 @@
+use std::os::unix::net::UnixStream;
+
+use gaea::unix::peer_cred;
+
+mod util;
+
+use self::util::init;
+
+#[test]
+fn peer_cred_socket_pair() {
+    init();
+
+    let (stream1, stream2) = UnixStream::pair().expect("unable to create socket pair");
+
+    let cred1 = peer_cred(&stream1).expect("unable to get peer credentials");
+    let cred2 = peer_cred(&stream2).expect("unable to get peer credentials");
+    assert_eq!(cred1, cred2);
+
+    assert_eq!(cred1.uid, unsafe { libc::getuid() });
+    assert_eq!(cred1.gid, unsafe { libc::getgid() });
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    assert_eq!(cred1.pid, Some(std::process::id() as libc::pid_t));
+}
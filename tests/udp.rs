@@ -1,6 +1,10 @@
 use std::io;
-use std::net::{self, SocketAddr};
+#[cfg(feature = "nightly")]
+use std::io::IoSliceMut;
+use std::mem::MaybeUninit;
+use std::net::{self, IpAddr, SocketAddr};
 use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
+use std::slice;
 use std::sync::{Arc, Barrier};
 use std::thread::{self, sleep};
 use std::time::Duration;
@@ -641,3 +645,283 @@ fn send_packets(address: SocketAddr, n_packets: usize, barrier: Arc<Barrier>) ->
         }
     })
 }
+
+#[test]
+fn udp_socket_send_recv_mmsg() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    let mut socket1 = UdpSocket::bind(any_local_address()).unwrap();
+    let mut socket2 = UdpSocket::bind(any_local_address()).unwrap();
+
+    let address1 = socket1.local_addr().unwrap();
+    let address2 = socket2.local_addr().unwrap();
+
+    os_queue.register(&mut socket2, ID2, Interests::READABLE, RegisterOption::EDGE)
+        .expect("unable to register UDP socket");
+
+    // Nothing to receive yet.
+    let mut buf1 = [0; 20];
+    let mut buf2 = [0; 20];
+    let mut buf3 = [0; 20];
+    {
+        let mut bufs = [(&mut buf1[..], address1, false)];
+        assert_would_block(socket2.recv_mmsg(&mut bufs));
+    }
+
+    let bufs = [(DATA1, address2), (DATA2, address2)];
+    assert_eq!(socket1.send_mmsg(&bufs).unwrap(), 2);
+
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(ID2, Ready::READABLE),
+    ]);
+
+    let unspecified = any_local_ipv6_address();
+    let mut bufs = [
+        (&mut buf1[..], unspecified, true),
+        (&mut buf2[..], unspecified, true),
+        (&mut buf3[..], unspecified, true),
+    ];
+    assert_eq!(socket2.recv_mmsg(&mut bufs).unwrap(), 2);
+    assert_eq!(bufs[0].0, DATA1);
+    assert_eq!(bufs[0].1, address1);
+    assert!(!bufs[0].2);
+    assert_eq!(bufs[1].0, DATA2);
+    assert_eq!(bufs[1].1, address1);
+    assert!(!bufs[1].2);
+    // Unused entries should be untouched.
+    assert_eq!(bufs[2].0.len(), 20);
+    assert_eq!(bufs[2].1, unspecified);
+    assert!(bufs[2].2);
+
+    assert_would_block(socket2.recv_mmsg(&mut bufs));
+}
+
+#[test]
+fn udp_socket_recv_mmsg_truncated() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    let mut socket1 = UdpSocket::bind(any_local_address()).unwrap();
+    let mut socket2 = UdpSocket::bind(any_local_address()).unwrap();
+
+    let address1 = socket1.local_addr().unwrap();
+    let address2 = socket2.local_addr().unwrap();
+
+    os_queue.register(&mut socket2, ID2, Interests::READABLE, RegisterOption::EDGE)
+        .expect("unable to register UDP socket");
+
+    let bufs = [(DATA1, address2), (DATA2, address2)];
+    assert_eq!(socket1.send_mmsg(&bufs).unwrap(), 2);
+
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(ID2, Ready::READABLE),
+    ]);
+
+    // First buffer is too small for the datagram.
+    let mut buf1 = [0; 4];
+    let mut buf2 = [0; 20];
+    let unspecified = any_local_ipv6_address();
+    let mut bufs = [
+        (&mut buf1[..], unspecified, false),
+        (&mut buf2[..], unspecified, false),
+    ];
+    assert_eq!(socket2.recv_mmsg(&mut bufs).unwrap(), 2);
+    assert_eq!(bufs[0].0, &DATA1[..4]);
+    assert_eq!(bufs[0].1, address1);
+    assert!(bufs[0].2);
+    assert_eq!(bufs[1].0, DATA2);
+    assert_eq!(bufs[1].1, address1);
+    assert!(!bufs[1].2);
+}
+
+#[test]
+fn udp_socket_mmsg_empty() {
+    init();
+
+    let mut socket = UdpSocket::bind(any_local_address()).unwrap();
+    assert_eq!(socket.send_mmsg(&[]).unwrap(), 0);
+    assert_eq!(socket.recv_mmsg(&mut []).unwrap(), 0);
+}
+
+#[test]
+fn udp_socket_tos() {
+    init();
+
+    const TOS: u32 = 46 << 2;
+    for address in &[any_local_address(), any_local_ipv6_address()] {
+        let mut socket = UdpSocket::bind(*address).unwrap();
+        socket.set_tos(TOS).unwrap();
+        assert_eq!(socket.tos().unwrap(), TOS);
+    }
+}
+
+#[test]
+fn udp_socket_sockopt() {
+    init();
+
+    let mut socket = UdpSocket::bind(any_local_address()).unwrap();
+    const TOS: i32 = 46 << 2;
+    socket.set_sockopt(libc::IPPROTO_IP, libc::IP_TOS, &TOS.to_ne_bytes()).unwrap();
+    assert_eq!(socket.tos().unwrap(), TOS as u32);
+
+    let mut buf = [0; 4];
+    assert_eq!(socket.get_sockopt(libc::IPPROTO_IP, libc::IP_TOS, &mut buf).unwrap(), 4);
+    assert_eq!(i32::from_ne_bytes(buf), TOS);
+
+    // Invalid option.
+    assert!(socket.set_sockopt(libc::IPPROTO_IP, -1, &TOS.to_ne_bytes()).is_err());
+}
+
+#[test]
+fn udp_socket_recv_from_with_info() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    let tests: &[(&str, &str)] = &[("0.0.0.0:0", "127.0.0.1"), ("[::]:0", "::1")];
+    for &(bind_address, destination) in tests {
+        let mut socket = UdpSocket::bind(bind_address.parse().unwrap()).unwrap();
+        socket.set_pktinfo(true).unwrap();
+        let destination: IpAddr = destination.parse().unwrap();
+        let address = SocketAddr::new(destination, socket.local_addr().unwrap().port());
+
+        os_queue.register(&mut socket, ID1, Interests::READABLE, RegisterOption::EDGE).unwrap();
+
+        let mut buf = [0; 20];
+        assert_would_block(socket.recv_from_with_info(&mut buf));
+
+        let sender = net::UdpSocket::bind(SocketAddr::new(destination, 0)).unwrap();
+        sender.send_to(DATA1, address).unwrap();
+
+        expect_events(&mut os_queue, &mut events, vec![
+            Event::new(ID1, Ready::READABLE),
+        ]);
+
+        let (n, source, dest) = socket.recv_from_with_info(&mut buf).unwrap();
+        assert_eq!(&buf[..n], DATA1);
+        assert_eq!(source, sender.local_addr().unwrap());
+        assert_eq!(dest, destination);
+
+        os_queue.deregister(&mut socket).unwrap();
+    }
+}
+
+#[test]
+fn udp_socket_recv_from_with_info_disabled() {
+    init();
+
+    let mut socket = UdpSocket::bind(any_local_address()).unwrap();
+    let sender = net::UdpSocket::bind(any_local_address()).unwrap();
+    sender.send_to(DATA1, socket.local_addr().unwrap()).unwrap();
+    sleep(Duration::from_millis(10));
+
+    let mut buf = [0; 20];
+    assert_error(socket.recv_from_with_info(&mut buf), "pktinfo");
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn udp_socket_recv_from_with_info_control_truncated() {
+    init();
+
+    let mut socket = UdpSocket::bind("[::1]:0".parse().unwrap()).unwrap();
+    socket.set_pktinfo(true).unwrap();
+    // Timestamps are added before the packet info, leaving no space for the
+    // latter.
+    socket.set_sockopt(libc::SOL_SOCKET, libc::SO_TIMESTAMPNS, &1i32.to_ne_bytes()).unwrap();
+    let sender = net::UdpSocket::bind("[::1]:0").unwrap();
+    sender.send_to(DATA1, socket.local_addr().unwrap()).unwrap();
+    sleep(Duration::from_millis(10));
+
+    let mut buf = [0; 20];
+    assert_error(socket.recv_from_with_info(&mut buf), "control messages truncated");
+}
+
+#[test]
+#[cfg(feature = "nightly")]
+fn udp_socket_recv_from_vectored() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    let mut socket = UdpSocket::bind(any_local_address()).unwrap();
+    let sender = net::UdpSocket::bind(any_local_address()).unwrap();
+    let address = socket.local_addr().unwrap();
+
+    os_queue.register(&mut socket, ID1, Interests::READABLE, RegisterOption::EDGE).unwrap();
+
+    let mut header = [0; 6];
+    let mut body = [0; 20];
+    assert_would_block(socket.recv_from_vectored(&mut [IoSliceMut::new(&mut header), IoSliceMut::new(&mut body)]));
+
+    sender.send_to(DATA1, address).unwrap();
+    sender.send_to(DATA2, address).unwrap();
+
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(ID1, Ready::READABLE),
+    ]);
+
+    let (n, source) = socket.recv_from_vectored(&mut [IoSliceMut::new(&mut header), IoSliceMut::new(&mut body)]).unwrap();
+    assert_eq!(n, DATA1.len());
+    assert_eq!(source, sender.local_addr().unwrap());
+    assert_eq!(&header, &DATA1[..6]);
+    assert_eq!(&body[..n - 6], &DATA1[6..]);
+
+    // Datagram larger then the buffers is truncated.
+    let mut small = [0; 4];
+    let (n, _) = socket.recv_from_vectored(&mut [IoSliceMut::new(&mut header), IoSliceMut::new(&mut small)]).unwrap();
+    assert_eq!(n, 10);
+    assert_eq!(&header, &DATA2[..6]);
+    assert_eq!(&small, &DATA2[6..10]);
+
+    // Drained the socket.
+    assert_would_block(socket.recv_from_vectored(&mut [IoSliceMut::new(&mut header)]));
+}
+
+#[test]
+fn udp_socket_recv_uninit() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    let mut socket = UdpSocket::bind(any_local_address()).unwrap();
+    let sender = net::UdpSocket::bind(any_local_address()).unwrap();
+    socket.connect(sender.local_addr().unwrap()).unwrap();
+    sender.connect(socket.local_addr().unwrap()).unwrap();
+
+    os_queue.register(&mut socket, ID1, Interests::READABLE, RegisterOption::EDGE).unwrap();
+
+    let mut buf: [MaybeUninit<u8>; 20] = unsafe { MaybeUninit::uninit().assume_init() };
+    assert_would_block(socket.recv_uninit(&mut buf));
+
+    sender.send(DATA1).unwrap();
+    sender.send(DATA2).unwrap();
+
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(ID1, Ready::READABLE),
+    ]);
+
+    let n = socket.recv_uninit(&mut buf).unwrap();
+    assert_eq!(unsafe { slice::from_raw_parts(buf.as_ptr() as *const u8, n) }, DATA1);
+    let n = socket.recv_uninit(&mut buf).unwrap();
+    assert_eq!(unsafe { slice::from_raw_parts(buf.as_ptr() as *const u8, n) }, DATA2);
+
+    assert_would_block(socket.recv_uninit(&mut buf));
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn udp_socket_bind_device() {
+    init();
+
+    let mut socket = UdpSocket::bind(any_local_address()).unwrap();
+    match socket.set_bind_device(Some("lo")) {
+        Ok(()) => {},
+        // Requires the `CAP_NET_RAW` capability.
+        Err(ref err) if err.kind() == io::ErrorKind::PermissionDenied => return,
+        Err(err) => panic!("unexpected error binding to device: {}", err),
+    }
+
+    let sender = net::UdpSocket::bind(any_local_address()).unwrap();
+    sender.send_to(DATA1, socket.local_addr().unwrap()).unwrap();
+    sleep(Duration::from_millis(10));
+    let mut buf = [0; 20];
+    let (n, _) = socket.recv_from(&mut buf).unwrap();
+    assert_eq!(&buf[..n], DATA1);
+
+    socket.set_bind_device(None).unwrap();
+    assert!(socket.set_bind_device(Some("not a valid interface")).is_err());
+}
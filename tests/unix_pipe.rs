@@ -69,3 +69,19 @@ fn sender_readable_interests() {
     os_queue.register(&mut sender, SENDER_ID, Interests::READABLE, RegisterOption::LEVEL)
         .unwrap();
 }
+
+#[test]
+fn unix_pipe_flags() {
+    use std::os::unix::io::AsRawFd;
+
+    let (sender, receiver) = new_pipe().expect("can't create pipe");
+    for fd in &[sender.as_raw_fd(), receiver.as_raw_fd()] {
+        let status_flags = unsafe { libc::fcntl(*fd, libc::F_GETFL) };
+        assert_ne!(status_flags, -1);
+        assert_ne!(status_flags & libc::O_NONBLOCK, 0, "pipe not in non-blocking mode");
+
+        let fd_flags = unsafe { libc::fcntl(*fd, libc::F_GETFD) };
+        assert_ne!(fd_flags, -1);
+        assert_ne!(fd_flags & libc::FD_CLOEXEC, 0, "pipe not in close-on-exec mode");
+    }
+}
@@ -0,0 +1,59 @@
+use std::io::{Read, Write};
+use std::os::unix::io::AsRawFd;
+use std::thread::sleep;
+use std::time::Duration;
+
+use gaea::event::{self, Event, Ready};
+use gaea::os::{Interests, RegisterOption};
+use gaea::unix::{new_pty, PtyMaster};
+
+mod util;
+
+use self::util::{assert_cloexec, assert_would_block, expect_events, expect_no_events, init_with_os_queue};
+
+const MASTER_ID: event::Id = event::Id(0);
+
+#[test]
+fn unix_pty_echo() {
+    let (mut os_queue, mut events) = init_with_os_queue();
+
+    let (mut master, mut slave) = new_pty().expect("can't create pty");
+    assert_cloexec(master.as_raw_fd());
+    assert_cloexec(slave.as_raw_fd());
+    assert!(slave.path().exists());
+
+    os_queue.register(&mut master, MASTER_ID, PtyMaster::INTERESTS, RegisterOption::EDGE)
+        .expect("can't register master");
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(MASTER_ID, Ready::WRITABLE),
+    ]);
+    // Master is in non-blocking mode.
+    let mut buf = [0; 32];
+    assert_would_block(master.read(&mut buf));
+
+    // Input is echoed by the terminal.
+    master.write_all(b"Hello\n").unwrap();
+    // Writing triggers a writable event, the echo is processed asynchronously.
+    sleep(Duration::from_millis(10));
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(MASTER_ID, Ready::READABLE),
+    ]);
+    let n = master.read(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"Hello\r\n");
+    assert_would_block(master.read(&mut buf));
+
+    let n = slave.read(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"Hello\n");
+
+    // Output of the slave side.
+    slave.write_all(b"world\n").unwrap();
+    expect_events(&mut os_queue, &mut events, vec![
+        Event::new(MASTER_ID, Ready::READABLE),
+    ]);
+    let n = master.read(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"world\r\n");
+
+    os_queue.reregister(&mut master, MASTER_ID, Interests::READABLE, RegisterOption::EDGE)
+        .expect("can't reregister master");
+    expect_no_events(&mut os_queue);
+}
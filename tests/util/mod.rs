@@ -61,7 +61,7 @@ pub fn expect_events<ES>(event_source: &mut ES, events: &mut Vec<Event>, mut exp
             expected.swap_remove(index);
         } else {
             // Must accept sporadic events.
-            warn!("got unexpected event: {:?}", event);
+            warn!("got unexpected event: {}", event);
         }
     }
 
@@ -120,3 +120,11 @@ pub fn any_local_address() -> SocketAddr {
 pub fn any_local_ipv6_address() -> SocketAddr {
     "[::1]:0".parse().unwrap()
 }
+
+/// Assert that the file descriptor has the close-on-exec flag set.
+#[cfg(unix)]
+pub fn assert_cloexec(fd: std::os::unix::io::RawFd) {
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+    assert_ne!(flags, -1, "unable to get file descriptor flags: {}", io::Error::last_os_error());
+    assert_ne!(flags & libc::FD_CLOEXEC, 0, "file descriptor not in close-on-exec mode");
+}